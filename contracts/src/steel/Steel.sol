@@ -49,6 +49,17 @@ library Steel {
         }
     }
 
+    /// @notice Validates the provided Commitment and that it was created with the expected network configuration.
+    /// @dev The `configID` is the digest of the chain spec used in the guest, e.g. `ETH_MAINNET_CHAIN_SPEC.digest()`.
+    ///      If the guest binds the fork using `EvmEnv::bind_spec_id`, it is the digest bound to that fork instead,
+    ///      e.g. `ETH_MAINNET_CHAIN_SPEC.spec_digest(SpecId::CANCUN)`.
+    /// @param commitment The Commitment struct to validate.
+    /// @param configID The digest of the expected network configuration.
+    /// @return True if the commitment is valid and matches the configuration, false otherwise.
+    function validateCommitment(Commitment memory commitment, bytes32 configID) internal view returns (bool) {
+        return commitment.configID == configID && validateCommitment(commitment);
+    }
+
     /// @notice Validates if the provided block commitment matches the block hash of the given block number.
     /// @param blockNumber The block number to compare against.
     /// @param blockHash The block hash to validate.
@@ -0,0 +1,55 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+pragma solidity ^0.8.13;
+
+import {Test} from "forge-std/Test.sol";
+
+import {Steel, Encoding} from "../src/steel/Steel.sol";
+
+contract SteelTest is Test {
+    bytes32 internal constant CONFIG_ID = keccak256("CONFIG_ID");
+    bytes32 internal constant OTHER_CONFIG_ID = keccak256("OTHER_CONFIG_ID");
+
+    /// @notice Returns a valid block commitment for the parent of the current block.
+    function blockCommitment(bytes32 configID) internal view returns (Steel.Commitment memory) {
+        uint256 blockNumber = block.number - 1;
+        return Steel.Commitment(Encoding.encodeVersionedID(uint240(blockNumber), 0), blockhash(blockNumber), configID);
+    }
+
+    function setUp() external {
+        vm.roll(100);
+    }
+
+    function test_ValidateCommitment_ConfigIDMatch() external view {
+        Steel.Commitment memory commitment = blockCommitment(CONFIG_ID);
+        assertTrue(Steel.validateCommitment(commitment));
+        assertTrue(Steel.validateCommitment(commitment, CONFIG_ID));
+    }
+
+    function test_ValidateCommitment_ConfigIDMismatch() external view {
+        Steel.Commitment memory commitment = blockCommitment(OTHER_CONFIG_ID);
+        // the commitment itself is valid, only the configuration does not match
+        assertTrue(Steel.validateCommitment(commitment));
+        assertFalse(Steel.validateCommitment(commitment, CONFIG_ID));
+    }
+
+    function test_ValidateCommitment_ConfigIDMatchInvalidDigest() external view {
+        Steel.Commitment memory commitment = blockCommitment(CONFIG_ID);
+        commitment.digest = ~commitment.digest;
+        assertFalse(Steel.validateCommitment(commitment, CONFIG_ID));
+    }
+}
@@ -30,6 +30,7 @@ sol! {
 sol! {
     struct APRCommitment {
         Commitment commitment;
+        uint64 blockTimestamp;
         uint64 annualSupplyRate;
     }
 }
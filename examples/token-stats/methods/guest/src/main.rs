@@ -15,7 +15,7 @@
 use alloy_sol_types::SolValue;
 use risc0_steel::{
     ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
-    Contract,
+    Contract, EvmBlockHeader,
 };
 use risc0_zkvm::guest::env;
 use token_stats_core::{APRCommitment, CometMainInterface, CONTRACT};
@@ -52,9 +52,13 @@ fn main() {
     // precision.
     let annual_supply_rate = supply_rate * SECONDS_PER_YEAR;
 
+    // The timestamp of the header is part of the proven block, so it can be committed as well.
+    let block_timestamp = env.header().timestamp();
+
     // This commits the APR at current utilization rate for this given block.
     let journal = APRCommitment {
         commitment: env.into_commitment(),
+        blockTimestamp: block_timestamp,
         annualSupplyRate: annual_supply_rate,
     };
     env::commit_slice(&journal.abi_encode());
@@ -0,0 +1,195 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{state::WrapStateDb, EvmBlockHeader, GuestEvmEnv};
+use alloy_primitives::{Address, B256, U256};
+use revm::{primitives::AccountInfo, Database};
+
+/// Represents an EVM account that is initialized with a specific environment and address.
+///
+/// This allows reading the balance, nonce, code hash and raw storage slots of an account in the
+/// committed block directly from the verified state, i.e. without executing any contract calls.
+/// Accounts that do not exist in the state are treated like empty accounts, exactly as by the EVM.
+///
+/// ### Usage
+/// - **Preflight on the Host:** To prepare the account access on the host environment and build the
+///   necessary proof, use [Account::preflight].
+/// - **Access in the Guest:** To initialize the account in the guest environment, use
+///   [Account::new]. The environment should be constructed using [EvmInput::into_env].
+///
+/// ### Examples
+/// ```rust,no_run
+/// # use risc0_steel::{ethereum::EthEvmEnv, Account};
+/// # use alloy_primitives::address;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> anyhow::Result<()> {
+/// let address = address!("F977814e90dA44bFA03b6295A0616a897441aceC");
+///
+/// // Host:
+/// let url = "https://ethereum-rpc.publicnode.com".parse()?;
+/// let mut env = EthEvmEnv::builder().rpc(url).build().await?;
+/// let mut account = Account::preflight(address, &mut env);
+/// let balance = account.balance().await?;
+///
+/// let evm_input = env.into_input().await?;
+///
+/// // Guest:
+/// let evm_env = evm_input.into_env();
+/// let account = Account::new(address, &evm_env);
+/// assert_eq!(account.balance(), balance);
+///
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [EvmInput::into_env]: crate::EvmInput::into_env
+pub struct Account<E> {
+    address: Address,
+    env: E,
+}
+
+impl<'a, H: EvmBlockHeader> Account<&'a GuestEvmEnv<H>> {
+    /// Constructor for accessing an account in the guest.
+    pub fn new(address: Address, env: &'a GuestEvmEnv<H>) -> Self {
+        Self { address, env }
+    }
+
+    /// Returns the verified [AccountInfo] of the account.
+    ///
+    /// Only the `balance`, `nonce` and `code_hash` fields are verified, the code itself must be
+    /// identified by its hash.
+    pub fn info(&self) -> AccountInfo {
+        // safe unwrap: the StateDb never returns an error
+        let info = WrapStateDb::new(self.env.db()).basic(self.address).unwrap();
+        info.unwrap_or_default()
+    }
+
+    /// Returns the verified balance of the account.
+    pub fn balance(&self) -> U256 {
+        self.info().balance
+    }
+
+    /// Returns the verified nonce of the account.
+    pub fn nonce(&self) -> u64 {
+        self.info().nonce
+    }
+
+    /// Returns the verified code hash of the account.
+    pub fn code_hash(&self) -> B256 {
+        self.info().code_hash
+    }
+
+    /// Returns the verified value of the storage slot `index` of the account.
+    ///
+    /// This reads the raw value of the slot, e.g. of a private variable or packed struct without a
+    /// getter, and is verified against the storage root of the account exactly like any storage
+    /// accessed during a call. Slots that were never written are zero.
+    pub fn storage(&self, index: U256) -> U256 {
+        let mut db = WrapStateDb::new(self.env.db());
+        // the account must be queried first to link its storage trie
+        // safe unwraps: the StateDb never returns an error
+        db.basic(self.address).unwrap();
+        db.storage(self.address, index).unwrap()
+    }
+}
+
+#[cfg(feature = "host")]
+mod host {
+    use super::*;
+    use crate::host::{db::AlloyDb, HostEvmEnv};
+    use alloy::{network::Network, providers::Provider, transports::Transport};
+    use anyhow::{Context, Result};
+
+    impl<'a, D, H, C> Account<&'a mut HostEvmEnv<D, H, C>> {
+        /// Constructor for preflighting the account access on the host.
+        ///
+        /// Initializes the environment for accessing the account, fetching necessary data via the
+        /// [Provider], and generating an account proof for it using [EvmEnv::into_input].
+        ///
+        /// [EvmEnv::into_input]: crate::EvmEnv::into_input
+        pub fn preflight(address: Address, env: &'a mut HostEvmEnv<D, H, C>) -> Self {
+            Self { address, env }
+        }
+    }
+
+    impl<'a, T, N, P, H, C> Account<&'a mut HostEvmEnv<AlloyDb<T, N, P>, H, C>>
+    where
+        T: Transport + Clone,
+        N: Network,
+        P: Provider<T, N> + Send + 'static,
+    {
+        /// Fetches the [AccountInfo] of the account and records it for the proof.
+        ///
+        /// Only the `balance`, `nonce` and `code_hash` fields are verified in the guest.
+        ///
+        /// This uses [tokio::task::spawn_blocking] to run the blocking database access.
+        pub async fn info(&mut self) -> Result<AccountInfo> {
+            log::info!("Executing preflight accessing account {}", self.address);
+
+            // as mutable references are not possible, the DB must be moved in and out of the task
+            let mut db = self.env.db.take().unwrap();
+
+            let address = self.address;
+            let (result, db) = tokio::task::spawn_blocking(move || (db.basic(address), db))
+                .await
+                .expect("DB access panicked");
+
+            // restore the DB before handling errors, so that we never return an env without a DB
+            self.env.db = Some(db);
+
+            let info = result.with_context(|| format!("failed to get account {}", address))?;
+            Ok(info.unwrap_or_default())
+        }
+
+        /// Fetches the balance of the account and records it for the proof.
+        pub async fn balance(&mut self) -> Result<U256> {
+            Ok(self.info().await?.balance)
+        }
+
+        /// Fetches the nonce of the account and records it for the proof.
+        pub async fn nonce(&mut self) -> Result<u64> {
+            Ok(self.info().await?.nonce)
+        }
+
+        /// Fetches the code hash of the account and records it for the proof.
+        pub async fn code_hash(&mut self) -> Result<B256> {
+            Ok(self.info().await?.code_hash)
+        }
+
+        /// Fetches the value of the storage slot `index` of the account and records it for the
+        /// proof.
+        ///
+        /// This uses [tokio::task::spawn_blocking] to run the blocking database access.
+        pub async fn storage(&mut self, index: U256) -> Result<U256> {
+            log::info!(
+                "Executing preflight accessing slot {} of account {}",
+                index,
+                self.address
+            );
+
+            let mut db = self.env.db.take().unwrap();
+
+            let address = self.address;
+            let (result, db) =
+                tokio::task::spawn_blocking(move || (db.storage(address, index), db))
+                    .await
+                    .expect("DB access panicked");
+
+            self.env.db = Some(db);
+
+            result.with_context(|| format!("failed to get slot {} of account {}", index, address))
+        }
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Type aliases and specifications for Arbitrum One.
+//!
+//! Arbitrum uses the same block header format as Ethereum, so the block hash can be computed as
+//! usual. However, some fields are repurposed: The `mixHash` encodes the number of the
+//! corresponding L1 block and the ArbOS version, and the `extraData` contains the send root. The
+//! EVM also behaves differently: The `NUMBER` opcode returns the L1 block number, and
+//! `DIFFICULTY`/`PREVRANDAO` always return 1. The [ArbBlockHeader] takes care of these differences.
+//!
+//! **Note:** Only blocks produced with ArbOS 20 ("Atlas") or later are supported, since this is
+//! the first version that corresponds to the Cancun fork. Furthermore, the following Arbitrum
+//! specific features are not supported:
+//! - The `BLOCKHASH` opcode, as it refers to the L1 block number on Arbitrum.
+//! - The ArbOS precompiles, e.g. `ArbSys` at address `0x64`.
+//! - Stylus contracts, as they are not executed by revm.
+//!
+//! Since `block.number` and `blockhash` also refer to L1 in Solidity, a commitment to an Arbitrum
+//! block cannot be validated with `Steel.validateCommitment` on Arbitrum itself. Instead, the
+//! block hash must be compared against `ArbSys.arbBlockHash`.
+use std::{collections::BTreeMap, ops::Deref, sync::LazyLock};
+
+use crate::{
+    config::{ChainSpec, ForkCondition},
+    serde::RlpHeader,
+    EvmBlockHeader, EvmEnv, EvmInput,
+};
+use alloy_primitives::{BlockNumber, B256, U256};
+use alloy_rlp::{BufMut, Decodable, Encodable};
+use anyhow::ensure;
+use revm::primitives::{BlockEnv, SpecId};
+
+/// The minimum ArbOS version supported by the [ArbBlockHeader], i.e. ArbOS 20 ("Atlas").
+pub const MIN_ARBOS_VERSION: u64 = 20;
+
+/// The block number of the Nitro genesis on Arbitrum One.
+///
+/// Blocks before were produced by Arbitrum Classic and cannot be used.
+pub const ARB_ONE_NITRO_GENESIS_BLOCK: BlockNumber = 22207817;
+
+/// The Arbitrum One [ChainSpec].
+///
+/// As the [ArbBlockHeader] only supports blocks produced with [MIN_ARBOS_VERSION] or later, which
+/// enables the Cancun EVM, this is the only required fork.
+pub static ARB_ONE_CHAIN_SPEC: LazyLock<ChainSpec> = LazyLock::new(|| ChainSpec {
+    chain_id: 42161,
+    forks: BTreeMap::from([(
+        SpecId::CANCUN,
+        ForkCondition::Block(ARB_ONE_NITRO_GENESIS_BLOCK),
+    )]),
+});
+
+/// [EvmEnv] for Arbitrum.
+pub type ArbEvmEnv<D, C> = EvmEnv<D, ArbBlockHeader, C>;
+
+/// [EvmInput] for Arbitrum.
+pub type ArbEvmInput = EvmInput<ArbBlockHeader>;
+
+/// [EvmBlockHeader] for Arbitrum.
+pub type ArbBlockHeader = RlpHeader<ArbHeader>;
+
+/// An Arbitrum block header.
+///
+/// This is an Ethereum header, whose `mixHash` contains the Arbitrum specific block info. It can
+/// only be created for headers with an ArbOS version of at least [MIN_ARBOS_VERSION].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbHeader(alloy_consensus::Header);
+
+impl ArbHeader {
+    /// Returns the number of the L1 block that corresponds to this block.
+    #[inline]
+    pub fn l1_block_number(&self) -> BlockNumber {
+        mix_hash_u64(&self.0.mix_hash, 1)
+    }
+
+    /// Returns the ArbOS version that produced this block.
+    #[inline]
+    pub fn arbos_version(&self) -> u64 {
+        mix_hash_u64(&self.0.mix_hash, 2)
+    }
+
+    /// Returns the underlying Ethereum header.
+    #[inline]
+    pub fn inner(&self) -> &alloy_consensus::Header {
+        &self.0
+    }
+}
+
+impl Deref for ArbHeader {
+    type Target = alloy_consensus::Header;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl TryFrom<alloy_consensus::Header> for ArbHeader {
+    type Error = anyhow::Error;
+
+    fn try_from(header: alloy_consensus::Header) -> anyhow::Result<Self> {
+        let header = ArbHeader(header);
+        ensure!(
+            header.arbos_version() >= MIN_ARBOS_VERSION,
+            "unsupported ArbOS version {}: at least {} required",
+            header.arbos_version(),
+            MIN_ARBOS_VERSION
+        );
+
+        Ok(header)
+    }
+}
+
+impl Encodable for ArbHeader {
+    #[inline]
+    fn encode(&self, out: &mut dyn BufMut) {
+        self.0.encode(out)
+    }
+
+    #[inline]
+    fn length(&self) -> usize {
+        self.0.length()
+    }
+}
+
+impl Decodable for ArbHeader {
+    #[inline]
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let header = alloy_consensus::Header::decode(buf)?;
+        header
+            .try_into()
+            .map_err(|_| alloy_rlp::Error::Custom("unsupported ArbOS version"))
+    }
+}
+
+impl EvmBlockHeader for ArbBlockHeader {
+    #[inline]
+    fn parent_hash(&self) -> &B256 {
+        &self.inner().parent_hash
+    }
+    #[inline]
+    fn number(&self) -> BlockNumber {
+        self.inner().number
+    }
+    #[inline]
+    fn timestamp(&self) -> u64 {
+        self.inner().timestamp
+    }
+    #[inline]
+    fn state_root(&self) -> &B256 {
+        &self.inner().state_root
+    }
+
+    #[inline]
+    fn fill_block_env(&self, blk_env: &mut BlockEnv) {
+        let header = self.inner();
+
+        // the NUMBER opcode returns the L1 block number on Arbitrum
+        blk_env.number = U256::from(header.l1_block_number());
+        blk_env.coinbase = header.beneficiary;
+        blk_env.timestamp = U256::from(header.timestamp);
+        blk_env.gas_limit = U256::from(header.gas_limit);
+        blk_env.basefee = U256::from(header.base_fee_per_gas.unwrap_or_default());
+        // DIFFICULTY and PREVRANDAO always return 1 on Arbitrum
+        blk_env.difficulty = U256::from(1);
+        blk_env.prevrandao = Some(U256::from(1).into());
+        if let Some(excess_blob_gas) = header.excess_blob_gas {
+            blk_env.set_blob_excess_gas_and_price(excess_blob_gas)
+        };
+    }
+}
+
+/// Returns the `index`-th big-endian u64 encoded in the `mixHash` of an Arbitrum header.
+#[inline]
+fn mix_hash_u64(mix_hash: &B256, index: usize) -> u64 {
+    let bytes = &mix_hash[index * 8..(index + 1) * 8];
+    // safe unwrap: the slice always has a length of 8
+    u64::from_be_bytes(bytes.try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+
+    fn header(mix_hash: B256) -> alloy_consensus::Header {
+        alloy_consensus::Header {
+            number: 250000000,
+            mix_hash,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn block_env() {
+        // sendCount = 1, L1 block number = 20000000, ArbOS version = 31
+        let mix_hash = b256!("00000000000000010000000001312d00000000000000001f0000000000000000");
+        let header = RlpHeader::new(ArbHeader::try_from(header(mix_hash)).unwrap());
+        assert_eq!(header.l1_block_number(), 20000000);
+        assert_eq!(header.arbos_version(), 31);
+
+        let mut blk_env = BlockEnv::default();
+        header.fill_block_env(&mut blk_env);
+        assert_eq!(header.number(), 250000000);
+        assert_eq!(blk_env.number, U256::from(20000000));
+        assert_eq!(blk_env.prevrandao, Some(U256::from(1).into()));
+    }
+
+    #[test]
+    fn unsupported_arbos_version() {
+        // ArbOS version = 11
+        let mix_hash = b256!("00000000000000010000000001312d00000000000000000b0000000000000000");
+        assert!(ArbHeader::try_from(header(mix_hash)).is_err());
+
+        // the version must also be checked when decoding the header, e.g. in the guest
+        let rlp = alloy_rlp::encode(header(mix_hash));
+        assert!(ArbHeader::decode(&mut rlp.as_slice()).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "queries actual RPC nodes"]
+    async fn view_call() {
+        use crate::Contract;
+        use alloy::providers::ProviderBuilder;
+
+        alloy_sol_types::sol! {
+            interface IERC20 {
+                function totalSupply() external view returns (uint256);
+            }
+        }
+        // native USDC on Arbitrum One
+        let usdc = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
+        let provider =
+            ProviderBuilder::new().on_http("https://arb1.arbitrum.io/rpc".parse().unwrap());
+
+        let mut env = ArbEvmEnv::builder()
+            .provider(provider)
+            .build()
+            .await
+            .unwrap()
+            .with_chain_spec(&ARB_ONE_CHAIN_SPEC);
+        let block_hash = env.header().seal();
+        let preflight = Contract::preflight(usdc, &mut env)
+            .call_builder(&IERC20::totalSupplyCall {})
+            .call()
+            .await
+            .unwrap();
+        let input = env.into_input().await.unwrap();
+
+        let env = input.into_env().with_chain_spec(&ARB_ONE_CHAIN_SPEC);
+        let result = Contract::new(usdc, &env)
+            .call_builder(&IERC20::totalSupplyCall {})
+            .call();
+        assert_eq!(result._0, preflight._0);
+        // the commitment must bind to the L2 block hash
+        assert_eq!(env.commitment().digest, block_hash);
+    }
+}
@@ -17,6 +17,7 @@ use crate::{
     merkle, BlockHeaderCommit, Commitment, CommitmentVersion, ComposeInput, EvmBlockHeader,
 };
 use alloy_primitives::{Sealed, B256};
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 
 /// The generalized Merkle tree index of the `state_root` field in the `BeaconBlock`.
@@ -113,6 +114,19 @@ impl<H: EvmBlockHeader, const LEAF_INDEX: usize> BlockHeaderCommit<H>
             config_id,
         )
     }
+
+    #[inline]
+    fn try_commit(self, header: &Sealed<H>, config_id: B256) -> anyhow::Result<Commitment> {
+        let beacon_root = self
+            .process_proof(header.seal())
+            .context("invalid beacon inclusion proof")?;
+        Ok(Commitment::new(
+            CommitmentVersion::Beacon as u16,
+            self.timestamp,
+            beacon_root,
+            config_id,
+        ))
+    }
 }
 
 #[cfg(feature = "host")]
@@ -132,9 +146,65 @@ pub(crate) mod host {
         types::SignedBeaconBlock,
         Fork,
     };
+    use lru::LruCache;
     use proofs::ProofAndWitness;
+    use std::{
+        fmt,
+        num::NonZeroUsize,
+        sync::{Arc, Mutex},
+    };
     use url::Url;
 
+    /// Cache of the execution payload proofs derived from the Beacon API.
+    ///
+    /// Beacon blocks are identified by their root, so a cached proof can never become stale. This
+    /// avoids downloading the same beacon block again when creating many inputs for nearby blocks.
+    /// Clones share the same entries, so that all environments built from the same
+    /// [EvmEnvBuilder] use the same cache. Once the capacity is reached, the least recently used
+    /// proof is evicted.
+    ///
+    /// [EvmEnvBuilder]: crate::host::EvmEnvBuilder
+    #[derive(Clone)]
+    pub(crate) struct ProofCache(Arc<Mutex<ProofCacheEntries>>);
+
+    /// The cached proofs and slots, keyed by the beacon root and the leaf index.
+    type ProofCacheEntries = LruCache<(B256, usize), (Vec<B256>, u64)>;
+
+    impl ProofCache {
+        /// Default maximum number of cached proofs.
+        const DEFAULT_CAPACITY: usize = 1024;
+
+        fn new(capacity: usize) -> Self {
+            let capacity = NonZeroUsize::new(capacity).expect("capacity must not be zero");
+            Self(Arc::new(Mutex::new(LruCache::new(capacity))))
+        }
+
+        /// Returns the cached proof and slot, marking the entry as recently used.
+        fn get(&self, beacon_root: B256, leaf_index: usize) -> Option<(Vec<B256>, u64)> {
+            let mut cache = self.0.lock().unwrap();
+            cache.get(&(beacon_root, leaf_index)).cloned()
+        }
+
+        /// Inserts a proof, evicting the least recently used entry if the capacity is reached.
+        fn insert(&self, beacon_root: B256, leaf_index: usize, proof: Vec<B256>, slot: u64) {
+            let mut cache = self.0.lock().unwrap();
+            cache.put((beacon_root, leaf_index), (proof, slot));
+        }
+    }
+
+    impl Default for ProofCache {
+        fn default() -> Self {
+            Self::new(Self::DEFAULT_CAPACITY)
+        }
+    }
+
+    impl fmt::Debug for ProofCache {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let len = self.0.lock().unwrap().len();
+            f.debug_struct("ProofCache").field("len", &len).finish()
+        }
+    }
+
     pub(crate) mod client {
         use ethereum_consensus::{
             phase0::SignedBeaconBlockHeader, primitives::Root, types::mainnet::SignedBeaconBlock,
@@ -224,40 +294,56 @@ pub(crate) mod host {
     impl BeaconCommit {
         /// Creates a new `BeaconCommit` for the provided header which proofs the inclusion of the
         /// corresponding block hash in the referenced beacon block.
+        ///
+        /// It also returns the slot of the referenced beacon block. As this block is identified by
+        /// its root, missed slots on the beacon chain do not affect the commitment; the returned
+        /// slot is simply the one of the beacon block containing the execution block.
         pub(crate) async fn from_header<T, P>(
             header: &Sealed<EthBlockHeader>,
             rpc_provider: P,
             beacon_url: Url,
-        ) -> anyhow::Result<Self>
+            cache: &ProofCache,
+        ) -> anyhow::Result<(Self, u64)>
         where
             T: Transport + Clone,
             P: Provider<T, Ethereum>,
         {
             let client = BeaconClient::new(beacon_url).context("invalid URL")?;
-            let (commit, beacon_root) =
-                create_beacon_commit(header, "block_hash".into(), rpc_provider, &client).await?;
+            let (commit, beacon_root, slot) =
+                create_beacon_commit(header, "block_hash".into(), rpc_provider, &client, cache)
+                    .await?;
             commit
                 .verify(header.seal(), beacon_root)
                 .context("proof derived from API does not verify")?;
 
             log::info!(
-                "Committing to parent beacon block: root={},timestamp={}",
+                "Committing to parent beacon block: root={},slot={},timestamp={}",
                 beacon_root,
+                slot,
                 commit.timestamp()
             );
 
-            Ok(commit)
+            Ok((commit, slot))
         }
     }
 
     /// Creates a beacon commitment that `field` is contained in the `ExecutionPayload` of the
     /// beacon block corresponding to `header`.
+    ///
+    /// The beacon block is determined by the `parent_beacon_block_root` of the child of `header`,
+    /// which always references the last beacon block containing an execution payload, even if
+    /// the subsequent slots have been missed. Returns the commitment together with the root and
+    /// the slot of that beacon block.
+    ///
+    /// The proof derived from the Beacon API is stored in the `cache`, so repeated commitments to
+    /// the same beacon block only query the execution layer RPC for the child header.
     pub(crate) async fn create_beacon_commit<T, P, H, const LEAF_INDEX: usize>(
         header: &Sealed<H>,
         field: PathElement,
         rpc_provider: P,
         beacon_client: &BeaconClient,
-    ) -> anyhow::Result<(GeneralizedBeaconCommit<LEAF_INDEX>, B256)>
+        cache: &ProofCache,
+    ) -> anyhow::Result<(GeneralizedBeaconCommit<LEAF_INDEX>, B256, u64)>
     where
         T: Transport + Clone,
         P: Provider<T, Ethereum>,
@@ -286,32 +372,44 @@ pub(crate) mod host {
         let beacon_root = child
             .parent_beacon_block_root
             .context("parent_beacon_block_root missing in execution header")?;
-        let proof = create_execution_payload_proof(field, beacon_root, beacon_client).await?;
-        ensure!(proof.index == LEAF_INDEX, "field has the wrong leaf index");
+        let cached = cache.get(beacon_root, LEAF_INDEX);
+        let (proof, slot) = match cached {
+            Some(entry) => {
+                log::debug!("Using cached proof for beacon block {}", beacon_root);
+                entry
+            }
+            None => {
+                let (proof, slot) =
+                    create_execution_payload_proof(field, beacon_root, beacon_client).await?;
+                ensure!(proof.index == LEAF_INDEX, "field has the wrong leaf index");
+                let proof: Vec<B256> = proof.branch.iter().map(|n| n.0.into()).collect();
+                cache.insert(beacon_root, LEAF_INDEX, proof.clone(), slot);
+                (proof, slot)
+            }
+        };
 
-        let commit = GeneralizedBeaconCommit::new(
-            proof.branch.iter().map(|n| n.0.into()).collect(),
-            child.timestamp,
-        );
+        let commit = GeneralizedBeaconCommit::new(proof, child.timestamp);
 
-        Ok((commit, beacon_root))
+        Ok((commit, beacon_root, slot))
     }
 
     /// Creates the Merkle inclusion proof of the element `field` in the `ExecutionPayload` of the
-    /// beacon block with the given `beacon_root`.
+    /// beacon block with the given `beacon_root` and returns it together with the block's slot.
     async fn create_execution_payload_proof(
         field: PathElement,
         beacon_root: B256,
         client: &BeaconClient,
-    ) -> anyhow::Result<Proof> {
+    ) -> anyhow::Result<(Proof, u64)> {
         let signed_beacon_block = client
             .get_block(beacon_root)
             .await
             .with_context(|| format!("failed to get block {}", beacon_root))?;
         // create the inclusion proof of the execution block hash depending on the fork version
-        let (proof, _) = match signed_beacon_block {
+        let (proof, slot) = match signed_beacon_block {
             SignedBeaconBlock::Deneb(signed_block) => {
-                prove_execution_payload_field(signed_block.message, field)?
+                let slot = signed_block.message.slot;
+                let (proof, _) = prove_execution_payload_field(signed_block.message, field)?;
+                (proof, slot)
             }
             _ => {
                 bail!(
@@ -323,7 +421,7 @@ pub(crate) mod host {
             }
         };
 
-        Ok(proof)
+        Ok((proof, slot))
     }
 
     /// Creates the Merkle inclusion proof of the element `field` in the `ExecutionPayload` in the
@@ -358,12 +456,43 @@ pub(crate) mod host {
             let beacon_root = block.header().parent_beacon_block_root.unwrap();
 
             let block_hash = block.header().parent_hash;
-            let proof =
+            let (proof, slot) =
                 super::create_execution_payload_proof("block_hash".into(), beacon_root, &cl)
                     .await
                     .expect("proving 'block_hash' failed");
+            assert!(slot > 0);
             let branch: Vec<B256> = proof.branch.iter().map(|n| n.0.into()).collect();
             merkle::verify(block_hash, &branch, BLOCK_HASH_LEAF_INDEX, beacon_root).unwrap();
         }
+
+        #[test]
+        fn proof_cache() {
+            let cache = ProofCache::new(2);
+            let (a, b, c) = (
+                B256::repeat_byte(0xa),
+                B256::repeat_byte(0xb),
+                B256::repeat_byte(0xc),
+            );
+
+            cache.insert(a, BLOCK_HASH_LEAF_INDEX, vec![a], 1);
+            assert_eq!(cache.get(a, BLOCK_HASH_LEAF_INDEX), Some((vec![a], 1)));
+            // entries are specific to the leaf index
+            assert_eq!(cache.get(a, STATE_ROOT_LEAF_INDEX), None);
+
+            cache.insert(b, BLOCK_HASH_LEAF_INDEX, vec![b], 2);
+            assert_eq!(cache.get(b, BLOCK_HASH_LEAF_INDEX), Some((vec![b], 2)));
+
+            // exceeding the capacity evicts only the least recently used entry
+            assert!(cache.get(a, BLOCK_HASH_LEAF_INDEX).is_some());
+            cache.insert(c, BLOCK_HASH_LEAF_INDEX, vec![c], 3);
+            assert_eq!(cache.0.lock().unwrap().len(), 2);
+            assert_eq!(cache.get(a, BLOCK_HASH_LEAF_INDEX), Some((vec![a], 1)));
+            assert_eq!(cache.get(b, BLOCK_HASH_LEAF_INDEX), None);
+            assert_eq!(cache.get(c, BLOCK_HASH_LEAF_INDEX), Some((vec![c], 3)));
+
+            // clones share the same entries
+            cache.clone().insert(b, BLOCK_HASH_LEAF_INDEX, vec![b], 2);
+            assert_eq!(cache.get(b, BLOCK_HASH_LEAF_INDEX), Some((vec![b], 2)));
+        }
     }
 }
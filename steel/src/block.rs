@@ -12,12 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::{btree_map, BTreeMap};
+
 use crate::{
     config::ChainSpec, state::StateDb, Commitment, CommitmentVersion, EvmBlockHeader, EvmEnv,
-    GuestEvmEnv, MerkleTrie,
+    EvmInputParts, GuestEvmEnv, MerkleTrie,
 };
 use ::serde::{Deserialize, Serialize};
-use alloy_primitives::{map::HashMap, Bytes};
+use alloy_primitives::{keccak256, map::HashMap, Bytes, B256};
+use anyhow::ensure;
 
 /// Input committing to the corresponding execution block hash.
 #[derive(Clone, Serialize, Deserialize)]
@@ -29,7 +32,127 @@ pub struct BlockInput<H> {
     ancestors: Vec<H>,
 }
 
+impl<H> BlockInput<H> {
+    /// Creates a new `BlockInput` from its parts.
+    pub(crate) fn from_parts(parts: EvmInputParts<H>) -> Self {
+        Self {
+            header: parts.header,
+            state_trie: parts.state_trie,
+            storage_tries: parts.storage_tries,
+            contracts: parts.contracts,
+            ancestors: parts.ancestors,
+        }
+    }
+
+    /// Disassembles this `BlockInput` into its parts, without any commitment.
+    pub(crate) fn into_parts(self) -> EvmInputParts<H> {
+        EvmInputParts {
+            header: self.header,
+            state_trie: self.state_trie,
+            storage_tries: self.storage_tries,
+            contracts: self.contracts,
+            ancestors: self.ancestors,
+            beacon_commit: None,
+        }
+    }
+}
+
 impl<H: EvmBlockHeader> BlockInput<H> {
+    /// Returns the header of the block.
+    #[inline]
+    pub(crate) fn header(&self) -> &H {
+        &self.header
+    }
+
+    /// Merges the state of another input for the same block into this input.
+    ///
+    /// This returns an error if the two inputs do not correspond to the same block or contain
+    /// conflicting data.
+    pub fn merge(self, other: Self) -> anyhow::Result<Self> {
+        ensure!(
+            self.header.hash_slow() == other.header.hash_slow(),
+            "block hash mismatch"
+        );
+        let state_root = self.header.state_root();
+        ensure!(
+            &self.state_trie.hash_slow() == state_root
+                && &other.state_trie.hash_slow() == state_root,
+            "state root mismatch"
+        );
+        let state_trie = self.state_trie.merge(other.state_trie);
+
+        // use ordered maps, so that the merged input has a canonical order
+        let mut storage_tries = BTreeMap::<B256, MerkleTrie>::new();
+        for trie in self.storage_tries.into_iter().chain(other.storage_tries) {
+            match storage_tries.entry(trie.hash_slow()) {
+                btree_map::Entry::Occupied(mut entry) => {
+                    let existing = std::mem::take(entry.get_mut());
+                    *entry.get_mut() = existing.merge(trie);
+                }
+                btree_map::Entry::Vacant(entry) => {
+                    entry.insert(trie);
+                }
+            }
+        }
+
+        let contracts: BTreeMap<B256, Bytes> = self
+            .contracts
+            .into_iter()
+            .chain(other.contracts)
+            .map(|code| (keccak256(&code), code))
+            .collect();
+
+        // both ancestor chains start with the parent, so the shorter one must be a prefix
+        let (ancestors, shorter) = if self.ancestors.len() >= other.ancestors.len() {
+            (self.ancestors, other.ancestors)
+        } else {
+            (other.ancestors, self.ancestors)
+        };
+        ensure!(
+            shorter
+                .iter()
+                .zip(&ancestors)
+                .all(|(a, b)| a.hash_slow() == b.hash_slow()),
+            "ancestor mismatch"
+        );
+
+        Ok(BlockInput {
+            header: self.header,
+            state_trie,
+            storage_tries: storage_tries.into_values().collect(),
+            contracts: contracts.into_values().collect(),
+            ancestors,
+        })
+    }
+
+    /// Verifies the internal consistency of the input without executing any calls.
+    ///
+    /// This performs the same checks as [BlockInput::into_env], i.e. that the state trie matches
+    /// the state root of the header and that the ancestors form a valid chain, but returns an
+    /// error instead of panicking.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let state_root = self.state_trie.hash_slow();
+        ensure!(
+            self.header.state_root() == &state_root,
+            "state root mismatch: header {}, state trie {}",
+            self.header.state_root(),
+            state_root
+        );
+
+        let mut previous_header = &self.header;
+        for ancestor in &self.ancestors {
+            ensure!(
+                previous_header.parent_hash() == &ancestor.hash_slow(),
+                "invalid ancestor chain: block {} is not the parent of block {}",
+                ancestor.number(),
+                previous_header.number()
+            );
+            previous_header = ancestor;
+        }
+
+        Ok(())
+    }
+
     /// Converts the input into a [EvmEnv] for verifiable state access in the guest.
     pub fn into_env(self) -> GuestEvmEnv<H> {
         // verify that the state root matches the state trie
@@ -77,22 +200,84 @@ impl<H: EvmBlockHeader> BlockInput<H> {
 
 #[cfg(feature = "host")]
 pub mod host {
-    use std::fmt::Display;
+    use std::{collections::BTreeMap, fmt::Display};
 
     use super::BlockInput;
     use crate::{
-        host::db::{AlloyDb, ProofDb, ProviderDb},
-        EvmBlockHeader,
+        host::db::{AlloyDb, InMemoryDb, ProofDb, ProviderDb},
+        EvmBlockHeader, EvmInput, MerkleTrie,
     };
     use alloy::{network::Network, providers::Provider, transports::Transport};
     use alloy_primitives::Sealed;
     use anyhow::{anyhow, ensure};
     use log::debug;
+    use serde::Serialize;
 
     impl<H: EvmBlockHeader> BlockInput<H> {
+        /// Creates the `BlockInput` from an externally provided state snapshot for the given
+        /// header.
+        ///
+        /// This allows proving against a block for which no RPC node is available, e.g. the
+        /// genesis state of a local devnet. As the state trie is rebuilt from the supplied
+        /// values, the snapshot must contain the complete state, i.e. every account and every
+        /// non-zero storage slot. Otherwise, its root does not match the `stateRoot` of the
+        /// header, and an error is returned. Thus, the resulting input is verified in the guest
+        /// exactly like an input created during preflight, and an incorrect snapshot can never
+        /// result in a valid proof for the header. The input does not contain any ancestors, so
+        /// the `BLOCKHASH` opcode cannot be used.
+        ///
+        /// Only full, devnet-sized states are supported: partial tries built from proof nodes
+        /// cannot be supplied, and the complete state is included in the input. This makes it
+        /// unsuitable for the state of public networks like Ethereum mainnet.
+        pub fn from_state(header: H, state: InMemoryDb) -> anyhow::Result<Self> {
+            state.into_block_input(header)
+        }
+
         /// Creates the `BlockInput` containing the necessary EVM state that can be verified against
         /// the block hash.
+        ///
+        /// If [ProviderConfig::verify_determinism] is set, the input is built a second time from
+        /// the same recorded accesses, fetching all the data again, and an error is returned if
+        /// the two inputs are not byte-identical.
+        ///
+        /// [ProviderConfig::verify_determinism]: crate::host::db::ProviderConfig::verify_determinism
         pub(crate) async fn from_proof_db<T, N, P>(
+            db: ProofDb<AlloyDb<T, N, P>>,
+            header: Sealed<H>,
+        ) -> anyhow::Result<Self>
+        where
+            T: Transport + Clone,
+            N: Network,
+            P: Provider<T, N>,
+            H: EvmBlockHeader + TryFrom<<N as Network>::HeaderResponse> + Clone + Serialize,
+            <H as TryFrom<<N as Network>::HeaderResponse>>::Error: Display,
+        {
+            if !db.inner().config().verify_determinism {
+                return Self::build(db, header).await;
+            }
+
+            let replayed = Self::build(db.replay(), header.clone()).await?;
+            let input = Self::build(db, header).await?;
+            input.ensure_identical(replayed)?;
+
+            Ok(input)
+        }
+
+        /// Returns an error if the other input, built from the same preflight, is not
+        /// byte-identical to this input when encoded with [EvmInput::to_bytes].
+        pub(crate) fn ensure_identical(&self, other: Self) -> anyhow::Result<()>
+        where
+            H: Clone + Serialize,
+        {
+            ensure!(
+                EvmInput::Block(self.clone()).to_bytes()? == EvmInput::Block(other).to_bytes()?,
+                "nondeterministic preflight: building the input twice resulted in different bytes"
+            );
+
+            Ok(())
+        }
+
+        async fn build<T, N, P>(
             mut db: ProofDb<AlloyDb<T, N, P>>,
             header: Sealed<H>,
         ) -> anyhow::Result<Self>
@@ -105,14 +290,37 @@ pub mod host {
         {
             assert_eq!(db.inner().block_hash(), header.seal(), "DB block mismatch");
 
-            let (state_trie, storage_tries) = db.state_proof().await?;
-            ensure!(
-                header.state_root() == &state_trie.hash_slow(),
-                "accountProof root does not match header's stateRoot"
-            );
+            let (mut state_trie, mut storage_tries) = db.state_proof().await?;
+            // without any accessed accounts, e.g. when only the block hash is committed, the state
+            // trie does not need to contain anything but its root
+            if db.accounts().is_empty() {
+                state_trie = MerkleTrie::from_digest(*header.state_root());
+            }
+            if db.inner().config().trust_rpc_state {
+                log::warn!("Skipping the validation of the proofs: the input might be unprovable");
+            } else {
+                // chains that do not commit to their state using a Merkle Patricia trie (e.g.
+                // Linea, which uses a sparse Merkle tree, or Scroll before its switch to an MPT)
+                // return proofs that cannot be verified against the stateRoot of the header
+                ensure!(
+                    header.state_root() == &state_trie.hash_slow(),
+                    "accountProof root does not match header's stateRoot: \
+                    the chain might not use a Merkle Patricia trie for its state"
+                );
+                // some providers return proofs with missing intermediate nodes, which would cause
+                // the guest to panic when accessing the corresponding state
+                if db.recover_proofs(*header.state_root()).await? {
+                    (state_trie, storage_tries) = db.build_tries()?;
+                }
+                if db.inner().config().verify_eip1186_proofs {
+                    db.verify_proofs(*header.state_root())?;
+                }
+            }
 
-            // collect the bytecode of all referenced contracts
-            let contracts: Vec<_> = db.contracts().values().cloned().collect();
+            // collect the bytecode of all referenced contracts, ordered by their hash so that
+            // identical preflights always result in identical inputs
+            let contracts: BTreeMap<_, _> = db.contracts().iter().collect();
+            let contracts: Vec<_> = contracts.into_values().cloned().collect();
 
             // retrieve ancestor block headers
             let mut ancestors = Vec::new();
@@ -144,3 +352,137 @@ pub mod host {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{beacon::BeaconCommit, ethereum::EthBlockHeader, serde::RlpHeader, ComposeInput};
+
+    /// Returns a trie consisting of a single leaf with the given value for the zero key.
+    fn leaf_trie(value: u8) -> MerkleTrie {
+        let mut path = vec![0x20];
+        path.extend_from_slice(B256::ZERO.as_slice());
+        let node = alloy_rlp::encode(vec![Bytes::from(path), Bytes::from(vec![value])]);
+        MerkleTrie::from_rlp_nodes([node]).unwrap()
+    }
+
+    fn block_input() -> BlockInput<EthBlockHeader> {
+        let state_trie = leaf_trie(0);
+        let parent = alloy_consensus::Header {
+            number: 0,
+            ..Default::default()
+        };
+        let header = alloy_consensus::Header {
+            number: 1,
+            parent_hash: parent.hash_slow(),
+            state_root: state_trie.hash_slow(),
+            ..Default::default()
+        };
+
+        BlockInput {
+            header: RlpHeader::new(header),
+            state_trie,
+            storage_tries: vec![],
+            contracts: vec![],
+            ancestors: vec![RlpHeader::new(parent)],
+        }
+    }
+
+    #[test]
+    fn validate() {
+        let input = block_input();
+        input.validate().unwrap();
+        // a valid input must also be accepted by the guest
+        input.into_env();
+    }
+
+    #[test]
+    fn validate_corrupted_trie() {
+        let mut input = block_input();
+        input.state_trie = leaf_trie(1);
+        let err = input.validate().unwrap_err();
+        assert!(err.to_string().contains("state root mismatch"));
+    }
+
+    #[test]
+    fn validate_invalid_ancestor() {
+        let mut input = block_input();
+        input.ancestors[0] = RlpHeader::new(alloy_consensus::Header {
+            number: 0,
+            gas_limit: 1,
+            ..Default::default()
+        });
+        let err = input.validate().unwrap_err();
+        assert!(err.to_string().contains("invalid ancestor chain"));
+    }
+
+    #[test]
+    fn merge() {
+        let mut input = block_input();
+        input.storage_tries = vec![leaf_trie(1)];
+        input.contracts = vec![Bytes::from_static(&[0x01])];
+        let mut other = block_input();
+        other.storage_tries = vec![leaf_trie(2), leaf_trie(1)];
+        other.contracts = vec![Bytes::from_static(&[0x02])];
+        other.ancestors.clear();
+
+        let merged = input.merge(other).unwrap();
+        assert_eq!(merged.storage_tries.len(), 2);
+        assert_eq!(merged.contracts.len(), 2);
+        assert_eq!(merged.ancestors.len(), 1);
+        merged.validate().unwrap();
+    }
+
+    #[test]
+    fn merge_block_hash_mismatch() {
+        let mut other = block_input();
+        other.header = RlpHeader::new(alloy_consensus::Header {
+            number: 2,
+            ..Default::default()
+        });
+        let err = block_input().merge(other).err().unwrap();
+        assert!(err.to_string().contains("block hash mismatch"));
+    }
+
+    #[test]
+    fn merge_state_root_mismatch() {
+        let mut other = block_input();
+        other.state_trie = leaf_trie(1);
+        let err = block_input().merge(other).err().unwrap();
+        assert!(err.to_string().contains("state root mismatch"));
+    }
+
+    #[test]
+    fn merge_ancestor_mismatch() {
+        let mut other = block_input();
+        other.ancestors[0] = RlpHeader::new(alloy_consensus::Header {
+            number: 0,
+            gas_limit: 1,
+            ..Default::default()
+        });
+        let err = block_input().merge(other).err().unwrap();
+        assert!(err.to_string().contains("ancestor mismatch"));
+    }
+
+    #[test]
+    fn merge_invalid_commitment() {
+        // an empty proof can never be valid for a leaf of the Beacon block
+        let input = ComposeInput::new(block_input(), BeaconCommit::new(vec![], 0));
+        let err = input.clone().merge(input).err().unwrap();
+        assert!(err.to_string().contains("invalid beacon inclusion proof"));
+    }
+
+    #[cfg(feature = "host")]
+    #[test]
+    fn ensure_identical() {
+        let mut input = block_input();
+        input.storage_tries = vec![leaf_trie(1), leaf_trie(2)];
+        input.ensure_identical(input.clone()).unwrap();
+
+        // the same state in a different order must be detected as nondeterministic
+        let mut reordered = input.clone();
+        reordered.storage_tries.reverse();
+        let err = input.ensure_identical(reordered).unwrap_err();
+        assert!(err.to_string().contains("nondeterministic preflight"));
+    }
+}
@@ -15,6 +15,7 @@
 //! Handling different blockchain specifications.
 use std::collections::BTreeMap;
 
+use crate::{arbitrum, ethereum, gnosis, polygon};
 use alloy_primitives::{b256, BlockNumber, BlockTimestamp, ChainId, B256};
 use anyhow::bail;
 use revm::primitives::SpecId;
@@ -78,6 +79,69 @@ impl ChainSpec {
         }
     }
 
+    /// Creates a new configuration from the `config` section of a Geth genesis file.
+    ///
+    /// The chain ID and the activation blocks and timestamps of all supported forks (up to and
+    /// including `pragueTime`) are taken from the genesis file. The Merge is activated at
+    /// `mergeNetsplitBlock` or, for chains starting as proof-of-stake, if the
+    /// `terminalTotalDifficulty` is zero. Since the parsing is deterministic, the same genesis
+    /// file can be used on the host and in the guest to obtain a [ChainSpec] with the same
+    /// [ChainSpec::digest]. This requires the `genesis-json` feature:
+    /// ```rust
+    /// # use revm::primitives::SpecId;
+    /// # use risc0_steel::config::ChainSpec;
+    /// let genesis = r#"{
+    ///   "config": {
+    ///     "chainId": 1337,
+    ///     "londonBlock": 0,
+    ///     "terminalTotalDifficulty": 0,
+    ///     "shanghaiTime": 0,
+    ///     "cancunTime": 1700000000
+    ///   }
+    /// }"#;
+    /// let spec = ChainSpec::from_genesis_json(genesis)?;
+    /// assert_eq!(spec.active_fork(0, 1700000000)?, SpecId::CANCUN);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "genesis-json")]
+    pub fn from_genesis_json(json: &str) -> anyhow::Result<Self> {
+        use anyhow::Context;
+
+        let genesis: Genesis = serde_json::from_str(json).context("invalid genesis file")?;
+        genesis.config.try_into()
+    }
+
+    /// Returns the built-in [ChainSpec] of the chain with the given ID.
+    ///
+    /// This covers all the specs defined in this crate, e.g. [ETH_MAINNET_CHAIN_SPEC], so that
+    /// hosts supporting several chains can select the spec based on the chain ID of the provider.
+    /// Returns `None` for unknown chains, for which a custom spec must be used instead, e.g.
+    /// created with [ChainSpec::from_genesis_json]. The [EvmBlockHeader] of the environment must
+    /// still match the chain, e.g. [ArbBlockHeader] for Arbitrum One.
+    ///
+    /// ```rust
+    /// # use risc0_steel::config::ChainSpec;
+    /// let spec = ChainSpec::for_chain_id(1).unwrap();
+    /// assert_eq!(spec.chain_id(), 1);
+    /// assert!(ChainSpec::for_chain_id(31337).is_none());
+    /// ```
+    ///
+    /// [ETH_MAINNET_CHAIN_SPEC]: crate::ethereum::ETH_MAINNET_CHAIN_SPEC
+    /// [EvmBlockHeader]: crate::EvmBlockHeader
+    /// [ArbBlockHeader]: crate::arbitrum::ArbBlockHeader
+    pub fn for_chain_id(chain_id: ChainId) -> Option<&'static ChainSpec> {
+        [
+            &*ethereum::ETH_MAINNET_CHAIN_SPEC,
+            &*ethereum::ETH_SEPOLIA_CHAIN_SPEC,
+            &*ethereum::ETH_HOLESKY_CHAIN_SPEC,
+            &*gnosis::GNOSIS_CHAIN_SPEC,
+            &*arbitrum::ARB_ONE_CHAIN_SPEC,
+            &*polygon::POLYGON_MAINNET_CHAIN_SPEC,
+        ]
+        .into_iter()
+        .find(|spec| spec.chain_id == chain_id)
+    }
+
     /// Returns the network chain ID.
     #[inline]
     pub fn chain_id(&self) -> ChainId {
@@ -90,6 +154,17 @@ impl ChainSpec {
         <[u8; 32]>::from(StructHash::digest::<Sha256>(self)).into()
     }
 
+    /// Returns the digest of the network configuration bound to the given fork.
+    ///
+    /// This is the `configID` committed by an environment using [EvmEnv::bind_spec_id] for a
+    /// block for which `spec_id` is active.
+    ///
+    /// [EvmEnv::bind_spec_id]: crate::EvmEnv::bind_spec_id
+    #[inline]
+    pub fn spec_digest(&self, spec_id: SpecId) -> B256 {
+        bind_spec_id(self.digest(), spec_id)
+    }
+
     /// Returns the [SpecId] for a given block number and timestamp or an error if not supported.
     pub fn active_fork(&self, block_number: BlockNumber, timestamp: u64) -> anyhow::Result<SpecId> {
         for (spec_id, fork) in self.forks.iter().rev() {
@@ -97,10 +172,114 @@ impl ChainSpec {
                 return Ok(*spec_id);
             }
         }
-        bail!("no supported fork for block {}", block_number)
+        bail!(
+            "no supported fork of chain {} for block {} with timestamp {}",
+            self.chain_id,
+            block_number,
+            timestamp
+        )
     }
 }
 
+/// The relevant parts of a Geth genesis file.
+#[cfg(feature = "genesis-json")]
+#[derive(Deserialize)]
+struct Genesis {
+    config: GenesisConfig,
+}
+
+/// The chain configuration of a Geth genesis file.
+#[cfg(feature = "genesis-json")]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenesisConfig {
+    chain_id: ChainId,
+    homestead_block: Option<BlockNumber>,
+    dao_fork_block: Option<BlockNumber>,
+    eip150_block: Option<BlockNumber>,
+    eip158_block: Option<BlockNumber>,
+    byzantium_block: Option<BlockNumber>,
+    constantinople_block: Option<BlockNumber>,
+    petersburg_block: Option<BlockNumber>,
+    istanbul_block: Option<BlockNumber>,
+    muir_glacier_block: Option<BlockNumber>,
+    berlin_block: Option<BlockNumber>,
+    london_block: Option<BlockNumber>,
+    arrow_glacier_block: Option<BlockNumber>,
+    gray_glacier_block: Option<BlockNumber>,
+    merge_netsplit_block: Option<BlockNumber>,
+    // the TTD of mainnet does not fit into an u64, so parse it as an arbitrary JSON number
+    terminal_total_difficulty: Option<serde_json::Number>,
+    shanghai_time: Option<BlockTimestamp>,
+    cancun_time: Option<BlockTimestamp>,
+    prague_time: Option<BlockTimestamp>,
+}
+
+#[cfg(feature = "genesis-json")]
+impl TryFrom<GenesisConfig> for ChainSpec {
+    type Error = anyhow::Error;
+
+    fn try_from(config: GenesisConfig) -> anyhow::Result<Self> {
+        let merge_block = match (
+            config.merge_netsplit_block,
+            config.terminal_total_difficulty,
+        ) {
+            (Some(block), _) => Some(block),
+            (None, Some(ttd)) if ttd.as_u64() == Some(0) => Some(0),
+            (None, Some(_)) => {
+                bail!("activation block of the Merge cannot be derived: mergeNetsplitBlock missing")
+            }
+            (None, None) => None,
+        };
+
+        let blocks = [
+            (SpecId::FRONTIER, Some(0)),
+            (SpecId::HOMESTEAD, config.homestead_block),
+            (SpecId::DAO_FORK, config.dao_fork_block),
+            (SpecId::TANGERINE, config.eip150_block),
+            (SpecId::SPURIOUS_DRAGON, config.eip158_block),
+            (SpecId::BYZANTIUM, config.byzantium_block),
+            (SpecId::CONSTANTINOPLE, config.constantinople_block),
+            (SpecId::PETERSBURG, config.petersburg_block),
+            (SpecId::ISTANBUL, config.istanbul_block),
+            (SpecId::MUIR_GLACIER, config.muir_glacier_block),
+            (SpecId::BERLIN, config.berlin_block),
+            (SpecId::LONDON, config.london_block),
+            (SpecId::ARROW_GLACIER, config.arrow_glacier_block),
+            (SpecId::GRAY_GLACIER, config.gray_glacier_block),
+            (SpecId::MERGE, merge_block),
+        ];
+        let timestamps = [
+            (SpecId::SHANGHAI, config.shanghai_time),
+            (SpecId::CANCUN, config.cancun_time),
+            (SpecId::PRAGUE, config.prague_time),
+        ];
+
+        let forks = blocks
+            .into_iter()
+            .filter_map(|(spec_id, block)| Some((spec_id, ForkCondition::Block(block?))))
+            .chain(
+                timestamps
+                    .into_iter()
+                    .filter_map(|(spec_id, ts)| Some((spec_id, ForkCondition::Timestamp(ts?)))),
+            )
+            .collect();
+
+        Ok(ChainSpec {
+            chain_id: config.chain_id,
+            forks,
+        })
+    }
+}
+
+/// Binds the [SpecId] to the digest of a network configuration.
+pub(crate) fn bind_spec_id(config_id: B256, spec_id: SpecId) -> B256 {
+    let mut hasher = Sha256::new();
+    hasher.update(config_id);
+    hasher.update([spec_id as u8]);
+    <[u8; 32]>::from(hasher.finalize()).into()
+}
+
 // NOTE: We do not want to make this public, to avoid having multiple traits with the `digest`
 // function in the RISC Zero ecosystem of crates.
 /// A simple structured hasher.
@@ -156,6 +335,16 @@ impl StructHash for ChainSpec {
 mod tests {
     use super::*;
 
+    #[test]
+    fn for_chain_id() {
+        let spec = ChainSpec::for_chain_id(1).unwrap();
+        assert_eq!(spec.digest(), ethereum::ETH_MAINNET_CHAIN_SPEC.digest());
+        let spec = ChainSpec::for_chain_id(42161).unwrap();
+        assert_eq!(spec.digest(), arbitrum::ARB_ONE_CHAIN_SPEC.digest());
+
+        assert!(ChainSpec::for_chain_id(31337).is_none());
+    }
+
     #[test]
     fn active_fork() {
         let spec = ChainSpec {
@@ -176,6 +365,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn from_genesis_json() {
+        let genesis = r#"{
+            "config": {
+                "chainId": 12345,
+                "homesteadBlock": 0,
+                "eip150Block": 0,
+                "eip155Block": 0,
+                "eip158Block": 0,
+                "byzantiumBlock": 0,
+                "constantinopleBlock": 0,
+                "petersburgBlock": 0,
+                "istanbulBlock": 0,
+                "berlinBlock": 10,
+                "londonBlock": 20,
+                "mergeNetsplitBlock": 30,
+                "terminalTotalDifficulty": 58750000000000000000000,
+                "shanghaiTime": 1000,
+                "cancunTime": 2000
+            },
+            "difficulty": "0x1",
+            "gasLimit": "0x1c9c380",
+            "alloc": {}
+        }"#;
+        let spec = ChainSpec::from_genesis_json(genesis).unwrap();
+        assert_eq!(spec.chain_id(), 12345);
+
+        assert_eq!(spec.active_fork(0, 0).unwrap(), SpecId::ISTANBUL);
+        assert_eq!(spec.active_fork(9, 0).unwrap(), SpecId::ISTANBUL);
+        assert_eq!(spec.active_fork(10, 0).unwrap(), SpecId::BERLIN);
+        assert_eq!(spec.active_fork(19, 0).unwrap(), SpecId::BERLIN);
+        assert_eq!(spec.active_fork(20, 0).unwrap(), SpecId::LONDON);
+        assert_eq!(spec.active_fork(29, 0).unwrap(), SpecId::LONDON);
+        assert_eq!(spec.active_fork(30, 0).unwrap(), SpecId::MERGE);
+        assert_eq!(spec.active_fork(30, 999).unwrap(), SpecId::MERGE);
+        assert_eq!(spec.active_fork(30, 1000).unwrap(), SpecId::SHANGHAI);
+        assert_eq!(spec.active_fork(30, 1999).unwrap(), SpecId::SHANGHAI);
+        assert_eq!(spec.active_fork(30, 2000).unwrap(), SpecId::CANCUN);
+
+        // parsing must be deterministic, so that the host and guest compute the same digest
+        let again = ChainSpec::from_genesis_json(genesis).unwrap();
+        assert_eq!(spec.digest(), again.digest());
+        let deserialized: ChainSpec =
+            serde_json::from_str(&serde_json::to_string(&spec).unwrap()).unwrap();
+        assert_eq!(spec.digest(), deserialized.digest());
+    }
+
+    #[test]
+    fn from_genesis_json_pos() {
+        let genesis = r#"{"config": {"chainId": 1337, "terminalTotalDifficulty": 0}}"#;
+        let spec = ChainSpec::from_genesis_json(genesis).unwrap();
+        assert_eq!(spec.active_fork(0, 0).unwrap(), SpecId::MERGE);
+
+        // without mergeNetsplitBlock, the Merge block cannot be derived from a non-zero TTD
+        let genesis = r#"{"config": {"chainId": 1337, "terminalTotalDifficulty": 1}}"#;
+        assert!(ChainSpec::from_genesis_json(genesis).is_err());
+    }
+
     #[test]
     fn default_digest() {
         let exp: [u8; 32] = {
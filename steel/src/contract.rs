@@ -12,14 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{borrow::Borrow, fmt::Debug, marker::PhantomData, mem};
+use std::{
+    borrow::Borrow,
+    collections::BTreeMap,
+    fmt::Debug,
+    marker::PhantomData,
+    mem,
+    sync::{Arc, Mutex},
+};
 
-use crate::{state::WrapStateDb, EvmBlockHeader, GuestEvmEnv};
-use alloy_primitives::{Address, TxKind, U256};
-use alloy_sol_types::{SolCall, SolType};
+use crate::{state::WrapStateDb, Account, EvmBlockHeader, GuestEvmEnv};
+use alloy_primitives::{Address, Bytes, TxKind, B256, U256};
+use alloy_sol_types::{Panic, Revert, SolCall, SolError, SolType};
 use anyhow::anyhow;
 use revm::{
-    primitives::{CfgEnvWithHandlerCfg, ExecutionResult, ResultAndState, SuccessReason},
+    primitives::{
+        BlobExcessGasAndPrice, CfgEnvWithHandlerCfg, EvmState, ExecutionResult, HaltReason,
+        ResultAndState, SuccessReason, KECCAK_EMPTY,
+    },
     Database, Evm,
 };
 
@@ -69,66 +79,140 @@ use revm::{
 /// # }
 /// ```
 ///
+/// ### Return values
+/// Calls return the [SolCall::Return] type generated by the `sol!` macro, which is decoded with
+/// [SolCall::abi_decode_returns] exactly like in `alloy`. Named return values become fields of
+/// the same name, while unnamed ones are called `_0`, `_1`, etc. Thus, for functions returning
+/// multiple values or a struct, it is best to name the return values in the interface:
+/// ```rust
+/// # use alloy_sol_types::sol;
+/// sol! {
+///     interface IPool {
+///         struct Slot0 { uint160 sqrtPriceX96; int24 tick; }
+///         // returns `getReservesReturn { reserve0, reserve1 }`
+///         function getReserves() external view returns (uint112 reserve0, uint112 reserve1);
+///         // returns `slot0Return { slot0 }`, where `slot0` is of type `Slot0`
+///         function slot0() external view returns (Slot0 memory slot0);
+///     }
+/// }
+/// ```
+///
 /// [EthEvmEnv::builder]: crate::ethereum::EthEvmEnv::builder
 /// [EvmEnv::builder]: crate::EvmEnv::builder
 /// [EvmInput::into_env]: crate::EvmInput::into_env
 pub struct Contract<E> {
     address: Address,
     env: E,
+    cache: Option<CallCache>,
 }
 
 impl<'a, H> Contract<&'a GuestEvmEnv<H>> {
     /// Constructor for executing calls to an Ethereum contract in the guest.
     pub fn new(address: Address, env: &'a GuestEvmEnv<H>) -> Self {
-        Self { address, env }
+        Self {
+            address,
+            env,
+            cache: None,
+        }
+    }
+
+    /// Enables the caching of call results for this contract.
+    ///
+    /// With the cache enabled, the result of each successful call is stored, and executing an
+    /// identical call again, i.e. with the same calldata and the same call parameters like the
+    /// caller or gas limit, returns the stored result instead of executing the EVM again. This
+    /// saves cycles when a guest needs the result of the same call at several places. As the state
+    /// of the committed block is immutable, this is sound for all calls without side effects,
+    /// which includes all `view` and `pure` functions. Calls that fail are not cached.
+    pub fn with_call_cache(mut self) -> Self {
+        self.cache = Some(CallCache::default());
+        self
     }
 
     /// Initializes a call builder to execute a call on the contract.
     pub fn call_builder<S: SolCall>(&self, call: &S) -> CallBuilder<S, &GuestEvmEnv<H>> {
-        CallBuilder::new(self.env, self.address, call)
+        let mut builder = CallBuilder::new(self.env, self.address, call);
+        builder.cache = self.cache.clone();
+        builder
+    }
+}
+
+impl<'a, H: EvmBlockHeader> Contract<&'a GuestEvmEnv<H>> {
+    /// Constructor like [Contract::new] that asserts that the address has code.
+    ///
+    /// Calls to an address without code, e.g. an EOA, always succeed with empty return data,
+    /// which often leads to confusing results. This panics if the account has no code in the
+    /// committed block. On the host, use [Contract::preflight_checked] for the same check.
+    pub fn new_checked(address: Address, env: &'a GuestEvmEnv<H>) -> Self {
+        let contract = Self::new(address, env);
+        assert_ne!(
+            contract.code_hash(),
+            KECCAK_EMPTY,
+            "contract {} has no code",
+            address
+        );
+        contract
+    }
+
+    /// Returns the verified code hash of the contract.
+    ///
+    /// The result of a call depends on the code deployed at the committed block, e.g. on the
+    /// implementation of an upgradeable proxy. Committing this hash, e.g. in the journal, allows
+    /// the verifier to check which bytecode was executed.
+    pub fn code_hash(&self) -> B256 {
+        Account::new(self.address, self.env).code_hash()
     }
 }
 
 /// A builder for calling an Ethereum contract.
 ///
 /// Once configured, call with [CallBuilder::call].
+///
+/// The call is simulated without validating the transaction against the block or the state. In
+/// particular, the gas price is not checked against the block's base fee and the gas limit is not
+/// checked against the block gas limit, as if revm's `disable_base_fee` and
+/// `disable_block_gas_limit` options were set. As the same rules apply on the host and in the
+/// guest, the preflight always matches the guest execution.
 #[derive(Debug, Clone)]
 #[must_use]
 pub struct CallBuilder<S, E> {
     tx: CallTxData<S>,
     env: E,
+    cache: Option<CallCache>,
 }
 
 impl<S, E> CallBuilder<S, E> {
-    /// The default gas limit for function calls.
-    const DEFAULT_GAS_LIMIT: u64 = 30_000_000;
-
     /// Creates a new builder for the given contract call.
     fn new(env: E, address: Address, call: &S) -> Self
     where
         S: SolCall,
     {
-        let tx = CallTxData {
-            caller: address, // by default the contract calls itself
-            gas_limit: Self::DEFAULT_GAS_LIMIT,
-            gas_price: U256::ZERO,
-            to: address,
-            value: U256::ZERO,
-            data: call.abi_encode(),
-            phantom: PhantomData,
-        };
-        Self { tx, env }
+        let tx = CallTxData::new(address, call);
+        Self {
+            tx,
+            env,
+            cache: None,
+        }
     }
 
     /// Sets the caller of the function call.
+    ///
+    /// This sets both `msg.sender` and `tx.origin` of the call. By default, the contract calls
+    /// itself, i.e. the caller is the address of the contract. The caller is applied identically
+    /// during the preflight and in the guest, so changing it changes the proven result.
     pub fn from(mut self, from: Address) -> Self {
         self.tx.caller = from;
         self
     }
 
     /// Sets the gas limit of the function call.
+    ///
+    /// The default is the gas limit of the committed block, so that `gasleft()` returns the same
+    /// value as for a transaction using the entire block. As with all other call parameters, the
+    /// gas limit is applied identically during the preflight and in the guest, so calls depending
+    /// on `gasleft()` access the same state and return the same result.
     pub fn gas(mut self, gas: u64) -> Self {
-        self.tx.gas_limit = gas;
+        self.tx.gas_limit = Some(gas);
         self
     }
 
@@ -139,17 +223,39 @@ impl<S, E> CallBuilder<S, E> {
     }
 
     /// Sets the value field of the function call.
+    ///
+    /// The default is zero. A non-zero value is transferred from the caller to the contract, so
+    /// the caller must have a sufficient balance at the execution block.
     pub fn value(mut self, value: U256) -> Self {
         self.tx.value = value;
         self
     }
+
+    /// Overrides the blob base fee returned by the `BLOBBASEFEE` opcode.
+    ///
+    /// By default, the blob base fee is derived from the `excess_blob_gas` of the block header,
+    /// exactly as on the actual chain. This override can be used to simulate blob-fee-dependent
+    /// calls under different conditions. As it is applied identically on the host and in the
+    /// guest, the override is part of the proven execution and the result no longer reflects the
+    /// committed block.
+    pub fn blob_base_fee_override(mut self, blob_base_fee: u128) -> Self {
+        self.tx.blob_base_fee = Some(blob_base_fee);
+        self
+    }
+
+    /// Returns the ABI-encoded calldata of the function call.
+    ///
+    /// This can be useful to debug selector or encoding mismatches without executing the call.
+    pub fn calldata(&self) -> Bytes {
+        self.tx.data.clone().into()
+    }
 }
 
 #[cfg(feature = "host")]
 mod host {
     use super::*;
     use crate::host::{
-        db::{AlloyDb, ProviderDb},
+        db::{AlloyDb, ProofDb, ProviderDb},
         HostEvmEnv,
     };
     use alloy::{
@@ -158,7 +264,10 @@ mod host {
         providers::Provider,
         transports::Transport,
     };
-    use anyhow::{anyhow, Context, Result};
+    use anyhow::{anyhow, ensure, Context, Result};
+    use revm::{
+        inspector_handle_register, inspectors::NoOpInspector, primitives::BlockEnv, Inspector,
+    };
 
     impl<'a, D: Database, H, C> Contract<&'a mut HostEvmEnv<D, H, C>> {
         /// Constructor for preflighting calls to an Ethereum contract on the host.
@@ -170,7 +279,11 @@ mod host {
         /// [EvmEnv::into_input]: crate::EvmEnv::into_input
         /// [EvmEnv]: crate::EvmEnv
         pub fn preflight(address: Address, env: &'a mut HostEvmEnv<D, H, C>) -> Self {
-            Self { address, env }
+            Self {
+                address,
+                env,
+                cache: None,
+            }
         }
 
         /// Initializes a call builder to execute a call on the contract.
@@ -182,6 +295,36 @@ mod host {
         }
     }
 
+    impl<'a, T, N, P, H, C> Contract<&'a mut HostEvmEnv<AlloyDb<T, N, P>, H, C>>
+    where
+        T: Transport + Clone,
+        N: Network,
+        P: Provider<T, N> + Send + 'static,
+    {
+        /// Constructor like [Contract::preflight] that checks that the address has code.
+        ///
+        /// This returns an error if the account has no code in the committed block, e.g. because
+        /// it is an EOA or the address is wrong. The account is recorded for the proof, so that
+        /// [Contract::new_checked] and [Contract::code_hash] can be used in the guest.
+        pub async fn preflight_checked(
+            address: Address,
+            env: &'a mut HostEvmEnv<AlloyDb<T, N, P>, H, C>,
+        ) -> Result<Self> {
+            let code_hash = Account::preflight(address, &mut *env).code_hash().await?;
+            ensure!(
+                code_hash != KECCAK_EMPTY,
+                "contract {} has no code",
+                address
+            );
+
+            Ok(Self {
+                address,
+                env,
+                cache: None,
+            })
+        }
+    }
+
     impl<'a, S, T, N, P, H, C> CallBuilder<S, &'a mut HostEvmEnv<AlloyDb<T, N, P>, H, C>>
     where
         T: Transport + Clone,
@@ -203,29 +346,129 @@ mod host {
             Ok(self)
         }
 
+        /// Forces all accounts and storage slots of the EIP-2930 `access_list` into the input.
+        ///
+        /// Similar to [CallBuilder::prefetch_access_list], the proofs are fetched upfront, but
+        /// the listed state is included in the resulting [EvmInput] even if the call does not
+        /// access it during preflight. This is useful when the guest executes code paths that
+        /// differ from the preflight, e.g. due to a different caller or gas limit. A suitable
+        /// list can be obtained from the `eth_createAccessList` RPC for the expected transaction.
+        /// Each additional entry increases the size of the input and the cycles in the guest, so
+        /// the list should be as tight as possible.
+        ///
+        /// [EvmInput]: crate::EvmInput
+        pub async fn access_list(self, access_list: AccessList) -> Result<Self> {
+            let db = self.env.db_mut();
+            db.include_access_list(access_list).await?;
+
+            Ok(self)
+        }
+
         /// Executes the call using an [EvmEnv] constructed with [Contract::preflight].
         ///
+        /// The execution is not based on a fixed snapshot: any state that is not yet part of the
+        /// environment is fetched from the RPC provider when it is first accessed, and every access
+        /// is recorded. The resulting input therefore contains exactly the state read by this call,
+        /// even if the access pattern is data-dependent. The guest can only miss state, if it
+        /// executes differently than the host, e.g. with different call parameters.
+        ///
         /// This uses [tokio::task::spawn_blocking] to run the blocking revm execution.
         ///
         /// [EvmEnv]: crate::EvmEnv
         pub async fn call(self) -> Result<S::Return> {
+            let (returns, _) = self.execute().await?;
+            Ok(returns)
+        }
+
+        /// Executes the call like [CallBuilder::call] and returns an estimate of its costs.
+        ///
+        /// The costs of executing the call in the guest mainly depend on the executed EVM
+        /// instructions and the number of state accesses, each requiring a Merkle proof to be
+        /// processed. Thus, the returned [CallEstimate] can be used as a proxy for the number of
+        /// guest cycles, e.g. to split work over multiple proofs, before running the zkVM.
+        /// As the call is executed exactly as during preflight, the accessed state is still
+        /// recorded in the environment.
+        pub async fn estimate(self) -> Result<CallEstimate> {
+            let (_, estimate) = self.execute().await?;
+            Ok(estimate)
+        }
+
+        /// Executes the call like [CallBuilder::call] with the given revm [Inspector] attached.
+        ///
+        /// The inspector receives all the hooks of the EVM execution, e.g. each executed opcode,
+        /// each sub-call and all `SLOAD` and `SSTORE` operations, so that a custom tracer can
+        /// be used to debug unexpected call results. The inspector is returned together with the
+        /// result of the call, so that the collected trace can be inspected afterward. It is only
+        /// used on the host: the accessed state is recorded exactly as for [CallBuilder::call],
+        /// so, as long as the inspector does not alter the execution, the resulting [EvmInput]
+        /// is the same.
+        ///
+        /// [EvmInput]: crate::EvmInput
+        pub async fn call_with_inspector<I>(self, inspector: I) -> Result<(S::Return, I)>
+        where
+            I: Inspector<ProofDb<AlloyDb<T, N, P>>> + Send + 'static,
+        {
+            let (returns, _, inspector) = self.execute_with(Some(inspector)).await?;
+            Ok((returns, inspector.unwrap()))
+        }
+
+        /// Executes the call and returns the result together with its [CallEstimate].
+        async fn execute(self) -> Result<(S::Return, CallEstimate)> {
+            let (returns, estimate, _) = self.execute_with(None::<NoOpInspector>).await?;
+            Ok((returns, estimate))
+        }
+
+        /// Executes the call with the optional [Inspector] attached and returns the result
+        /// together with its [CallEstimate] and the inspector.
+        async fn execute_with<I>(
+            mut self,
+            inspector: Option<I>,
+        ) -> Result<(S::Return, CallEstimate, Option<I>)>
+        where
+            I: Inspector<ProofDb<AlloyDb<T, N, P>>> + Send + 'static,
+        {
             log::info!(
                 "Executing preflight calling '{}' on {}",
                 S::SIGNATURE,
                 self.tx.to
             );
 
+            if self.env.db_mut().inner().config().auto_access_list {
+                let access_list = self.create_access_list().await?;
+                self.env
+                    .db_mut()
+                    .include_access_list(access_list)
+                    .await
+                    .context("including access list failed")?;
+            }
+
             // as mutable references are not possible, the DB must be moved in and out of the task
             let db = self.env.db.take().unwrap();
 
             let cfg = self.env.cfg_env.clone();
             let header = self.env.header.inner().clone();
-            let (result, db) = tokio::task::spawn_blocking(move || {
-                let mut evm = new_evm(db, cfg, header);
-                let result = self.tx.transact(&mut evm);
-                let (db, _) = evm.into_db_and_env_with_handler_cfg();
+            let tx = self.tx;
+            let (result, db, inspector) = tokio::task::spawn_blocking(move || match inspector {
+                Some(inspector) => {
+                    let mut evm = Evm::builder()
+                        .with_db(db)
+                        .with_external_context(inspector)
+                        .with_cfg_env_with_handler_cfg(cfg)
+                        .modify_block_env(|blk_env| header.fill_block_env(blk_env))
+                        .append_handler_register(inspector_handle_register)
+                        .build();
+                    let result = tx.transact_raw(&mut evm);
+                    let context = evm.into_context();
 
-                (result, db)
+                    (result, context.evm.inner.db, Some(context.external))
+                }
+                None => {
+                    let mut evm = new_evm(db, cfg, header);
+                    let result = tx.transact_raw(&mut evm);
+                    let (db, _) = evm.into_db_and_env_with_handler_cfg();
+
+                    (result, db, None)
+                }
             })
             .await
             .expect("EVM execution panicked");
@@ -233,7 +476,15 @@ mod host {
             // restore the DB before handling errors, so that we never return an env without a DB
             self.env.db = Some(db);
 
-            result.map_err(|err| anyhow!("call '{}' failed: {}", S::SIGNATURE, err))
+            let (returns, estimate, output) = result
+                .and_then(|(output, estimate, _)| {
+                    let returns = CallTxData::<S>::decode_returns(&output)?;
+                    Ok((returns, estimate, output))
+                })
+                .map_err(|err| anyhow!("call '{}' failed: {}", S::SIGNATURE, err))?;
+            self.env.push_call_result(output);
+
+            Ok((returns, estimate, inspector))
         }
 
         /// Automatically prefetches the access list before executing the call using an [EvmEnv]
@@ -244,25 +495,8 @@ mod host {
         /// [CallBuilder::call]. See the corresponding methods for more information.
         ///
         /// [EvmEnv]: crate::EvmEnv
-        pub async fn call_with_prefetch(self) -> Result<S::Return> {
-            let access_list = {
-                let tx = <N as Network>::TransactionRequest::default()
-                    .with_from(self.tx.caller)
-                    .with_gas_limit(self.tx.gas_limit)
-                    .with_gas_price(self.tx.gas_price.to())
-                    .with_to(self.tx.to)
-                    .with_value(self.tx.value)
-                    .with_input(self.tx.data.clone());
-
-                let db = self.env.db_mut();
-                let provider = db.inner().provider();
-                let access_list = provider
-                    .create_access_list(&tx)
-                    .hash(db.inner().block_hash())
-                    .await
-                    .context("eth_createAccessList failed")?;
-                access_list.access_list
-            };
+        pub async fn call_with_prefetch(mut self) -> Result<S::Return> {
+            let access_list = self.create_access_list().await?;
 
             self.prefetch_access_list(access_list)
                 .await
@@ -270,9 +504,231 @@ mod host {
                 .call()
                 .await
         }
+
+        /// Returns the EIP-2930 access list of the call using the `eth_createAccessList` RPC.
+        async fn create_access_list(&mut self) -> Result<AccessList> {
+            // use the same gas limit as the revm execution
+            let gas_limit = match self.tx.gas_limit {
+                Some(gas_limit) => gas_limit,
+                None => {
+                    let mut blk_env = BlockEnv::default();
+                    self.env.header().inner().fill_block_env(&mut blk_env);
+                    blk_env.gas_limit.saturating_to()
+                }
+            };
+            let tx = <N as Network>::TransactionRequest::default()
+                .with_from(self.tx.caller)
+                .with_gas_limit(gas_limit)
+                .with_gas_price(self.tx.gas_price.to())
+                .with_to(self.tx.to)
+                .with_value(self.tx.value)
+                .with_input(self.tx.data.clone());
+
+            let db = self.env.db_mut();
+            let provider = db.inner().provider();
+            let access_list = provider
+                .create_access_list(&tx)
+                .hash(db.inner().block_hash())
+                .await
+                .context("eth_createAccessList failed")?;
+
+            Ok(access_list.access_list)
+        }
+    }
+
+    impl<'a, S: SolCall, D: Database, H, C> MultiCall<S, &'a mut HostEvmEnv<D, H, C>> {
+        /// Constructor for preflighting the given calls on the host.
+        ///
+        /// Each call is a pair of the address of the contract and the function call. The state
+        /// accessed by all calls is recorded in the same environment.
+        pub fn preflight(
+            env: &'a mut HostEvmEnv<D, H, C>,
+            calls: impl IntoIterator<Item = (Address, S)>,
+        ) -> Self {
+            Self::new_with_env(env, calls)
+        }
+    }
+
+    impl<'a, S, T, N, P, H, C> MultiCall<S, &'a mut HostEvmEnv<AlloyDb<T, N, P>, H, C>>
+    where
+        T: Transport + Clone,
+        N: Network,
+        P: Provider<T, N> + Send + 'static,
+        S: SolCall + Send + 'static,
+        <S as SolCall>::Return: Send,
+        H: EvmBlockHeader + Clone + Send + 'static,
+    {
+        /// Executes the calls using an [EvmEnv] constructed with [MultiCall::preflight].
+        ///
+        /// This uses [tokio::task::spawn_blocking] to run the blocking revm execution.
+        ///
+        /// [EvmEnv]: crate::EvmEnv
+        pub async fn call(self) -> Result<Vec<S::Return>> {
+            log::info!(
+                "Executing preflight calling '{}' {} times",
+                S::SIGNATURE,
+                self.txs.len()
+            );
+
+            // as mutable references are not possible, the DB must be moved in and out of the task
+            let db = self.env.db.take().unwrap();
+
+            let cfg = self.env.cfg_env.clone();
+            let header = self.env.header.inner().clone();
+            let txs = self.txs;
+            let (result, db) = tokio::task::spawn_blocking(move || {
+                let mut evm = new_evm(db, cfg, header);
+                let result: Result<Vec<_>, String> = txs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, tx)| {
+                        let (output, _, _) = tx
+                            .transact_raw(&mut evm)
+                            .map_err(|err| format!("call {}: {}", i, err))?;
+                        let returns = CallTxData::<S>::decode_returns(&output)
+                            .map_err(|err| format!("call {}: {}", i, err))?;
+                        Ok((returns, output))
+                    })
+                    .collect();
+                let (db, _) = evm.into_db_and_env_with_handler_cfg();
+
+                (result, db)
+            })
+            .await
+            .expect("EVM execution panicked");
+
+            // restore the DB before handling errors, so that we never return an env without a DB
+            self.env.db = Some(db);
+
+            let results =
+                result.map_err(|err| anyhow!("multicall '{}' failed: {}", S::SIGNATURE, err))?;
+            let mut returns = Vec::with_capacity(results.len());
+            for (result, output) in results {
+                self.env.push_call_result(output);
+                returns.push(result);
+            }
+
+            Ok(returns)
+        }
+    }
+
+    /// A builder for preflighting calls to several contracts in a single step on the host.
+    ///
+    /// Each registered call can be of a different type, so that the accesses of calls to several
+    /// unrelated contracts are recorded together, before converting the environment into a single
+    /// [EvmInput]. The calls are executed independently and in order, using the default parameters
+    /// of a [CallBuilder]. In the guest, the calls can then be executed with [Contract::new] or
+    /// [MultiCall::new] against the same input.
+    ///
+    /// Create the builder with [EvmEnv::preflight_batch].
+    ///
+    /// [EvmInput]: crate::EvmInput
+    /// [EvmEnv::preflight_batch]: crate::EvmEnv::preflight_batch
+    #[must_use]
+    pub struct PreflightBatch<'a, D, H, C> {
+        env: &'a mut HostEvmEnv<D, H, C>,
+        calls: Vec<UntypedCall>,
+    }
+
+    /// A call whose type has been removed, together with a check of its return data.
+    struct UntypedCall {
+        tx: CallTxData<()>,
+        signature: &'static str,
+        check_returns: fn(&[u8]) -> Result<(), CallError>,
+    }
+
+    impl<'a, D, H, C> PreflightBatch<'a, D, H, C> {
+        /// Creates a new empty batch for the given environment.
+        pub(crate) fn new(env: &'a mut HostEvmEnv<D, H, C>) -> Self {
+            Self {
+                env,
+                calls: Vec::new(),
+            }
+        }
+
+        /// Adds the given calls to the contract at `address` to the batch.
+        pub fn add<S: SolCall>(
+            mut self,
+            address: Address,
+            calls: impl IntoIterator<Item = S>,
+        ) -> Self {
+            self.calls.extend(calls.into_iter().map(|call| UntypedCall {
+                tx: CallTxData::new(address, &call).into_untyped(),
+                signature: S::SIGNATURE,
+                check_returns: |data| CallTxData::<S>::decode_returns(data).map(drop),
+            }));
+            self
+        }
+
+        /// Returns the number of calls in the batch.
+        pub fn len(&self) -> usize {
+            self.calls.len()
+        }
+
+        /// Returns `true` if the batch does not contain any calls.
+        pub fn is_empty(&self) -> bool {
+            self.calls.is_empty()
+        }
+    }
+
+    impl<'a, T, N, P, H, C> PreflightBatch<'a, AlloyDb<T, N, P>, H, C>
+    where
+        T: Transport + Clone,
+        N: Network,
+        P: Provider<T, N> + Send + 'static,
+        H: EvmBlockHeader + Clone + Send + 'static,
+    {
+        /// Executes all calls of the batch, recording the accessed state in the environment.
+        ///
+        /// Returns the ABI-encoded return data of each call in the order in which the calls were
+        /// added. It returns an error if any call fails or if its return data cannot be decoded,
+        /// as the same call would panic in the guest.
+        ///
+        /// This uses [tokio::task::spawn_blocking] to run the blocking revm execution.
+        pub async fn call(self) -> Result<Vec<Bytes>> {
+            log::info!("Executing preflight of {} batched calls", self.calls.len());
+
+            // as mutable references are not possible, the DB must be moved in and out of the task
+            let db = self.env.db.take().unwrap();
+
+            let cfg = self.env.cfg_env.clone();
+            let header = self.env.header.inner().clone();
+            let calls = self.calls;
+            let (result, db) = tokio::task::spawn_blocking(move || {
+                let mut evm = new_evm(db, cfg, header);
+                let result: Result<Vec<_>> = calls
+                    .into_iter()
+                    .map(|call| {
+                        let output = call.tx.transact_raw(&mut evm).and_then(|(output, _, _)| {
+                            (call.check_returns)(&output)?;
+                            Ok(output)
+                        });
+                        output.map_err(|err| anyhow!("call '{}' failed: {}", call.signature, err))
+                    })
+                    .collect();
+                let (db, _) = evm.into_db_and_env_with_handler_cfg();
+
+                (result, db)
+            })
+            .await
+            .expect("EVM execution panicked");
+
+            // restore the DB before handling errors, so that we never return an env without a DB
+            self.env.db = Some(db);
+
+            let results = result?;
+            for output in &results {
+                self.env.push_call_result(output.clone());
+            }
+
+            Ok(results)
+        }
     }
 }
 
+#[cfg(feature = "host")]
+pub use host::PreflightBatch;
+
 impl<'a, S, H> CallBuilder<S, &'a GuestEvmEnv<H>>
 where
     S: SolCall,
@@ -280,39 +736,229 @@ where
 {
     /// Executes the call and returns an error if the call fails.
     ///
-    /// In general, it's recommended to use [CallBuilder::call] unless explicit error handling is
+    /// The [CallError] distinguishes a revert, including its data, from running out of gas. This
+    /// allows the guest to handle a failing call, e.g. to commit a sentinel value or to try a
+    /// fallback contract, instead of aborting. In general, it's recommended to use
+    /// [CallBuilder::call] unless explicit error handling is required.
+    pub fn try_call(self) -> Result<S::Return, CallError> {
+        let build_evm = || {
+            new_evm::<_, H>(
+                WrapStateDb::new(self.env.db()),
+                self.env.cfg_env.clone(),
+                self.env.header.inner(),
+            )
+        };
+        let Some(cache) = &self.cache else {
+            return self.tx.transact(&mut build_evm());
+        };
+
+        let key = self.tx.cache_key();
+        let cached = cache.lock().unwrap().get(&key).cloned();
+        let output = match cached {
+            Some(output) => output,
+            None => {
+                // only set up the EVM if the result is not cached
+                let (output, _, _) = self.tx.transact_raw(&mut build_evm())?;
+                cache.lock().unwrap().insert(key, output.clone());
+                output
+            }
+        };
+
+        CallTxData::<S>::decode_returns(&output)
+    }
+
+    /// Executes the call and panics on failure.
+    ///
+    /// A convenience wrapper for [CallBuilder::try_call], panicking if the call fails. Useful when
+    /// success is expected.
+    pub fn call(self) -> S::Return {
+        self.try_call().unwrap()
+    }
+
+    /// Executes the call like [CallBuilder::try_call] and also returns the verified code hashes
+    /// of all contracts touched by the call, ordered by their address.
+    ///
+    /// The result of a call depends on the bytecode deployed at the committed block, e.g. on the
+    /// current implementation of an upgradeable proxy, which is not part of the [Commitment].
+    /// Committing the returned hashes, e.g. in the journal, allows the verifier to reject proofs
+    /// executed against an unexpected implementation. To commit the code hash of a single
+    /// contract, [Contract::code_hash] can be used instead.
+    ///
+    /// [Commitment]: crate::Commitment
+    pub fn try_call_with_code_hashes(
+        self,
+    ) -> Result<(S::Return, BTreeMap<Address, B256>), CallError> {
+        let mut evm = new_evm::<_, H>(
+            WrapStateDb::new(self.env.db()),
+            self.env.cfg_env.clone(),
+            self.env.header.inner(),
+        );
+        let (output, _, state) = self.tx.transact_raw(&mut evm)?;
+        let returns = CallTxData::<S>::decode_returns(&output)?;
+        let code_hashes = state
+            .into_iter()
+            .filter(|(_, account)| account.info.code_hash != KECCAK_EMPTY)
+            .map(|(address, account)| (address, account.info.code_hash))
+            .collect();
+
+        Ok((returns, code_hashes))
+    }
+
+    /// Executes the call like [CallBuilder::try_call_with_code_hashes] and panics on failure.
+    pub fn call_with_code_hashes(self) -> (S::Return, BTreeMap<Address, B256>) {
+        self.try_call_with_code_hashes().unwrap()
+    }
+}
+
+/// A builder for executing multiple calls of the same type in a single EVM context.
+///
+/// Compared to executing each call with its own [CallBuilder], the EVM is only set up once, which
+/// reduces the number of cycles in the guest, e.g. when reading the balances of several tokens.
+/// The calls are executed independently and in order, i.e. no call observes state changes of a
+/// previous call, and all calls use the default parameters of a [CallBuilder].
+///
+/// ### Usage
+/// - **Preflight calls on the Host:** To prepare the calls on the host and record all accessed
+///   state in the same environment, use [MultiCall::preflight].
+/// - **Calls in the Guest:** To execute the calls in the guest, use [MultiCall::new].
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct MultiCall<S, E> {
+    txs: Vec<CallTxData<S>>,
+    env: E,
+}
+
+impl<S: SolCall, E> MultiCall<S, E> {
+    /// Creates a new builder for the given pairs of contract address and call.
+    fn new_with_env(env: E, calls: impl IntoIterator<Item = (Address, S)>) -> Self {
+        let txs = calls
+            .into_iter()
+            .map(|(address, call)| CallTxData::new(address, &call))
+            .collect();
+        Self { txs, env }
+    }
+}
+
+impl<'a, S: SolCall, H> MultiCall<S, &'a GuestEvmEnv<H>> {
+    /// Constructor for executing the given calls in the guest.
+    ///
+    /// Each call is a pair of the address of the contract and the function call.
+    pub fn new(env: &'a GuestEvmEnv<H>, calls: impl IntoIterator<Item = (Address, S)>) -> Self {
+        Self::new_with_env(env, calls)
+    }
+}
+
+impl<'a, S: SolCall, H: EvmBlockHeader> MultiCall<S, &'a GuestEvmEnv<H>> {
+    /// Executes the calls and returns the [CallError] of the first call that fails.
+    ///
+    /// In general, it's recommended to use [MultiCall::call] unless explicit error handling is
     /// required.
-    pub fn try_call(self) -> Result<S::Return, String> {
+    pub fn try_call(self) -> Result<Vec<S::Return>, CallError> {
         let mut evm = new_evm::<_, H>(
             WrapStateDb::new(self.env.db()),
             self.env.cfg_env.clone(),
             self.env.header.inner(),
         );
-        self.tx.transact(&mut evm)
+        transact_all(self.txs, &mut evm)
     }
 
-    /// Executes the call and panics on failure.
+    /// Executes the calls and panics on failure.
     ///
-    /// A convenience wrapper for [CallBuilder::try_call], panicking if the call fails. Useful when
-    /// success is expected.
-    pub fn call(self) -> S::Return {
+    /// A convenience wrapper for [MultiCall::try_call], panicking if any call fails.
+    pub fn call(self) -> Vec<S::Return> {
         self.try_call().unwrap()
     }
 }
 
+/// Executes all the transactions in order in the provided [Evm].
+fn transact_all<S, EXT, DB>(
+    txs: Vec<CallTxData<S>>,
+    evm: &mut Evm<'_, EXT, DB>,
+) -> Result<Vec<S::Return>, CallError>
+where
+    S: SolCall,
+    DB: Database,
+    <DB as Database>::Error: std::error::Error + Send + Sync + 'static,
+{
+    txs.into_iter().map(|tx| tx.transact(evm)).collect()
+}
+
+/// Error returned when executing a call fails.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum CallError {
+    /// The call reverted with the contained revert data.
+    #[error("{}", revert_reason(.0))]
+    Reverted(Bytes),
+    /// The call ran out of gas.
+    #[error("out of gas")]
+    OutOfGas,
+    /// The call halted for any other reason, e.g. an invalid opcode.
+    #[error("halted: {0:?}")]
+    Halted(HaltReason),
+    /// The call succeeded, but its return data could not be decoded.
+    #[error("{0}")]
+    InvalidReturn(String),
+    /// The EVM failed to execute the call, e.g. because of missing state.
+    #[error("EVM error: {0}")]
+    Evm(String),
+}
+
+impl CallError {
+    /// Returns the revert data, if the call reverted.
+    pub fn revert_data(&self) -> Option<&Bytes> {
+        match self {
+            CallError::Reverted(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+/// An estimate of the costs of executing a call in the guest.
+///
+/// See [CallBuilder::estimate] for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CallEstimate {
+    /// Gas used by the EVM execution of the call.
+    pub gas_used: u64,
+    /// Number of accounts accessed during the call.
+    pub accounts_touched: usize,
+    /// Number of storage slots accessed during the call.
+    pub slots_touched: usize,
+}
+
+/// The raw return data of successful calls, keyed by their untyped transaction data.
+type CallCache = Arc<Mutex<BTreeMap<CallTxData<()>, Bytes>>>;
+
 /// Transaction data to be used with [CallBuilder] for an execution.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 struct CallTxData<S> {
     caller: Address,
-    gas_limit: u64,
+    gas_limit: Option<u64>,
     gas_price: U256,
     to: Address,
     value: U256,
     data: Vec<u8>,
+    blob_base_fee: Option<u128>,
     phantom: PhantomData<S>,
 }
 
 impl<S: SolCall> CallTxData<S> {
+    /// Creates the default transaction data for the given contract call.
+    fn new(address: Address, call: &S) -> Self {
+        Self {
+            caller: address, // by default the contract calls itself
+            gas_limit: None, // by default the block gas limit is used
+            gas_price: U256::ZERO,
+            to: address,
+            value: U256::ZERO,
+            data: call.abi_encode(),
+            blob_base_fee: None,
+            phantom: PhantomData,
+        }
+    }
+
     /// Compile-time assertion that the call C has a return value.
     const RETURNS: () = assert!(
         mem::size_of::<S::Return>() > 0,
@@ -320,46 +966,156 @@ impl<S: SolCall> CallTxData<S> {
     );
 
     /// Executes the call in the provided [Evm].
-    fn transact<EXT, DB>(self, evm: &mut Evm<'_, EXT, DB>) -> Result<S::Return, String>
+    fn transact<EXT, DB>(self, evm: &mut Evm<'_, EXT, DB>) -> Result<S::Return, CallError>
     where
         DB: Database,
         <DB as Database>::Error: std::error::Error + Send + Sync + 'static,
     {
+        self.transact_with_estimate(evm).map(|(returns, _)| returns)
+    }
+
+    /// Executes the call like [CallTxData::transact] and returns its [CallEstimate].
+    fn transact_with_estimate<EXT, DB>(
+        self,
+        evm: &mut Evm<'_, EXT, DB>,
+    ) -> Result<(S::Return, CallEstimate), CallError>
+    where
+        DB: Database,
+        <DB as Database>::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let (output, estimate, _) = self.transact_raw(evm)?;
+        let returns = Self::decode_returns(&output)?;
+
+        Ok((returns, estimate))
+    }
+
+    /// Decodes the ABI-encoded return data of the call.
+    fn decode_returns(data: &[u8]) -> Result<S::Return, CallError> {
         #[allow(clippy::let_unit_value)]
         let _ = Self::RETURNS;
 
+        S::abi_decode_returns(data, true).map_err(|err| {
+            CallError::InvalidReturn(format!(
+                "return type invalid; expected '{}': {}",
+                <S::ReturnTuple<'_> as SolType>::SOL_NAME,
+                err
+            ))
+        })
+    }
+
+    /// Removes the type of the call, so that calls of different types can be stored together.
+    #[cfg(feature = "host")]
+    fn into_untyped(self) -> CallTxData<()> {
+        CallTxData {
+            caller: self.caller,
+            gas_limit: self.gas_limit,
+            gas_price: self.gas_price,
+            to: self.to,
+            value: self.value,
+            data: self.data,
+            blob_base_fee: self.blob_base_fee,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S> CallTxData<S> {
+    /// Returns an untyped copy of the transaction data, identifying the call in a [CallCache].
+    fn cache_key(&self) -> CallTxData<()> {
+        CallTxData {
+            caller: self.caller,
+            gas_limit: self.gas_limit,
+            gas_price: self.gas_price,
+            to: self.to,
+            value: self.value,
+            data: self.data.clone(),
+            blob_base_fee: self.blob_base_fee,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Executes the call in the provided [Evm] and returns its raw output without decoding it,
+    /// together with the touched state.
+    fn transact_raw<EXT, DB>(
+        self,
+        evm: &mut Evm<'_, EXT, DB>,
+    ) -> Result<(Bytes, CallEstimate, EvmState), CallError>
+    where
+        DB: Database,
+        <DB as Database>::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let gas_limit = self
+            .gas_limit
+            .unwrap_or_else(|| evm.block().gas_limit.saturating_to());
         let tx_env = evm.tx_mut();
         tx_env.caller = self.caller;
-        tx_env.gas_limit = self.gas_limit;
+        tx_env.gas_limit = gas_limit;
         tx_env.gas_price = self.gas_price;
         tx_env.transact_to = TxKind::Call(self.to);
         tx_env.value = self.value;
         tx_env.data = self.data.into();
+        if let Some(blob_base_fee) = self.blob_base_fee {
+            let blob_env =
+                evm.block_mut()
+                    .blob_excess_gas_and_price
+                    .get_or_insert(BlobExcessGasAndPrice {
+                        excess_blob_gas: 0,
+                        blob_gasprice: 0,
+                    });
+            blob_env.blob_gasprice = blob_base_fee;
+        }
 
-        let ResultAndState { result, .. } = evm
+        let ResultAndState { result, state } = evm
             .transact_preverified()
-            .map_err(|err| format!("EVM error: {:#}", anyhow!(err)))?;
+            .map_err(|err| CallError::Evm(format!("{:#}", anyhow!(err))))?;
+        let estimate = CallEstimate {
+            gas_used: result.gas_used(),
+            accounts_touched: state.len(),
+            slots_touched: state.values().map(|account| account.storage.len()).sum(),
+        };
         let output = match result {
             ExecutionResult::Success { reason, output, .. } => {
                 // there must be a return value to decode
                 if reason != SuccessReason::Return {
-                    Err(format!("did not return: {:?}", reason))
+                    Err(CallError::InvalidReturn(format!(
+                        "did not return: {:?}",
+                        reason
+                    )))
                 } else {
                     Ok(output)
                 }
             }
-            ExecutionResult::Revert { output, .. } => Err(format!("reverted: {}", output)),
-            ExecutionResult::Halt { reason, .. } => Err(format!("halted: {:?}", reason)),
+            ExecutionResult::Revert { output, .. } => Err(CallError::Reverted(output)),
+            ExecutionResult::Halt {
+                reason: HaltReason::OutOfGas(_),
+                ..
+            } => Err(CallError::OutOfGas),
+            ExecutionResult::Halt { reason, .. } => Err(CallError::Halted(reason)),
         }?;
-        let returns = S::abi_decode_returns(&output.into_data(), true).map_err(|err| {
-            format!(
-                "return type invalid; expected '{}': {}",
-                <S::ReturnTuple<'_> as SolType>::SOL_NAME,
-                err
-            )
-        })?;
 
-        Ok(returns)
+        Ok((output.into_data(), estimate, state))
+    }
+}
+
+/// Returns a description of a revert including its decoded reason.
+///
+/// The standard `Error(string)` and `Panic(uint256)` errors are decoded. For custom errors, only
+/// the selector and the raw data can be returned, as their ABI is unknown.
+fn revert_reason(output: &[u8]) -> String {
+    if let Ok(revert) = Revert::abi_decode(output, true) {
+        format!("reverted: {}", revert.reason)
+    } else if let Ok(panic) = Panic::abi_decode(output, true) {
+        format!("reverted with panic code {:#x}", panic.code)
+    } else if output.len() >= 4 {
+        format!(
+            "reverted with custom error {}: {}",
+            Bytes::copy_from_slice(&output[..4]),
+            Bytes::copy_from_slice(&output[4..])
+        )
+    } else if output.is_empty() {
+        "reverted without reason".to_string()
+    } else {
+        format!("reverted: {}", Bytes::copy_from_slice(output))
     }
 }
 
@@ -0,0 +1,184 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers for proving the state of an [OpenZeppelin Governor] proposal.
+//!
+//! This allows proving statements like "proposal X reached quorum at block B" by calling the
+//! corresponding view functions of a Governor contract at the execution block.
+//!
+//! [OpenZeppelin Governor]: https://docs.openzeppelin.com/contracts/5.x/api/governance#Governor
+use crate::{Contract, EvmBlockHeader, GuestEvmEnv};
+use alloy_primitives::{Address, U256};
+
+pub use IGovernor::ProposalState;
+
+alloy_sol_types::sol! {
+    /// The subset of the OpenZeppelin Governor ABI used to prove the state of a proposal.
+    ///
+    /// The `proposalVotes` function is provided by the `GovernorCountingSimple` extension.
+    interface IGovernor {
+        /// The state of a proposal.
+        #[derive(Debug, PartialEq, Eq)]
+        enum ProposalState {
+            Pending,
+            Active,
+            Canceled,
+            Defeated,
+            Succeeded,
+            Queued,
+            Expired,
+            Executed
+        }
+
+        function state(uint256 proposalId) external view returns (ProposalState);
+        function proposalSnapshot(uint256 proposalId) external view returns (uint256);
+        function proposalVotes(uint256 proposalId) external view returns (uint256 againstVotes, uint256 forVotes, uint256 abstainVotes);
+        function quorum(uint256 timepoint) external view returns (uint256);
+    }
+}
+
+/// The votes cast for a proposal.
+pub type ProposalVotes = IGovernor::proposalVotesReturn;
+
+/// Returns whether the `votes` reach the `quorum`.
+///
+/// As in `GovernorCountingSimple`, both the for and the abstain votes count towards the quorum.
+pub fn quorum_reached(quorum: U256, votes: &ProposalVotes) -> bool {
+    quorum <= votes.forVotes.saturating_add(votes.abstainVotes)
+}
+
+/// A Governor contract that is initialized with a specific environment and contract address.
+pub struct Governor<E> {
+    contract: Contract<E>,
+}
+
+impl<'a, H: EvmBlockHeader> Governor<&'a GuestEvmEnv<H>> {
+    /// Constructor for executing calls to a Governor contract in the guest.
+    pub fn new(address: Address, env: &'a GuestEvmEnv<H>) -> Self {
+        Self {
+            contract: Contract::new(address, env),
+        }
+    }
+
+    /// Returns the current state of the proposal.
+    pub fn state(&self, proposal_id: U256) -> ProposalState {
+        let call = IGovernor::stateCall {
+            proposalId: proposal_id,
+        };
+        self.contract.call_builder(&call).call()._0
+    }
+
+    /// Returns the votes cast for the proposal.
+    pub fn proposal_votes(&self, proposal_id: U256) -> ProposalVotes {
+        let call = IGovernor::proposalVotesCall {
+            proposalId: proposal_id,
+        };
+        self.contract.call_builder(&call).call()
+    }
+
+    /// Returns the minimum number of votes required for a proposal to succeed at `timepoint`.
+    pub fn quorum(&self, timepoint: U256) -> U256 {
+        let call = IGovernor::quorumCall { timepoint };
+        self.contract.call_builder(&call).call()._0
+    }
+
+    /// Returns whether the proposal has reached the quorum at its snapshot.
+    pub fn reached_quorum(&self, proposal_id: U256) -> bool {
+        let call = IGovernor::proposalSnapshotCall {
+            proposalId: proposal_id,
+        };
+        let snapshot = self.contract.call_builder(&call).call()._0;
+
+        quorum_reached(self.quorum(snapshot), &self.proposal_votes(proposal_id))
+    }
+}
+
+#[cfg(feature = "host")]
+mod host {
+    use super::*;
+    use crate::host::{db::AlloyDb, HostEvmEnv};
+    use alloy::{network::Network, providers::Provider, transports::Transport};
+    use anyhow::Result;
+
+    impl<'a, T, N, P, H, C> Governor<&'a mut HostEvmEnv<AlloyDb<T, N, P>, H, C>>
+    where
+        T: Transport + Clone,
+        N: Network,
+        P: Provider<T, N> + Send + 'static,
+        H: EvmBlockHeader + Clone + Send + 'static,
+    {
+        /// Constructor for preflighting calls to a Governor contract on the host.
+        pub fn preflight(
+            address: Address,
+            env: &'a mut HostEvmEnv<AlloyDb<T, N, P>, H, C>,
+        ) -> Self {
+            Self {
+                contract: Contract::preflight(address, env),
+            }
+        }
+
+        /// Preflights [Governor::state].
+        pub async fn state(&mut self, proposal_id: U256) -> Result<ProposalState> {
+            let call = IGovernor::stateCall {
+                proposalId: proposal_id,
+            };
+            Ok(self.contract.call_builder(&call).call().await?._0)
+        }
+
+        /// Preflights [Governor::proposal_votes].
+        pub async fn proposal_votes(&mut self, proposal_id: U256) -> Result<ProposalVotes> {
+            let call = IGovernor::proposalVotesCall {
+                proposalId: proposal_id,
+            };
+            self.contract.call_builder(&call).call().await
+        }
+
+        /// Preflights [Governor::quorum].
+        pub async fn quorum(&mut self, timepoint: U256) -> Result<U256> {
+            let call = IGovernor::quorumCall { timepoint };
+            Ok(self.contract.call_builder(&call).call().await?._0)
+        }
+
+        /// Preflights [Governor::reached_quorum].
+        pub async fn reached_quorum(&mut self, proposal_id: U256) -> Result<bool> {
+            let call = IGovernor::proposalSnapshotCall {
+                proposalId: proposal_id,
+            };
+            let snapshot = self.contract.call_builder(&call).call().await?._0;
+            let quorum = self.quorum(snapshot).await?;
+
+            Ok(quorum_reached(
+                quorum,
+                &self.proposal_votes(proposal_id).await?,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::uint;
+
+    #[test]
+    fn counting_simple_quorum() {
+        let votes = ProposalVotes {
+            againstVotes: uint!(100_U256),
+            forVotes: uint!(30_U256),
+            abstainVotes: uint!(20_U256),
+        };
+        assert!(quorum_reached(uint!(50_U256), &votes));
+        assert!(!quorum_reached(uint!(51_U256), &votes));
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Type aliases and specifications for Gnosis Chain.
+//!
+//! Since the Merge, Gnosis Chain uses the same execution block header as Ethereum, so the
+//! [EthBlockHeader] can be used without modifications. Blocks produced before the Merge were sealed
+//! by the AuRa consensus engine, whose headers contain a `step` and `signature` instead of the
+//! `mixHash` and `nonce`. As the hash of these headers cannot be computed from an
+//! [EthBlockHeader], only post-Merge blocks can be used for the execution or the commitment.
+//!
+//! The fork schedule nevertheless covers the entire history of the chain, so that
+//! [ChainSpec::active_fork] resolves to the correct [SpecId] for any block.
+//!
+//! **Note:** Gnosis Chain uses different EIP-4844 blob fee parameters than Ethereum. As revm
+//! derives the blob gas price using the Ethereum parameters, the value returned by the
+//! `BLOBBASEFEE` opcode does not match the actual blob gas price on Gnosis Chain.
+//!
+//! [EthBlockHeader]: crate::ethereum::EthBlockHeader
+use std::{collections::BTreeMap, sync::LazyLock};
+
+use crate::config::{ChainSpec, ForkCondition};
+use revm::primitives::SpecId;
+
+/// The block number of the Merge on Gnosis Chain.
+///
+/// This is the first block with a header compatible with [EthBlockHeader].
+///
+/// [EthBlockHeader]: crate::ethereum::EthBlockHeader
+pub const GNOSIS_MERGE_BLOCK: u64 = 25349536;
+
+/// The Gnosis Chain (formerly xDai) [ChainSpec].
+pub static GNOSIS_CHAIN_SPEC: LazyLock<ChainSpec> = LazyLock::new(|| ChainSpec {
+    chain_id: 100,
+    forks: BTreeMap::from([
+        // the AuRa chain launched with the Byzantium rules
+        (SpecId::BYZANTIUM, ForkCondition::Block(0)),
+        (SpecId::CONSTANTINOPLE, ForkCondition::Block(1604400)),
+        (SpecId::PETERSBURG, ForkCondition::Block(2508800)),
+        (SpecId::ISTANBUL, ForkCondition::Block(7298030)),
+        (SpecId::BERLIN, ForkCondition::Block(16101500)),
+        (SpecId::LONDON, ForkCondition::Block(19040000)),
+        (SpecId::MERGE, ForkCondition::Block(GNOSIS_MERGE_BLOCK)),
+        (SpecId::SHANGHAI, ForkCondition::Timestamp(1690889660)),
+        (SpecId::CANCUN, ForkCondition::Timestamp(1710181820)),
+    ]),
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn active_fork() {
+        let spec = &*GNOSIS_CHAIN_SPEC;
+
+        assert_eq!(spec.active_fork(0, 0).unwrap(), SpecId::BYZANTIUM);
+        assert_eq!(spec.active_fork(19040000, 0).unwrap(), SpecId::LONDON);
+        assert_eq!(
+            spec.active_fork(GNOSIS_MERGE_BLOCK - 1, 0).unwrap(),
+            SpecId::LONDON
+        );
+        assert_eq!(
+            spec.active_fork(GNOSIS_MERGE_BLOCK, 0).unwrap(),
+            SpecId::MERGE
+        );
+        assert_eq!(
+            spec.active_fork(GNOSIS_MERGE_BLOCK, 1690889660).unwrap(),
+            SpecId::SHANGHAI
+        );
+        assert_eq!(
+            spec.active_fork(GNOSIS_MERGE_BLOCK, 1710181820).unwrap(),
+            SpecId::CANCUN
+        );
+    }
+}
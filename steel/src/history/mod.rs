@@ -17,6 +17,7 @@ use crate::{
     beacon, BlockHeaderCommit, Commitment, CommitmentVersion, ComposeInput, EvmBlockHeader,
 };
 use alloy_primitives::{Sealed, B256, U256};
+use anyhow::{anyhow, ensure, Context};
 use beacon::{BeaconCommit, GeneralizedBeaconCommit, STATE_ROOT_LEAF_INDEX};
 use beacon_roots::BeaconRootsContract;
 use serde::{Deserialize, Serialize};
@@ -52,8 +53,15 @@ impl<H: EvmBlockHeader> BlockHeaderCommit<H> for HistoryCommit {
     /// history. Panics if the provided [HistoryCommit] data is invalid or inconsistent.
     #[inline]
     fn commit(self, header: &Sealed<H>, config_id: B256) -> Commitment {
+        self.try_commit(header, config_id)
+            .expect("Invalid history commitment")
+    }
+
+    /// Generates a commitment that proves the given block header is included in the Beacon Chain's
+    /// history. Returns an error if the provided [HistoryCommit] data is invalid or inconsistent.
+    fn try_commit(self, header: &Sealed<H>, config_id: B256) -> anyhow::Result<Commitment> {
         // first, compute the beacon commit of the EVM execution
-        let initial_commitment = self.evm_commit.commit(header, config_id);
+        let initial_commitment = self.evm_commit.try_commit(header, config_id)?;
         let (mut timestamp, version) = initial_commitment.decode_id();
         // just a sanity check, a BeaconCommit will always have this version
         assert_eq!(version, CommitmentVersion::Beacon as u16);
@@ -65,21 +73,24 @@ impl<H: EvmBlockHeader> BlockHeaderCommit<H> for HistoryCommit {
             let state_root = state_commit.state.root();
             let commitment_root =
                 BeaconRootsContract::get_from_state(state_commit.state, timestamp)
-                    .expect("Beacon roots contract failed");
-            assert_eq!(commitment_root, beacon_root, "Beacon root does not match");
+                    .map_err(|err| anyhow!("Beacon roots contract failed: {:?}", err))?;
+            ensure!(commitment_root == beacon_root, "Beacon root does not match");
 
             // compute the beacon commitment of the current state
-            let (commit_ts, commit_beacon_root) = state_commit.state_commit.into_commit(state_root);
-            timestamp = U256::from(commit_ts);
+            let commit_beacon_root = state_commit
+                .state_commit
+                .process_proof(state_root)
+                .context("invalid beacon inclusion proof")?;
+            timestamp = U256::from(state_commit.state_commit.timestamp());
             beacon_root = commit_beacon_root;
         }
 
-        Commitment::new(
+        Ok(Commitment::new(
             CommitmentVersion::Beacon as u16,
             timestamp.to(),
             beacon_root,
             initial_commitment.configID,
-        )
+        ))
     }
 }
 
@@ -87,7 +98,7 @@ impl<H: EvmBlockHeader> BlockHeaderCommit<H> for HistoryCommit {
 mod host {
     use super::*;
     use crate::{
-        beacon::host::{client::BeaconClient, create_beacon_commit},
+        beacon::host::{client::BeaconClient, create_beacon_commit, ProofCache},
         ethereum::EthBlockHeader,
     };
     use alloy::{
@@ -100,6 +111,19 @@ mod host {
     use url::Url;
 
     impl HistoryCommit {
+        /// Returns the maximum number of blocks between two consecutive state commitments.
+        pub(crate) fn step() -> BlockNumber {
+            // we assume that not more than 25% of the blocks have been skipped
+            // TODO(#309): implement a more sophisticated way to determine the step size
+            BeaconRootsContract::HISTORY_BUFFER_LENGTH.to::<BlockNumber>() * 75 / 100
+        }
+
+        /// Returns the number of state commitments [HistoryCommit::from_headers] creates to chain
+        /// the execution block `from` to the commitment block `to`.
+        pub(crate) fn depth(from: BlockNumber, to: BlockNumber) -> usize {
+            to.saturating_sub(from).div_ceil(Self::step()) as usize
+        }
+
         /// Creates a `HistoryCommit` from an EVM block header and a commitment header.
         ///
         /// This method fetches the necessary data from the Ethereum and Beacon chain to construct a
@@ -110,6 +134,7 @@ mod host {
             commitment_header: &Sealed<EthBlockHeader>,
             rpc_provider: P,
             beacon_url: Url,
+            cache: &ProofCache,
         ) -> anyhow::Result<Self>
         where
             T: Transport + Clone,
@@ -122,17 +147,15 @@ mod host {
             let client = BeaconClient::new(beacon_url.clone()).context("invalid URL")?;
 
             // create a regular beacon commit to the block header used for EVM execution
-            let evm_commit =
-                BeaconCommit::from_header(evm_header, &rpc_provider, beacon_url).await?;
+            let (evm_commit, _) =
+                BeaconCommit::from_header(evm_header, &rpc_provider, beacon_url, cache).await?;
             let mut commit_ts = evm_commit.timestamp();
             // safe unwrap: BeaconCommit::from_header checks that the proof can be processed
             let mut commit_beacon_root = evm_commit.process_proof(evm_header.seal()).unwrap();
 
             let mut state_commits: Vec<StateCommit> = Vec::new();
 
-            // we assume that not more than 25% of the blocks have been skipped
-            // TODO(#309): implement a more sophisticated way to determine the step size
-            let step = BeaconRootsContract::HISTORY_BUFFER_LENGTH.to::<BlockNumber>() * 75 / 100;
+            let step = Self::step();
             let target = commitment_header.number();
 
             let mut state_block = evm_header.number;
@@ -173,9 +196,14 @@ mod host {
                 );
 
                 // create a beacon commitment to that state
-                let (state_commit, beacon_root) =
-                    create_beacon_commit(&header, "state_root".into(), &rpc_provider, &client)
-                        .await?;
+                let (state_commit, beacon_root, _) = create_beacon_commit(
+                    &header,
+                    "state_root".into(),
+                    &rpc_provider,
+                    &client,
+                    cache,
+                )
+                .await?;
                 state_commit
                     .verify(state.root(), beacon_root)
                     .context("proof derived from API does not verify")?;
@@ -201,7 +229,7 @@ mod host {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ethereum::EthBlockHeader;
+    use crate::{beacon::host::ProofCache, ethereum::EthBlockHeader};
     use alloy::{
         network::primitives::BlockTransactionsKind,
         providers::{Provider, ProviderBuilder},
@@ -211,6 +239,16 @@ mod tests {
     const EL_URL: &str = "https://ethereum-rpc.publicnode.com";
     const CL_URL: &str = "https://ethereum-beacon-api.publicnode.com";
 
+    #[test]
+    fn depth() {
+        let step = HistoryCommit::step();
+        assert_eq!(HistoryCommit::depth(100, 100), 0);
+        assert_eq!(HistoryCommit::depth(100, 101), 1);
+        assert_eq!(HistoryCommit::depth(100, 100 + step), 1);
+        assert_eq!(HistoryCommit::depth(100, 101 + step), 2);
+        assert_eq!(HistoryCommit::depth(100, 100 + 10 * step), 10);
+    }
+
     #[tokio::test]
     #[ignore = "queries actual RPC nodes"]
     async fn from_beacon_commit_and_header() {
@@ -220,10 +258,15 @@ mod tests {
         let headers = get_headers(4).await.unwrap();
 
         // create a history commitment executing on header[0] and committing to header[2]
-        let commit =
-            HistoryCommit::from_headers(&headers[0], &headers[2], &el, CL_URL.parse().unwrap())
-                .await
-                .unwrap();
+        let commit = HistoryCommit::from_headers(
+            &headers[0],
+            &headers[2],
+            &el,
+            CL_URL.parse().unwrap(),
+            &ProofCache::default(),
+        )
+        .await
+        .unwrap();
 
         let [StateCommit {
             state,
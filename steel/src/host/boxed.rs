@@ -0,0 +1,386 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloy::{
+    network::{primitives::BlockTransactionsKind, Ethereum, Network},
+    providers::{
+        utils::{Eip1559Estimation, EstimatorFunction},
+        EthCall, FilterPollerBuilder, PendingTransaction, PendingTransactionBuilder,
+        PendingTransactionConfig, PendingTransactionError, Provider, ProviderCall, RootProvider,
+        RpcWithBlock, SendableTx,
+    },
+    rpc::{
+        client::{ClientRef, NoParams, WeakClient},
+        types::{
+            simulate::{SimulatePayload, SimulatedBlock},
+            AccessListResult, BlockId, BlockNumberOrTag, EIP1186AccountProofResponse, FeeHistory,
+            Filter, FilterChanges, Index, Log, SyncStatus,
+        },
+    },
+    transports::{BoxTransport, TransportResult},
+};
+use alloy_primitives::{
+    Address, BlockHash, BlockNumber, Bytes, StorageKey, StorageValue, TxHash, B256, U128, U256, U64,
+};
+use serde_json::value::RawValue;
+use std::{borrow::Cow, fmt, sync::Arc};
+
+/// A [Provider] with an erased type, e.g. to use a provider with several custom layers.
+///
+/// Every call is forwarded to the wrapped provider, so that all its layers still apply. Only the
+/// pub-sub subscriptions are not forwarded, as they are not supported by boxed transports. See
+/// [EvmEnvBuilder::boxed_provider](super::EvmEnvBuilder::boxed_provider).
+pub struct BoxedProvider<N: Network = Ethereum>(Arc<dyn Provider<BoxTransport, N>>);
+
+impl<N: Network> BoxedProvider<N> {
+    /// Erases the type of the given provider.
+    pub fn new<P: Provider<BoxTransport, N> + 'static>(provider: P) -> Self {
+        Self(Arc::new(provider))
+    }
+}
+
+impl<N: Network> Clone for BoxedProvider<N> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<N: Network> fmt::Debug for BoxedProvider<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BoxedProvider").field(self.0.root()).finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl<N: Network> Provider<BoxTransport, N> for BoxedProvider<N> {
+    fn root(&self) -> &RootProvider<BoxTransport, N> {
+        self.0.root()
+    }
+
+    fn client(&self) -> ClientRef<'_, BoxTransport> {
+        self.0.client()
+    }
+
+    fn weak_client(&self) -> WeakClient<BoxTransport> {
+        self.0.weak_client()
+    }
+
+    fn get_accounts(&self) -> ProviderCall<BoxTransport, NoParams, Vec<Address>> {
+        self.0.get_accounts()
+    }
+
+    fn get_blob_base_fee(&self) -> ProviderCall<BoxTransport, NoParams, U128, u128> {
+        self.0.get_blob_base_fee()
+    }
+
+    fn get_block_number(&self) -> ProviderCall<BoxTransport, NoParams, U64, BlockNumber> {
+        self.0.get_block_number()
+    }
+
+    fn call<'req>(&self, tx: &'req N::TransactionRequest) -> EthCall<'req, BoxTransport, N, Bytes> {
+        self.0.call(tx)
+    }
+
+    fn simulate<'req>(
+        &self,
+        payload: &'req SimulatePayload,
+    ) -> RpcWithBlock<BoxTransport, &'req SimulatePayload, Vec<SimulatedBlock<N::BlockResponse>>>
+    {
+        self.0.simulate(payload)
+    }
+
+    fn get_chain_id(&self) -> ProviderCall<BoxTransport, NoParams, U64, u64> {
+        self.0.get_chain_id()
+    }
+
+    fn create_access_list<'a>(
+        &self,
+        request: &'a N::TransactionRequest,
+    ) -> RpcWithBlock<BoxTransport, &'a N::TransactionRequest, AccessListResult> {
+        self.0.create_access_list(request)
+    }
+
+    fn estimate_gas<'req>(
+        &self,
+        tx: &'req N::TransactionRequest,
+    ) -> EthCall<'req, BoxTransport, N, U64, u64> {
+        self.0.estimate_gas(tx)
+    }
+
+    async fn estimate_eip1559_fees(
+        &self,
+        estimator: Option<EstimatorFunction>,
+    ) -> TransportResult<Eip1559Estimation> {
+        self.0.estimate_eip1559_fees(estimator).await
+    }
+
+    async fn get_fee_history(
+        &self,
+        block_count: u64,
+        last_block: BlockNumberOrTag,
+        reward_percentiles: &[f64],
+    ) -> TransportResult<FeeHistory> {
+        self.0
+            .get_fee_history(block_count, last_block, reward_percentiles)
+            .await
+    }
+
+    fn get_gas_price(&self) -> ProviderCall<BoxTransport, NoParams, U128, u128> {
+        self.0.get_gas_price()
+    }
+
+    fn get_account(
+        &self,
+        address: Address,
+    ) -> RpcWithBlock<BoxTransport, Address, alloy_consensus::Account> {
+        self.0.get_account(address)
+    }
+
+    fn get_balance(&self, address: Address) -> RpcWithBlock<BoxTransport, Address, U256, U256> {
+        self.0.get_balance(address)
+    }
+
+    async fn get_block(
+        &self,
+        block: BlockId,
+        kind: BlockTransactionsKind,
+    ) -> TransportResult<Option<N::BlockResponse>> {
+        self.0.get_block(block, kind).await
+    }
+
+    async fn get_block_by_hash(
+        &self,
+        hash: BlockHash,
+        kind: BlockTransactionsKind,
+    ) -> TransportResult<Option<N::BlockResponse>> {
+        self.0.get_block_by_hash(hash, kind).await
+    }
+
+    async fn get_block_by_number(
+        &self,
+        number: BlockNumberOrTag,
+        kind: BlockTransactionsKind,
+    ) -> TransportResult<Option<N::BlockResponse>> {
+        self.0.get_block_by_number(number, kind).await
+    }
+
+    fn get_block_receipts(
+        &self,
+        block: BlockId,
+    ) -> ProviderCall<BoxTransport, (BlockId,), Option<Vec<N::ReceiptResponse>>> {
+        self.0.get_block_receipts(block)
+    }
+
+    fn get_code_at(&self, address: Address) -> RpcWithBlock<BoxTransport, Address, Bytes> {
+        self.0.get_code_at(address)
+    }
+
+    async fn watch_blocks(&self) -> TransportResult<FilterPollerBuilder<BoxTransport, B256>> {
+        self.0.watch_blocks().await
+    }
+
+    async fn watch_pending_transactions(
+        &self,
+    ) -> TransportResult<FilterPollerBuilder<BoxTransport, B256>> {
+        self.0.watch_pending_transactions().await
+    }
+
+    async fn watch_logs(
+        &self,
+        filter: &Filter,
+    ) -> TransportResult<FilterPollerBuilder<BoxTransport, Log>> {
+        self.0.watch_logs(filter).await
+    }
+
+    async fn watch_full_pending_transactions(
+        &self,
+    ) -> TransportResult<FilterPollerBuilder<BoxTransport, N::TransactionResponse>> {
+        self.0.watch_full_pending_transactions().await
+    }
+
+    async fn get_filter_changes_dyn(&self, id: U256) -> TransportResult<FilterChanges> {
+        self.0.get_filter_changes_dyn(id).await
+    }
+
+    async fn watch_pending_transaction(
+        &self,
+        config: PendingTransactionConfig,
+    ) -> Result<PendingTransaction, PendingTransactionError> {
+        self.0.watch_pending_transaction(config).await
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> TransportResult<Vec<Log>> {
+        self.0.get_logs(filter).await
+    }
+
+    fn get_proof(
+        &self,
+        address: Address,
+        keys: Vec<StorageKey>,
+    ) -> RpcWithBlock<BoxTransport, (Address, Vec<StorageKey>), EIP1186AccountProofResponse> {
+        self.0.get_proof(address, keys)
+    }
+
+    fn get_storage_at(
+        &self,
+        address: Address,
+        key: U256,
+    ) -> RpcWithBlock<BoxTransport, (Address, U256), StorageValue> {
+        self.0.get_storage_at(address, key)
+    }
+
+    fn get_transaction_by_hash(
+        &self,
+        hash: TxHash,
+    ) -> ProviderCall<BoxTransport, (TxHash,), Option<N::TransactionResponse>> {
+        self.0.get_transaction_by_hash(hash)
+    }
+
+    fn get_transaction_by_block_hash_and_index(
+        &self,
+        block_hash: B256,
+        index: usize,
+    ) -> ProviderCall<BoxTransport, (B256, Index), Option<N::TransactionResponse>> {
+        self.0
+            .get_transaction_by_block_hash_and_index(block_hash, index)
+    }
+
+    fn get_raw_transaction_by_block_hash_and_index(
+        &self,
+        block_hash: B256,
+        index: usize,
+    ) -> ProviderCall<BoxTransport, (B256, Index), Option<Bytes>> {
+        self.0
+            .get_raw_transaction_by_block_hash_and_index(block_hash, index)
+    }
+
+    fn get_transaction_by_block_number_and_index(
+        &self,
+        block_number: BlockNumberOrTag,
+        index: usize,
+    ) -> ProviderCall<BoxTransport, (BlockNumberOrTag, Index), Option<N::TransactionResponse>> {
+        self.0
+            .get_transaction_by_block_number_and_index(block_number, index)
+    }
+
+    fn get_raw_transaction_by_block_number_and_index(
+        &self,
+        block_number: BlockNumberOrTag,
+        index: usize,
+    ) -> ProviderCall<BoxTransport, (BlockNumberOrTag, Index), Option<Bytes>> {
+        self.0
+            .get_raw_transaction_by_block_number_and_index(block_number, index)
+    }
+
+    fn get_raw_transaction_by_hash(
+        &self,
+        hash: TxHash,
+    ) -> ProviderCall<BoxTransport, (TxHash,), Option<Bytes>> {
+        self.0.get_raw_transaction_by_hash(hash)
+    }
+
+    fn get_transaction_count(
+        &self,
+        address: Address,
+    ) -> RpcWithBlock<BoxTransport, Address, U64, u64, fn(U64) -> u64> {
+        self.0.get_transaction_count(address)
+    }
+
+    fn get_transaction_receipt(
+        &self,
+        hash: TxHash,
+    ) -> ProviderCall<BoxTransport, (TxHash,), Option<N::ReceiptResponse>> {
+        self.0.get_transaction_receipt(hash)
+    }
+
+    async fn get_uncle(&self, tag: BlockId, idx: u64) -> TransportResult<Option<N::BlockResponse>> {
+        self.0.get_uncle(tag, idx).await
+    }
+
+    async fn get_uncle_count(&self, tag: BlockId) -> TransportResult<u64> {
+        self.0.get_uncle_count(tag).await
+    }
+
+    fn get_max_priority_fee_per_gas(&self) -> ProviderCall<BoxTransport, NoParams, U128, u128> {
+        self.0.get_max_priority_fee_per_gas()
+    }
+
+    async fn new_block_filter(&self) -> TransportResult<U256> {
+        self.0.new_block_filter().await
+    }
+
+    async fn new_filter(&self, filter: &Filter) -> TransportResult<U256> {
+        self.0.new_filter(filter).await
+    }
+
+    async fn new_pending_transactions_filter(&self, full: bool) -> TransportResult<U256> {
+        self.0.new_pending_transactions_filter(full).await
+    }
+
+    async fn send_raw_transaction(
+        &self,
+        encoded_tx: &[u8],
+    ) -> TransportResult<PendingTransactionBuilder<BoxTransport, N>> {
+        self.0.send_raw_transaction(encoded_tx).await
+    }
+
+    async fn send_transaction(
+        &self,
+        tx: N::TransactionRequest,
+    ) -> TransportResult<PendingTransactionBuilder<BoxTransport, N>> {
+        self.0.send_transaction(tx).await
+    }
+
+    async fn send_tx_envelope(
+        &self,
+        tx: N::TxEnvelope,
+    ) -> TransportResult<PendingTransactionBuilder<BoxTransport, N>> {
+        self.0.send_tx_envelope(tx).await
+    }
+
+    async fn send_transaction_internal(
+        &self,
+        tx: SendableTx<N>,
+    ) -> TransportResult<PendingTransactionBuilder<BoxTransport, N>> {
+        self.0.send_transaction_internal(tx).await
+    }
+
+    fn syncing(&self) -> ProviderCall<BoxTransport, NoParams, SyncStatus> {
+        self.0.syncing()
+    }
+
+    fn get_client_version(&self) -> ProviderCall<BoxTransport, NoParams, String> {
+        self.0.get_client_version()
+    }
+
+    fn get_sha3(&self, data: &[u8]) -> ProviderCall<BoxTransport, (String,), B256> {
+        self.0.get_sha3(data)
+    }
+
+    fn get_net_version(&self) -> ProviderCall<BoxTransport, NoParams, U64, u64> {
+        self.0.get_net_version()
+    }
+
+    async fn raw_request_dyn(
+        &self,
+        method: Cow<'static, str>,
+        params: &RawValue,
+    ) -> TransportResult<Box<RawValue>> {
+        self.0.raw_request_dyn(method, params).await
+    }
+
+    fn transaction_request(&self) -> N::TransactionRequest {
+        self.0.transaction_request()
+    }
+}
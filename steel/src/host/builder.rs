@@ -13,27 +13,40 @@
 // limitations under the License.
 
 use crate::{
-    beacon::BeaconCommit,
+    beacon::{host::ProofCache, BeaconCommit},
     config::ChainSpec,
     ethereum::EthBlockHeader,
     history::HistoryCommit,
     host::{
-        db::{AlloyDb, ProofDb, ProviderConfig},
-        BlockNumberOrTag, EthHostEvmEnv, HostCommit, HostEvmEnv,
+        db::{AlloyDb, ProgressCallback, ProofDb, ProofProgress, ProviderConfig},
+        BlockNumberOrTag, BoxedProvider, Error, EthHostEvmEnv, HostCommit, HostEvmEnv,
     },
-    EvmBlockHeader, EvmEnv,
+    Contract, EvmBlockHeader, EvmEnv, EvmInput,
 };
+#[cfg(feature = "ipc")]
+use alloy::providers::IpcConnect;
+#[cfg(feature = "ws")]
+use alloy::providers::WsConnect;
 use alloy::{
     network::{
         primitives::{BlockTransactionsKind, HeaderResponse},
         BlockResponse, Ethereum, Network,
     },
     providers::{Provider, ProviderBuilder, ReqwestProvider},
-    transports::Transport,
+    rpc::client::RpcClient,
+    transports::{
+        http::{Client, Http},
+        BoxTransport, Transport,
+    },
 };
-use alloy_primitives::Sealed;
-use anyhow::{anyhow, ensure, Context, Result};
-use std::{fmt::Display, marker::PhantomData};
+#[cfg(any(feature = "ws", feature = "ipc"))]
+use alloy::{providers::RootProvider, pubsub::PubSubFrontend};
+use alloy_primitives::{Address, Sealed, B256};
+use alloy_sol_types::SolCall;
+use anyhow::{anyhow, Result};
+use futures_util::future;
+use serde::Serialize;
+use std::{fmt::Display, marker::PhantomData, path::PathBuf, sync::Arc, time::Duration};
 use url::Url;
 
 impl<H> EvmEnv<(), H, ()> {
@@ -52,10 +65,14 @@ impl<H> EvmEnv<(), H, ()> {
     /// ```
     pub fn builder() -> EvmEnvBuilder<(), H, ()> {
         EvmEnvBuilder {
-            provider: (),
+            provider: Ok(()),
             provider_config: ProviderConfig::default(),
+            http_timeout: None,
+            block_hash: None,
+            chain_spec: None,
             block: BlockNumberOrTag::Latest,
             beacon_config: (),
+            beacon_cache: ProofCache::default(),
             phantom: PhantomData,
         }
     }
@@ -78,17 +95,140 @@ impl<H> EvmEnv<(), H, ()> {
 /// - `B`: The type of the configuration to access the Beacon API.
 #[derive(Clone, Debug)]
 pub struct EvmEnvBuilder<P, H, B> {
-    provider: P,
+    /// The provider, or the error if the HTTP client of [EvmEnvBuilder::rpc] failed to build.
+    provider: Result<P, Arc<reqwest::Error>>,
     provider_config: ProviderConfig,
+    http_timeout: Option<Duration>,
     block: BlockNumberOrTag,
+    block_hash: Option<B256>,
+    chain_spec: Option<ChainSpec>,
     beacon_config: B,
+    /// Cache of the Beacon API proofs, shared by all clones of the builder.
+    beacon_cache: ProofCache,
     phantom: PhantomData<H>,
 }
 
 impl EvmEnvBuilder<(), EthBlockHeader, ()> {
     /// Sets the Ethereum HTTP RPC endpoint that will be used by the [EvmEnv].
+    ///
+    /// If the HTTP client cannot be created, e.g. because the TLS backend fails to initialize,
+    /// the error is returned when building the environment.
     pub fn rpc(self, url: Url) -> EvmEnvBuilder<ReqwestProvider<Ethereum>, EthBlockHeader, ()> {
-        self.provider(ProviderBuilder::new().on_http(url))
+        let mut client = Client::builder();
+        if let Some(timeout) = self.http_timeout {
+            client = client.timeout(timeout);
+        }
+        match client.build() {
+            Ok(client) => self.rpc_with_client(url, client),
+            Err(err) => self.with_provider(Err(Arc::new(err))),
+        }
+    }
+
+    /// Sets the Ethereum HTTP RPC endpoint together with the [reqwest::Client] used to access it.
+    ///
+    /// This allows sending custom headers, e.g. for authentication, or using a proxy for all RPC
+    /// calls of the preflight without embedding any credentials in the URL. Any timeout configured
+    /// with [EvmEnvBuilder::http_timeout] is ignored, it must be set on the client instead.
+    ///
+    /// ```rust,no_run
+    /// # use risc0_steel::ethereum::EthEvmEnv;
+    /// # use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+    /// # use url::Url;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// # let url = Url::parse("https://ethereum-rpc.publicnode.com")?;
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer <API key>"));
+    /// let client = reqwest::Client::builder().default_headers(headers).build()?;
+    ///
+    /// let env = EthEvmEnv::builder()
+    ///     .rpc_with_client(url, client)
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rpc_with_client(
+        self,
+        url: Url,
+        client: Client,
+    ) -> EvmEnvBuilder<ReqwestProvider<Ethereum>, EthBlockHeader, ()> {
+        let transport = Http::with_client(client, url);
+        let is_local = transport.guess_local();
+        self.provider(ProviderBuilder::new().on_client(RpcClient::new(transport, is_local)))
+    }
+
+    /// Sets the Ethereum WebSocket RPC endpoint that will be used by the [EvmEnv].
+    ///
+    /// This connects to the endpoint immediately and returns an error if the connection fails.
+    ///
+    /// ```rust,no_run
+    /// # use risc0_steel::{ethereum::EthEvmEnv, Contract};
+    /// # use alloy_primitives::address;
+    /// # use alloy_sol_types::sol;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// sol! {
+    ///     interface IERC20 {
+    ///         function balanceOf(address account) external view returns (uint);
+    ///     }
+    /// }
+    ///
+    /// // connect to a local Geth node with WebSocket enabled, i.e. `geth --ws`
+    /// let mut env = EthEvmEnv::builder()
+    ///     .ws("ws://localhost:8546".parse()?)
+    ///     .await?
+    ///     .build()
+    ///     .await?;
+    ///
+    /// let contract_address = address!("dAC17F958D2ee523a2206206994597C13D831ec7");
+    /// let call = IERC20::balanceOfCall {
+    ///     account: address!("F977814e90dA44bFA03b6295A0616a897441aceC"),
+    /// };
+    /// let mut contract = Contract::preflight(contract_address, &mut env);
+    /// let balance = contract.call_builder(&call).call().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "ws")]
+    pub async fn ws(
+        self,
+        url: Url,
+    ) -> Result<EvmEnvBuilder<RootProvider<PubSubFrontend>, EthBlockHeader, ()>, Error> {
+        let provider = ProviderBuilder::new()
+            .on_ws(WsConnect::new(url))
+            .await
+            .map_err(|err| Error::Connection("WebSocket endpoint", err))?;
+
+        Ok(self.provider(provider))
+    }
+
+    /// Sets the IPC socket of a local Ethereum node that will be used by the [EvmEnv].
+    ///
+    /// This connects to the socket immediately and returns an error if the connection fails.
+    #[cfg(feature = "ipc")]
+    pub async fn ipc(
+        self,
+        path: impl Into<PathBuf>,
+    ) -> Result<EvmEnvBuilder<RootProvider<PubSubFrontend>, EthBlockHeader, ()>, Error> {
+        let provider = ProviderBuilder::new()
+            .on_ipc(IpcConnect::new(path.into()))
+            .await
+            .map_err(|err| Error::Connection("IPC socket", err))?;
+
+        Ok(self.provider(provider))
+    }
+
+    /// Sets the timeout for each HTTP request sent to the endpoint configured with
+    /// [EvmEnvBuilder::rpc].
+    ///
+    /// The timeout applies to all RPC calls of the preflight, including fetching the block header
+    /// and large `eth_getProof` requests, which can be slow on some archive nodes. As the HTTP
+    /// client is created by [EvmEnvBuilder::rpc], this must be called before it. By default, no
+    /// timeout is set. Custom providers set with [EvmEnvBuilder::provider] are not affected.
+    pub fn http_timeout(mut self, timeout: Duration) -> Self {
+        self.http_timeout = Some(timeout);
+        self
     }
 }
 
@@ -102,14 +242,62 @@ impl<H: EvmBlockHeader> EvmEnvBuilder<(), H, ()> {
         H: EvmBlockHeader + TryFrom<<N as Network>::HeaderResponse>,
         <H as TryFrom<<N as Network>::HeaderResponse>>::Error: Display,
     {
+        self.with_provider(Ok(provider))
+    }
+
+    /// Replaces the provider, keeping all other settings.
+    fn with_provider<P>(self, provider: Result<P, Arc<reqwest::Error>>) -> EvmEnvBuilder<P, H, ()> {
         EvmEnvBuilder {
             provider,
             provider_config: self.provider_config,
+            http_timeout: self.http_timeout,
+            block_hash: self.block_hash,
+            chain_spec: self.chain_spec,
             block: self.block,
             beacon_config: self.beacon_config,
+            beacon_cache: self.beacon_cache,
             phantom: self.phantom,
         }
     }
+
+    /// Sets a custom [Provider] like [EvmEnvBuilder::provider], but erases its type.
+    ///
+    /// Providers with several layers, e.g. for rate limiting or logging, have deeply nested types
+    /// that then also appear in the type of the resulting [EvmEnv]. Boxing the provider results
+    /// in the same [BoxedProvider] type for any such stack, so that the environment can easily be
+    /// stored or passed around. The complete provider is kept, so all RPC calls of the preflight
+    /// still go through every [ProviderLayer](alloy::providers::ProviderLayer) of the stack. To
+    /// also erase the type of custom transport layers, the transport must be boxed before
+    /// creating the provider.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use alloy::{
+    /// #     providers::ProviderBuilder, rpc::client::ClientBuilder,
+    /// #     transports::layers::RetryBackoffLayer,
+    /// # };
+    /// # use risc0_steel::ethereum::EthEvmEnv;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// // retry rate-limited requests up to 10 times, starting with a backoff of 500ms
+    /// let client = ClientBuilder::default()
+    ///     .layer(RetryBackoffLayer::new(10, 500, 330))
+    ///     .http("https://ethereum-rpc.publicnode.com".parse()?)
+    ///     .boxed();
+    /// let provider = ProviderBuilder::new().on_client(client);
+    /// let env = EthEvmEnv::builder().boxed_provider(provider).build().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn boxed_provider<N, P>(self, provider: P) -> EvmEnvBuilder<BoxedProvider<N>, H, ()>
+    where
+        N: Network,
+        P: Provider<BoxTransport, N> + 'static,
+        H: EvmBlockHeader + TryFrom<<N as Network>::HeaderResponse>,
+        <H as TryFrom<<N as Network>::HeaderResponse>>::Error: Display,
+    {
+        self.provider(BoxedProvider::new(provider))
+    }
 }
 
 impl<P> EvmEnvBuilder<P, EthBlockHeader, ()> {
@@ -117,12 +305,44 @@ impl<P> EvmEnvBuilder<P, EthBlockHeader, ()> {
     ///
     /// This function configures the [EvmEnv] to interact with an Ethereum Beacon chain.
     /// It assumes the use of the [mainnet](https://github.com/ethereum/consensus-specs/blob/v1.4.0/configs/mainnet.yaml) preset for consensus specs.
+    ///
+    /// The execution block can be combined with [EvmEnvBuilder::block_number] to read the state of
+    /// a specific past block, while the resulting [Commitment](crate::Commitment) anchors to the
+    /// root of the beacon block containing that execution block:
+    /// ```rust,no_run
+    /// # use risc0_steel::ethereum::EthEvmEnv;
+    /// # use url::Url;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// # let url = Url::parse("https://ethereum-rpc.publicnode.com")?;
+    /// # let beacon_url = Url::parse("https://ethereum-beacon-api.publicnode.com")?;
+    /// let env = EthEvmEnv::builder()
+    ///     .rpc(url)
+    ///     .beacon_api(beacon_url)
+    ///     .block_number(20_000_000)
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The following constraints apply to the block:
+    /// - It must be a post-Dencun block, as the beacon block root is only available with EIP-4788.
+    /// - It must not be the most recent block, as the beacon block root is taken from the header of
+    ///   its child block. Use [BlockNumberOrTag::Parent] instead of `latest`.
+    /// - As the commitment can only be verified using the EIP-4788 beacon roots contract, it must
+    ///   be verified on-chain within its history buffer of 8191 slots (about 27 hours). For older
+    ///   blocks, use `commitment_block` to anchor the state to a more recent beacon block root.
     pub fn beacon_api(self, url: Url) -> EvmEnvBuilder<P, EthBlockHeader, Url> {
         EvmEnvBuilder {
             provider: self.provider,
             provider_config: self.provider_config,
+            http_timeout: self.http_timeout,
+            block_hash: self.block_hash,
+            chain_spec: self.chain_spec,
             block: self.block,
             beacon_config: url,
+            beacon_cache: self.beacon_cache,
             phantom: self.phantom,
         }
     }
@@ -134,10 +354,38 @@ impl<P, H, B> EvmEnvBuilder<P, H, B> {
         self.block_number_or_tag(BlockNumberOrTag::Number(number))
     }
 
-    /// Sets the block number or block tag ("latest", "earliest", "pending") to be used for the EVM
-    /// execution.
+    /// Sets the block number or block tag ("latest", "safe", "finalized", "earliest") to be used for
+    /// the EVM execution.
+    ///
+    /// This replaces any block hash previously set with [EvmEnvBuilder::block_hash].
     pub fn block_number_or_tag(mut self, block: BlockNumberOrTag) -> Self {
         self.block = block;
+        self.block_hash = None;
+        self
+    }
+
+    /// Sets the hash of the block to be used for the EVM execution.
+    ///
+    /// The header is fetched directly by its hash using `eth_getBlockByHash`, so that the
+    /// environment always refers to exactly this block, even if it is no longer part of the
+    /// canonical chain after a reorg. Building the environment fails if the node does not know a
+    /// block with this hash. This replaces any block number or tag previously set.
+    pub fn block_hash(mut self, hash: B256) -> Self {
+        self.block_hash = Some(hash);
+        self
+    }
+
+    /// Sets the chain spec of the environment.
+    ///
+    /// This sets the chain ID, the [SpecId] of the execution block and the `configID` of the
+    /// [Commitment] when building the environment, equivalent to calling
+    /// [EvmEnv::with_chain_spec] on the result. Building the environment fails if no fork of the
+    /// chain spec is active for the execution block.
+    ///
+    /// [SpecId]: revm::primitives::SpecId
+    /// [Commitment]: crate::Commitment
+    pub fn chain_spec(mut self, chain_spec: &ChainSpec) -> Self {
+        self.chain_spec = Some(chain_spec.clone());
         self
     }
 
@@ -151,10 +399,201 @@ impl<P, H, B> EvmEnvBuilder<P, H, B> {
         self
     }
 
+    /// Sets the max number of concurrent `eth_getProof` calls (EIP-1186).
+    ///
+    /// When creating the input, the proofs of all accessed accounts are fetched concurrently.
+    /// The default is 16, but this can be lowered for RPC nodes with strict rate limits.
+    pub fn eip1186_proof_concurrency(mut self, concurrency: usize) -> Self {
+        assert_ne!(concurrency, 0, "concurrency must be non-zero");
+        self.provider_config.eip1186_proof_concurrency = concurrency;
+        self
+    }
+
+    /// Sets whether the `eth_getProof` calls (EIP-1186) of several accounts are batched.
+    ///
+    /// When enabled, the proofs of up to [EvmEnvBuilder::eip1186_proof_batch_size] accounts are
+    /// requested in a single JSON-RPC batch request, which can drastically reduce the latency
+    /// for preflights accessing many accounts. If the RPC node rejects a batch, the proofs are
+    /// requested individually instead. Batched responses bypass the [EvmEnvBuilder::cache_dir].
+    /// By default, batching is disabled.
+    pub fn batch_requests(mut self, enabled: bool) -> Self {
+        self.provider_config.batch_requests = enabled;
+        self
+    }
+
+    /// Sets the max number of `eth_getProof` calls (EIP-1186) in a single JSON-RPC batch.
+    ///
+    /// This only has an effect if [EvmEnvBuilder::batch_requests] is enabled. The default is
+    /// 100, but many RPC providers limit the size of batch requests.
+    pub fn eip1186_proof_batch_size(mut self, batch_size: usize) -> Self {
+        assert_ne!(batch_size, 0, "batch size must be non-zero");
+        self.provider_config.eip1186_proof_batch_size = batch_size;
+        self
+    }
+
+    /// Sets whether the `eth_getProof` responses (EIP-1186) are verified during preflight.
+    ///
+    /// Some RPC nodes return incomplete proofs for certain accounts, which would otherwise only
+    /// cause a panic in the guest. When enabled, each account and storage proof is checked against
+    /// the state root of the block on the host, and an error referencing the offending account is
+    /// returned if the proof does not verify. The default is `false`.
+    pub fn verify_eip1186_proofs(mut self, verify: bool) -> Self {
+        self.provider_config.verify_eip1186_proofs = verify;
+        self
+    }
+
+    /// Sets whether to check that the preflight is deterministic.
+    ///
+    /// When enabled, the input is built a second time from the same preflight, fetching all the
+    /// proofs and ancestor headers again, and an error is returned if the two encoded inputs are
+    /// not byte-identical. This surfaces inconsistent RPC responses or nondeterministic ordering
+    /// on the host before they cause confusing proof mismatches. As this doubles the number of
+    /// RPC calls when creating the input, it is disabled by default and mainly useful during
+    /// development.
+    pub fn verify_determinism(mut self, enabled: bool) -> Self {
+        self.provider_config.verify_determinism = enabled;
+        self
+    }
+
+    /// Skips the validation of the proofs returned by the RPC node on the host.
+    ///
+    /// **Warning:** This is only meant for development, e.g. to iterate faster when debugging
+    /// the guest logic. The proofs are no longer checked against the state root of the header and
+    /// incomplete proofs are not recovered, so the resulting input might be invalid and cause the
+    /// guest to panic. The verification in the guest is never skipped, so this cannot lead to an
+    /// unsound proof. It requires the non-default `unsafe-trust-rpc-state` feature, so that it
+    /// cannot be enabled in production by accident.
+    #[cfg(feature = "unsafe-trust-rpc-state")]
+    pub fn trust_rpc_state(mut self) -> Self {
+        self.provider_config.trust_rpc_state = true;
+        self
+    }
+
+    /// Sets whether to include the EIP-2930 access list of each preflight call in the input.
+    ///
+    /// When enabled, every call executed with [CallBuilder::call] or [CallBuilder::estimate]
+    /// first queries `eth_createAccessList` and includes all the listed accounts and storage
+    /// slots in the input, as with [CallBuilder::access_list]. This helps when the execution in
+    /// the guest accesses state that was not accessed during preflight. However, the node may
+    /// return more state than the call actually needs, e.g. for all branches it considers
+    /// reachable, and every additional proof increases the size of the input and the cycles
+    /// in the guest. It also adds one RPC call per call. By default, this is disabled.
+    ///
+    /// [CallBuilder::call]: crate::CallBuilder::call
+    /// [CallBuilder::estimate]: crate::CallBuilder::estimate
+    /// [CallBuilder::access_list]: crate::CallBuilder::access_list
+    pub fn auto_access_list(mut self, enabled: bool) -> Self {
+        self.provider_config.auto_access_list = enabled;
+        self
+    }
+
+    /// Sets a callback to report the progress of fetching the proofs.
+    ///
+    /// When creating the input, e.g. with [EvmEnv::into_input], the callback is invoked once
+    /// before the first and after each `eth_getProof` response with the number of fetched and
+    /// total accounts and storage slots. This can be used to render a progress bar for large
+    /// preflights and does not affect the resulting input.
+    ///
+    /// ```rust,no_run
+    /// # use risc0_steel::ethereum::EthEvmEnv;
+    /// # use url::Url;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// # let url = Url::parse("https://ethereum-rpc.publicnode.com")?;
+    /// let env = EthEvmEnv::builder()
+    ///     .rpc(url)
+    ///     .on_progress(|progress| {
+    ///         println!(
+    ///             "fetched {}/{} accounts",
+    ///             progress.accounts_fetched, progress.accounts_total
+    ///         )
+    ///     })
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_progress(mut self, callback: impl Fn(ProofProgress) + Send + Sync + 'static) -> Self {
+        self.provider_config.on_progress = Some(ProgressCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Sets the size in bytes of the encoded input above which a warning is logged.
+    ///
+    /// When creating the input, e.g. with [EvmEnv::into_input], its [EvmInput::encoded_len] is
+    /// compared against this threshold. Large inputs can exceed the practical limits of the
+    /// guest, so the warning signals to split the computation, e.g. using proof composition,
+    /// before proving fails. By default, no warning is logged.
+    ///
+    /// [EvmInput::encoded_len]: crate::EvmInput::encoded_len
+    pub fn input_size_warning(mut self, bytes: usize) -> Self {
+        self.provider_config.input_size_warning = Some(bytes);
+        self
+    }
+
+    /// Sets the max number of times a failed RPC call is retried.
+    ///
+    /// Retries use an exponential backoff with random jitter, starting with the delay configured
+    /// by [EvmEnvBuilder::rpc_retry_backoff]. Only idempotent read calls, like `eth_getProof`,
+    /// `eth_getStorageAt` or `eth_getBlockByNumber`, are retried. This makes long preflights more
+    /// robust against transient failures, such as rate limits or dropped connections. The error
+    /// of a call that still fails contains the number of attempts. The default is 0, i.e. no
+    /// retries.
+    ///
+    /// Waiting between retries requires a Tokio runtime with the time driver enabled.
+    pub fn rpc_retries(mut self, retries: usize) -> Self {
+        self.provider_config.rpc_retries = retries;
+        self
+    }
+
+    /// Sets the initial delay before retrying a failed RPC call.
+    ///
+    /// The delay is doubled for each subsequent retry. The default is 500ms.
+    pub fn rpc_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.provider_config.rpc_retry_backoff = backoff;
+        self
+    }
+
+    /// Sets a directory to cache the RPC responses of the preflight in.
+    ///
+    /// Responses are cached per block hash, RPC method and parameters. Since the environment is
+    /// bound to a fixed block, cached responses never become stale and can be reused
+    /// indefinitely, e.g. when iterating on the guest logic offline. Cache misses fall through to
+    /// the provider and their responses are written back to the cache. Resolving the block tag
+    /// itself is never cached, so [EvmEnvBuilder::block_number] should be used in combination.
+    /// Hashes of ancestors requested by the `BLOCKHASH` opcode are queried by number, which does
+    /// not identify a block of a fixed chain, so they are not cached either.
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.provider_config.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Resolves the configured block number or tag into a concrete block number.
+    ///
+    /// Block tags like "parent", "safe" or "finalized" are resolved using RPC calls each time an
+    /// environment is built. Resolving the tag once replaces it with the corresponding
+    /// [BlockNumberOrTag::Number], so that repeated builds from this builder (e.g. in a retry loop)
+    /// refer to the same block and do not need to resolve the tag again.
+    pub async fn resolve_block_number<T, N>(mut self) -> Result<Self, Error>
+    where
+        T: Transport + Clone,
+        N: Network,
+        P: Provider<T, N>,
+        H: EvmBlockHeader + TryFrom<<N as Network>::HeaderResponse>,
+        <H as TryFrom<<N as Network>::HeaderResponse>>::Error: Display,
+    {
+        if self.block_hash.is_none() && !matches!(self.block, BlockNumberOrTag::Number(_)) {
+            let header = self.get_header(None).await?;
+            self.block = BlockNumberOrTag::Number(header.number());
+        }
+
+        Ok(self)
+    }
+
     /// Returns the [EvmBlockHeader] of the specified block.
     ///
     /// If `block` is `None`, the block based on the current builder configuration is used instead.
-    async fn get_header<T, N>(&self, block: Option<BlockNumberOrTag>) -> Result<Sealed<H>>
+    async fn get_header<T, N>(&self, block: Option<BlockNumberOrTag>) -> Result<Sealed<H>, Error>
     where
         T: Transport + Clone,
         N: Network,
@@ -162,24 +601,52 @@ impl<P, H, B> EvmEnvBuilder<P, H, B> {
         H: EvmBlockHeader + TryFrom<<N as Network>::HeaderResponse>,
         <H as TryFrom<<N as Network>::HeaderResponse>>::Error: Display,
     {
-        let block = block.unwrap_or(self.block);
-        let number = block.into_rpc_type(&self.provider).await?;
-
-        let rpc_block = self
+        let provider = self
             .provider
-            .get_block_by_number(number, BlockTransactionsKind::Hashes)
-            .await
-            .context("eth_getBlockByNumber failed")?
-            .with_context(|| format!("block {} not found", number))?;
+            .as_ref()
+            .map_err(|err| Error::HttpClient(err.clone()))?;
+
+        // a pinned block hash only applies to the configured block, not to explicit blocks
+        let block_hash = if block.is_none() {
+            self.block_hash
+        } else {
+            None
+        };
+        let rpc_block = match block_hash {
+            Some(hash) => self
+                .provider_config
+                .retry(|| provider.get_block_by_hash(hash, BlockTransactionsKind::Hashes))
+                .await
+                .map_err(|err| Error::Rpc("eth_getBlockByHash", err))?
+                .ok_or_else(|| Error::BlockNotFound(hash.to_string()))?,
+            None => {
+                let block = block.unwrap_or(self.block);
+                let number = block.into_rpc_type(provider, &self.provider_config).await?;
+
+                self.provider_config
+                    .retry(|| provider.get_block_by_number(number, BlockTransactionsKind::Hashes))
+                    .await
+                    .map_err(|err| Error::Rpc("eth_getBlockByNumber", err))?
+                    .ok_or_else(|| match block {
+                        BlockNumberOrTag::Safe | BlockNumberOrTag::Finalized => {
+                            Error::UnsupportedBlockTag(block.to_string())
+                        }
+                        _ => Error::BlockNotFound(number.to_string()),
+                    })?
+            }
+        };
         let rpc_header = rpc_block.header().clone();
-        let header: H = rpc_header
-            .try_into()
-            .map_err(|err| anyhow!("header invalid: {}", err))?;
+        let header =
+            H::try_from(rpc_header).map_err(|err| Error::HeaderConversion(err.to_string()))?;
         let header = header.seal_slow();
-        ensure!(
-            header.seal() == rpc_block.header().hash(),
-            "computed block hash does not match the hash returned by the API"
-        );
+        if header.seal() != rpc_block.header().hash() {
+            return Err(Error::HeaderMismatch(
+                "computed block hash does not match the hash returned by the API",
+            ));
+        }
+        if block_hash.is_some_and(|hash| header.seal() != hash) {
+            return Err(Error::HeaderMismatch("API returned a different block"));
+        }
 
         Ok(header)
     }
@@ -187,7 +654,7 @@ impl<P, H, B> EvmEnvBuilder<P, H, B> {
 
 impl<P, H> EvmEnvBuilder<P, H, ()> {
     /// Builds and returns an [EvmEnv] with the configured settings that commits to a block hash.
-    pub async fn build<T, N>(self) -> Result<HostEvmEnv<AlloyDb<T, N, P>, H, ()>>
+    pub async fn build<T, N>(self) -> Result<HostEvmEnv<AlloyDb<T, N, P>, H, ()>, Error>
     where
         T: Transport + Clone,
         N: Network,
@@ -202,17 +669,129 @@ impl<P, H> EvmEnvBuilder<P, H, ()> {
             header.seal()
         );
 
-        let db = ProofDb::new(AlloyDb::new(
-            self.provider,
-            self.provider_config,
-            header.seal(),
-        ));
+        let provider = self.provider.map_err(Error::HttpClient)?;
+        let db = ProofDb::new(AlloyDb::new(provider, self.provider_config, header.seal()));
         let commit = HostCommit {
             inner: (),
             config_id: ChainSpec::DEFAULT_DIGEST,
+            beacon_slot: None,
+            call_results: Vec::new(),
         };
 
-        Ok(EvmEnv::new(db, header, commit))
+        with_chain_spec(EvmEnv::new(db, header, commit), self.chain_spec.as_ref())
+    }
+
+    /// Preflights the same call on each of the given blocks and returns one [EvmInput] per block.
+    ///
+    /// This is a convenience function for time series, e.g. to prove the value of a view call
+    /// now and one hour ago: For each block, an environment is built with the configured
+    /// settings, the call is preflighted and the environment is converted into an input. All
+    /// blocks are processed concurrently using the same provider, and the returned inputs are in
+    /// the same order as the `blocks`. In the guest, each input is converted into its own
+    /// environment, and the commitments of all environments must be validated.
+    ///
+    /// As the call is executed with the rules of the respective block, a chain spec must be set
+    /// using [EvmEnvBuilder::chain_spec]. An error is returned if it is missing, if any block
+    /// does not exist or if the chain spec does not support any of the blocks.
+    pub async fn call_many<T, N, S>(
+        self,
+        address: Address,
+        call: &S,
+        blocks: impl IntoIterator<Item = BlockNumberOrTag>,
+    ) -> Result<Vec<EvmInput<H>>, Error>
+    where
+        T: Transport + Clone,
+        N: Network,
+        P: Provider<T, N> + Clone + Send + 'static,
+        H: EvmBlockHeader + TryFrom<<N as Network>::HeaderResponse>,
+        H: Clone + Send + Serialize + 'static,
+        <H as TryFrom<<N as Network>::HeaderResponse>>::Error: Display,
+        S: SolCall + Send + 'static,
+        <S as SolCall>::Return: Send,
+    {
+        if self.chain_spec.is_none() {
+            return Err(Error::ChainSpec(anyhow!(
+                "chain spec must be set to execute a call on several blocks"
+            )));
+        }
+
+        let preflights = blocks.into_iter().map(|block| {
+            let builder = self.clone().block_number_or_tag(block);
+            async move {
+                let mut env = builder.build().await?;
+                Contract::preflight(address, &mut env)
+                    .call_builder(call)
+                    .call()
+                    .await
+                    .map_err(|err| Error::Call(format!("call on block {}", block), err))?;
+                let input = env.into_input().await?;
+
+                Ok::<_, Error>(input)
+            }
+        });
+
+        future::try_join_all(preflights).await
+    }
+
+    /// Preflights independent calls concurrently and merges their state into a single [EvmInput].
+    ///
+    /// All accesses of a preflight are recorded in its environment, so calls preflighted in the
+    /// same environment are executed one after another. Instead, this builds a separate
+    /// environment for each call, all pinned to the hash of the configured block, preflights the
+    /// calls concurrently using the same provider and merges the resulting inputs with
+    /// [EvmInput::merge]. As the state of an input is ordered canonically, the merged input does
+    /// not depend on the order in which the preflights complete and is byte-identical to the
+    /// input of preflighting the same calls one after another in a single environment. The
+    /// header and any state shared by several calls is fetched once per call, so this is only
+    /// beneficial for calls that access mostly disjoint state.
+    ///
+    /// The calls are executed with the default parameters of a [CallBuilder], and their results
+    /// are returned in the same order as the `calls`. In the guest, the calls can then be executed
+    /// with [Contract::new] on the environment of the merged input.
+    ///
+    /// [CallBuilder]: crate::CallBuilder
+    pub async fn call_concurrently<T, N, S>(
+        self,
+        calls: impl IntoIterator<Item = (Address, S)>,
+    ) -> Result<(EvmInput<H>, Vec<S::Return>), Error>
+    where
+        T: Transport + Clone,
+        N: Network,
+        P: Provider<T, N> + Clone + Send + 'static,
+        H: EvmBlockHeader + TryFrom<<N as Network>::HeaderResponse>,
+        H: Clone + Send + Serialize + 'static,
+        <H as TryFrom<<N as Network>::HeaderResponse>>::Error: Display,
+        S: SolCall + Send + 'static,
+        <S as SolCall>::Return: Send,
+    {
+        // pin the block, so that all calls are executed on the same state
+        let header = self.get_header(None).await?;
+        let builder = self.block_hash(header.seal());
+
+        let preflights = calls.into_iter().map(|(address, call)| {
+            let builder = builder.clone();
+            async move {
+                let mut env = builder.build().await?;
+                let returns = Contract::preflight(address, &mut env)
+                    .call_builder(&call)
+                    .call()
+                    .await
+                    .map_err(|err| Error::Call(format!("call to {}", address), err))?;
+                let input = env.into_input().await?;
+
+                Ok::<_, Error>((input, returns))
+            }
+        });
+        let (inputs, returns): (Vec<_>, Vec<_>) =
+            future::try_join_all(preflights).await?.into_iter().unzip();
+
+        let mut inputs = inputs.into_iter();
+        let first = inputs.next().ok_or(Error::NoCalls)?;
+        let input = inputs
+            .try_fold(first, |merged, input| merged.merge(input))
+            .map_err(Error::Merge)?;
+
+        Ok((input, returns))
     }
 }
 
@@ -241,17 +820,45 @@ impl<P> EvmEnvBuilder<P, EthBlockHeader, Url> {
         EvmEnvBuilder {
             provider: self.provider,
             provider_config: self.provider_config,
+            http_timeout: self.http_timeout,
+            block_hash: self.block_hash,
+            chain_spec: self.chain_spec,
             block: self.block,
             beacon_config: History {
                 beacon_url: self.beacon_config,
                 commitment_block: block,
             },
+            beacon_cache: self.beacon_cache,
             phantom: Default::default(),
         }
     }
 
+    /// Sets an earlier block for the EVM execution, while committing to the configured block.
+    ///
+    /// This is the reverse of [EvmEnvBuilder::commitment_block]: The block previously set with
+    /// [EvmEnvBuilder::block_number_or_tag] (or one of its variants) becomes the commitment block,
+    /// and the execution happens on the historical state of `block`. The execution block must be
+    /// before the commitment block, and both blocks must be after the Dencun fork, as the
+    /// commitment relies on the EIP-4788 Beacon roots of all blocks in between.
+    ///
+    /// A block hash previously set with [EvmEnvBuilder::block_hash] is not supported for the
+    /// commitment block and is replaced by the configured block number or tag.
+    ///
+    /// Note that this feature requires a Beacon chain RPC provider, as it uses EIP-4788.
+    #[stability::unstable(feature = "history")]
+    pub fn history_from(
+        self,
+        block: BlockNumberOrTag,
+    ) -> EvmEnvBuilder<P, EthBlockHeader, History> {
+        let commitment_block = self.block;
+        self.block_number_or_tag(block)
+            .commitment_block(commitment_block)
+    }
+
     /// Builds and returns an [EvmEnv] with the configured settings that commits to a beacon root.
-    pub async fn build<T>(self) -> Result<EthHostEvmEnv<AlloyDb<T, Ethereum, P>, BeaconCommit>>
+    pub async fn build<T>(
+        self,
+    ) -> Result<EthHostEvmEnv<AlloyDb<T, Ethereum, P>, BeaconCommit>, Error>
     where
         T: Transport + Clone,
         P: Provider<T, Ethereum>,
@@ -263,25 +870,53 @@ impl<P> EvmEnvBuilder<P, EthBlockHeader, Url> {
             header.seal()
         );
 
+        let provider = self.provider.map_err(Error::HttpClient)?;
+        let (beacon_commit, beacon_slot) =
+            BeaconCommit::from_header(&header, &provider, self.beacon_config, &self.beacon_cache)
+                .await
+                .map_err(Error::Commitment)?;
         let commit = HostCommit {
-            inner: BeaconCommit::from_header(&header, &self.provider, self.beacon_config).await?,
+            inner: beacon_commit,
             config_id: ChainSpec::DEFAULT_DIGEST,
+            beacon_slot: Some(beacon_slot),
+            call_results: Vec::new(),
         };
-        let db = ProofDb::new(AlloyDb::new(
-            self.provider,
-            self.provider_config,
-            header.seal(),
-        ));
+        let db = ProofDb::new(AlloyDb::new(provider, self.provider_config, header.seal()));
 
-        Ok(EvmEnv::new(db, header, commit))
+        with_chain_spec(EvmEnv::new(db, header, commit), self.chain_spec.as_ref())
     }
 }
 
 impl<P> EvmEnvBuilder<P, EthBlockHeader, History> {
+    /// Returns the number of recursive steps required to commit to the configured commitment
+    /// block while executing on the configured execution block.
+    ///
+    /// Each step adds the state of the EIP-4788 Beacon roots contract and a Merkle proof to the
+    /// input, which must be verified in the guest. Thus, the proving cost grows linearly with this
+    /// number, and the total number of Beacon roots involved is one more. This uses the same
+    /// logic as [EvmEnvBuilder::build], so it can be used to check whether a block range is
+    /// feasible before any data is fetched. As no RPC calls are made, `None` is returned unless
+    /// both blocks are set as block numbers.
+    #[stability::unstable(feature = "history")]
+    pub fn history_depth(&self) -> Option<usize> {
+        match (
+            self.block_hash,
+            self.block,
+            self.beacon_config.commitment_block,
+        ) {
+            (None, BlockNumberOrTag::Number(from), BlockNumberOrTag::Number(to)) => {
+                Some(HistoryCommit::depth(from, to))
+            }
+            _ => None,
+        }
+    }
+
     /// Builds and returns an [EvmEnv] with the configured settings, using a dedicated commitment
     /// block that is different from the execution block.
     #[stability::unstable(feature = "history")]
-    pub async fn build<T>(self) -> Result<EthHostEvmEnv<AlloyDb<T, Ethereum, P>, HistoryCommit>>
+    pub async fn build<T>(
+        self,
+    ) -> Result<EthHostEvmEnv<AlloyDb<T, Ethereum, P>, HistoryCommit>, Error>
     where
         T: Transport + Clone,
         P: Provider<T, Ethereum>,
@@ -290,10 +925,20 @@ impl<P> EvmEnvBuilder<P, EthBlockHeader, History> {
         let commitment_header = self
             .get_header(Some(self.beacon_config.commitment_block))
             .await?;
-        ensure!(
-            evm_header.number() < commitment_header.number(),
-            "EVM execution block not before commitment block"
-        );
+        if evm_header.number() >= commitment_header.number() {
+            return Err(Error::Commitment(anyhow!(
+                "EVM execution block {} not before commitment block {}",
+                evm_header.number(),
+                commitment_header.number()
+            )));
+        }
+        // all blocks of the range must provide the Beacon root of their parent (EIP-4788)
+        if evm_header.parent_beacon_block_root.is_none() {
+            return Err(Error::Commitment(anyhow!(
+                "EVM execution block {} is before the Dencun fork and has no Beacon root",
+                evm_header.number()
+            )));
+        }
 
         log::info!(
             "Environment initialized with block {} ({})",
@@ -301,25 +946,46 @@ impl<P> EvmEnvBuilder<P, EthBlockHeader, History> {
             evm_header.seal()
         );
 
+        let provider = self.provider.map_err(Error::HttpClient)?;
         let beacon_url = self.beacon_config.beacon_url;
         let history_commit = HistoryCommit::from_headers(
             &evm_header,
             &commitment_header,
-            &self.provider,
+            &provider,
             beacon_url,
+            &self.beacon_cache,
         )
-        .await?;
+        .await
+        .map_err(Error::Commitment)?;
         let commit = HostCommit {
             inner: history_commit,
             config_id: ChainSpec::DEFAULT_DIGEST,
+            beacon_slot: None,
+            call_results: Vec::new(),
         };
         let db = ProofDb::new(AlloyDb::new(
-            self.provider,
+            provider,
             self.provider_config,
             evm_header.seal(),
         ));
 
-        Ok(EvmEnv::new(db, evm_header, commit))
+        with_chain_spec(
+            EvmEnv::new(db, evm_header, commit),
+            self.chain_spec.as_ref(),
+        )
+    }
+}
+
+/// Applies the chain spec, if configured, to a newly built environment.
+fn with_chain_spec<D, H: EvmBlockHeader, C>(
+    env: HostEvmEnv<D, H, C>,
+    chain_spec: Option<&ChainSpec>,
+) -> Result<HostEvmEnv<D, H, C>, Error> {
+    match chain_spec {
+        Some(chain_spec) => env
+            .try_with_chain_spec(chain_spec)
+            .map_err(Error::ChainSpec),
+        None => Ok(env),
     }
 }
 
@@ -327,7 +993,9 @@ impl<P> EvmEnvBuilder<P, EthBlockHeader, History> {
 mod tests {
     use super::*;
     use crate::{
-        config::ChainSpec, ethereum::EthEvmEnv, BlockHeaderCommit, Commitment, CommitmentVersion,
+        config::ChainSpec,
+        ethereum::{EthEvmEnv, ETH_SEPOLIA_CHAIN_SPEC},
+        BlockHeaderCommit, Commitment, CommitmentVersion,
     };
     use test_log::test;
 
@@ -342,6 +1010,48 @@ mod tests {
         builder.clone().build().await.unwrap();
     }
 
+    #[test(tokio::test)]
+    #[ignore = "queries actual RPC nodes"]
+    async fn build_block_env_with_client() {
+        let client = reqwest::Client::builder()
+            .user_agent("risc0-steel")
+            .build()
+            .unwrap();
+        EthEvmEnv::builder()
+            .rpc_with_client(EL_URL.parse().unwrap(), client)
+            .block_number_or_tag(BlockNumberOrTag::Finalized)
+            .build()
+            .await
+            .unwrap();
+    }
+
+    #[test(tokio::test)]
+    #[ignore = "queries actual RPC nodes"]
+    async fn build_block_env_with_http_timeout() {
+        EthEvmEnv::builder()
+            .http_timeout(Duration::from_secs(120))
+            .rpc(EL_URL.parse().unwrap())
+            .build()
+            .await
+            .unwrap();
+    }
+
+    #[test(tokio::test)]
+    #[ignore = "queries actual RPC nodes"]
+    async fn resolve_block_number() {
+        let builder = EthEvmEnv::builder()
+            .rpc(EL_URL.parse().unwrap())
+            .block_number_or_tag(BlockNumberOrTag::Finalized)
+            .resolve_block_number()
+            .await
+            .unwrap();
+        let BlockNumberOrTag::Number(number) = builder.block else {
+            panic!("block tag not resolved")
+        };
+        let env = builder.build().await.unwrap();
+        assert_eq!(env.header().number(), number);
+    }
+
     #[test(tokio::test)]
     #[ignore = "queries actual RPC nodes"]
     async fn build_beacon_env() {
@@ -352,6 +1062,7 @@ mod tests {
             .beacon_api(CL_URL.parse().unwrap())
             .block_number_or_tag(BlockNumberOrTag::Parent);
         let env = builder.clone().build().await.unwrap();
+        assert!(env.beacon_slot().is_some());
         let commit = env.commit.inner.commit(&env.header, env.commit.config_id);
 
         // the commitment should verify against the parent_beacon_block_root of the child
@@ -374,6 +1085,45 @@ mod tests {
         );
     }
 
+    #[test(tokio::test)]
+    #[ignore = "queries actual RPC nodes"]
+    async fn build_beacon_input_sepolia() {
+        const EL_URL: &str = "https://ethereum-sepolia-rpc.publicnode.com";
+        const CL_URL: &str = "https://ethereum-sepolia-beacon-api.publicnode.com";
+
+        let provider = ProviderBuilder::new().on_builtin(EL_URL).await.unwrap();
+        let env = EthEvmEnv::builder()
+            .provider(&provider)
+            .beacon_api(CL_URL.parse().unwrap())
+            .block_number_or_tag(BlockNumberOrTag::Parent)
+            .build()
+            .await
+            .unwrap()
+            .with_chain_spec(&ETH_SEPOLIA_CHAIN_SPEC);
+        let number = env.header().number();
+        let input = env.into_input().await.unwrap();
+
+        // the beacon commitment does not depend on any network specific beacon parameters
+        let commit = input
+            .into_env()
+            .with_chain_spec(&ETH_SEPOLIA_CHAIN_SPEC)
+            .into_commitment();
+        let child_block = provider
+            .get_block_by_number((number + 1).into(), BlockTransactionsKind::Hashes)
+            .await
+            .unwrap();
+        let header = child_block.unwrap().header;
+        assert_eq!(
+            commit,
+            Commitment::new(
+                CommitmentVersion::Beacon as u16,
+                header.timestamp,
+                header.parent_beacon_block_root.unwrap(),
+                ETH_SEPOLIA_CHAIN_SPEC.digest(),
+            )
+        );
+    }
+
     #[test(tokio::test)]
     #[ignore = "queries actual RPC nodes"]
     async fn build_history_env() {
@@ -405,4 +1155,112 @@ mod tests {
             )
         );
     }
+
+    #[test(tokio::test)]
+    #[ignore = "queries actual RPC nodes"]
+    async fn history_input_with_results() {
+        use crate::ethereum::ETH_MAINNET_CHAIN_SPEC;
+
+        alloy_sol_types::sol! {
+            interface IERC20 {
+                function totalSupply() external view returns (uint256);
+            }
+        }
+        // USDT on Ethereum mainnet
+        let usdt = alloy_primitives::address!("dAC17F958D2ee523a2206206994597C13D831ec7");
+        let provider = ProviderBuilder::new().on_builtin(EL_URL).await.unwrap();
+
+        let latest = provider.get_block_number().await.unwrap();
+        let mut env = EthEvmEnv::builder()
+            .provider(provider.clone())
+            .block_number_or_tag(BlockNumberOrTag::Number(latest - 100))
+            .beacon_api(CL_URL.parse().unwrap())
+            .commitment_block(BlockNumberOrTag::Number(latest - 1))
+            .build()
+            .await
+            .unwrap()
+            .with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+        let total_supply = Contract::preflight(usdt, &mut env)
+            .call_builder(&IERC20::totalSupplyCall {})
+            .call()
+            .await
+            .unwrap();
+        let (input, results) = env.into_input_with_results().await.unwrap();
+        assert!(matches!(input, EvmInput::History(_)));
+
+        // the recorded result must match the result of the preflight
+        assert_eq!(results.len(), 1);
+        let decoded = IERC20::totalSupplyCall::abi_decode_returns(&results[0], true).unwrap();
+        assert_eq!(decoded._0, total_supply._0);
+    }
+
+    #[test]
+    fn history_depth() {
+        let builder = EthEvmEnv::builder()
+            .rpc(EL_URL.parse().unwrap())
+            .beacon_api(CL_URL.parse().unwrap());
+        let depth = builder
+            .clone()
+            .block_number(100)
+            .commitment_block(BlockNumberOrTag::Number(101))
+            .history_depth();
+        assert_eq!(depth, Some(1));
+
+        // tags cannot be resolved without RPC calls
+        let depth = builder
+            .block_number(100)
+            .commitment_block(BlockNumberOrTag::Latest)
+            .history_depth();
+        assert_eq!(depth, None);
+    }
+
+    #[test(tokio::test)]
+    async fn call_many_without_chain_spec() {
+        alloy_sol_types::sol! {
+            interface IERC20 {
+                function totalSupply() external view returns (uint256);
+            }
+        }
+        // the chain spec is checked before any RPC calls are made
+        let result = EthEvmEnv::builder()
+            .rpc(EL_URL.parse().unwrap())
+            .call_many(
+                Address::ZERO,
+                &IERC20::totalSupplyCall {},
+                [BlockNumberOrTag::Latest],
+            )
+            .await;
+        assert!(matches!(result, Err(Error::ChainSpec(_))));
+    }
+
+    #[test(tokio::test)]
+    #[ignore = "queries actual RPC nodes"]
+    async fn build_history_env_from() {
+        let provider = ProviderBuilder::new().on_builtin(EL_URL).await.unwrap();
+
+        // committing to latest - 1 while executing on latest - 100
+        let latest = provider.get_block_number().await.unwrap();
+        let env = EthEvmEnv::builder()
+            .provider(&provider)
+            .block_number(latest - 1)
+            .beacon_api(CL_URL.parse().unwrap())
+            .history_from(BlockNumberOrTag::Number(latest - 100))
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(env.header().number(), latest - 100);
+
+        // the execution block must be before the commitment block
+        let result = EthEvmEnv::builder()
+            .provider(&provider)
+            .block_number(latest - 100)
+            .beacon_api(CL_URL.parse().unwrap())
+            .history_from(BlockNumberOrTag::Number(latest - 1))
+            .build()
+            .await;
+        assert!(
+            result.is_err(),
+            "execution block after commitment block should fail"
+        );
+    }
 }
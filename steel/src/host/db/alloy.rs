@@ -14,7 +14,7 @@
 
 use std::{future::IntoFuture, marker::PhantomData};
 
-use super::provider::{ProviderConfig, ProviderDb};
+use super::provider::{ProviderConfig, ProviderDb, RetryError};
 use alloy::{
     network::{
         primitives::{BlockTransactionsKind, HeaderResponse},
@@ -66,6 +66,19 @@ impl<T: Transport + Clone, N: Network, P: Provider<T, N>> AlloyDb<T, N, P> {
             phantom: PhantomData,
         }
     }
+
+    /// Creates a new AlloyDb instance for the block with the given hash, using the default
+    /// configuration.
+    ///
+    /// This allows reusing a [Provider] and its connection pool, e.g. when embedding Steel in a
+    /// larger service. Use [EvmEnv::from_db] to create an environment from it.
+    ///
+    /// This will panic if called outside the context of a Tokio runtime.
+    ///
+    /// [EvmEnv::from_db]: crate::EvmEnv::from_db
+    pub fn from_provider(provider: P, block_hash: BlockHash) -> Self {
+        Self::new(provider, ProviderConfig::default(), block_hash)
+    }
 }
 
 impl<T: Transport + Clone, N: Network, P: Provider<T, N>> ProviderDb<T, N, P> for AlloyDb<T, N, P> {
@@ -86,7 +99,7 @@ impl<T: Transport + Clone, N: Network, P: Provider<T, N>> ProviderDb<T, N, P> fo
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("{0} failed")]
-    Rpc(&'static str, #[source] TransportError),
+    Rpc(&'static str, #[source] RetryError<TransportError>),
     #[error("block not found")]
     BlockNotFound,
 }
@@ -95,19 +108,24 @@ impl<T: Transport + Clone, N: Network, P: Provider<T, N>> Database for AlloyDb<T
     type Error = Error;
 
     fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let (config, hash) = (&self.provider_config, self.block_hash);
         let f = async {
-            let get_nonce = self
-                .provider
-                .get_transaction_count(address)
-                .hash(self.block_hash);
-            let get_balance = self.provider.get_balance(address).hash(self.block_hash);
-            let get_code = self.provider.get_code_at(address).hash(self.block_hash);
-
-            tokio::join!(
-                get_nonce.into_future(),
-                get_balance.into_future(),
-                get_code.into_future()
-            )
+            let get_nonce = config.cached(hash, "eth_getTransactionCount", address, || {
+                config.retry(|| {
+                    self.provider
+                        .get_transaction_count(address)
+                        .hash(hash)
+                        .into_future()
+                })
+            });
+            let get_balance = config.cached(hash, "eth_getBalance", address, || {
+                config.retry(|| self.provider.get_balance(address).hash(hash).into_future())
+            });
+            let get_code = config.cached(hash, "eth_getCode", address, || {
+                config.retry(|| self.provider.get_code_at(address).hash(hash).into_future())
+            });
+
+            tokio::join!(get_nonce, get_balance, get_code)
         };
         let (nonce, balance, code) = self.handle.block_on(f);
 
@@ -145,13 +163,18 @@ impl<T: Transport + Clone, N: Network, P: Provider<T, N>> Database for AlloyDb<T
     }
 
     fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let (config, hash) = (&self.provider_config, self.block_hash);
         let storage = self
             .handle
             .block_on(
-                self.provider
-                    .get_storage_at(address, index)
-                    .hash(self.block_hash)
-                    .into_future(),
+                config.cached(hash, "eth_getStorageAt", (address, index), || {
+                    config.retry(|| {
+                        self.provider
+                            .get_storage_at(address, index)
+                            .hash(hash)
+                            .into_future()
+                    })
+                }),
             )
             .map_err(|err| Error::Rpc("eth_getStorageAt", err))?;
 
@@ -159,12 +182,14 @@ impl<T: Transport + Clone, N: Network, P: Provider<T, N>> Database for AlloyDb<T
     }
 
     fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        // a block number does not identify a block of the environment's chain, e.g. after a
+        // reorg, so the response is never cached
         let block_response = self
             .handle
-            .block_on(
+            .block_on(self.provider_config.retry(|| {
                 self.provider
-                    .get_block_by_number(number.into(), BlockTransactionsKind::Hashes),
-            )
+                    .get_block_by_number(number.into(), BlockTransactionsKind::Hashes)
+            }))
             .map_err(|err| Error::Rpc("eth_getBlockByNumber", err))?;
         let block = block_response.ok_or(Error::BlockNotFound)?;
 
@@ -0,0 +1,262 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    mpt::EMPTY_ROOT_HASH, BlockInput, EvmBlockHeader, EvmInput, EvmInputParts, MerkleTrie,
+    StateAccount,
+};
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_trie::HashBuilder;
+use anyhow::{ensure, Context, Result};
+use nybbles::Nibbles;
+use revm::primitives::KECCAK_EMPTY;
+
+/// An explicitly supplied EVM state, that can be converted into an [EvmInput] without any RPC.
+///
+/// This allows writing deterministic tests for guests, which execute the same
+/// [EvmInput::into_env] and [Contract::new] path as with state fetched from a node.
+///
+/// Only full, devnet-sized states are supported. The state root is computed from all supplied
+/// accounts and storage slots, so partial states, e.g. built from the proof nodes of a public
+/// network, cannot be represented, and the complete state is included in the input.
+///
+/// ### Examples
+/// ```rust
+/// # use risc0_steel::{ethereum::EthBlockHeader, host::db::InMemoryDb};
+/// # use alloy_primitives::{address, U256};
+/// # fn main() -> anyhow::Result<()> {
+/// let mut db = InMemoryDb::new();
+/// let address = address!("0000000000000000000000000000000000000042");
+/// db.set_balance(address, U256::from(1));
+/// db.set_storage(address, U256::ZERO, U256::from(42));
+///
+/// // the synthetic header must commit to the state root of the supplied state
+/// let header = EthBlockHeader::new(alloy_consensus::Header {
+///     number: 1,
+///     state_root: db.state_root(),
+///     ..Default::default()
+/// });
+/// let input = db.into_input(header)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [Contract::new]: crate::Contract::new
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryDb {
+    accounts: BTreeMap<Address, InMemoryAccount>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct InMemoryAccount {
+    nonce: u64,
+    balance: U256,
+    code: Bytes,
+    storage: BTreeMap<U256, U256>,
+}
+
+impl InMemoryDb {
+    /// Creates a new empty state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the nonce of the account, creating the account if it does not exist.
+    pub fn set_nonce(&mut self, address: Address, nonce: u64) {
+        self.accounts.entry(address).or_default().nonce = nonce;
+    }
+
+    /// Sets the balance of the account, creating the account if it does not exist.
+    pub fn set_balance(&mut self, address: Address, balance: U256) {
+        self.accounts.entry(address).or_default().balance = balance;
+    }
+
+    /// Sets the runtime bytecode of the account, creating the account if it does not exist.
+    pub fn set_code(&mut self, address: Address, code: impl Into<Bytes>) {
+        self.accounts.entry(address).or_default().code = code.into();
+    }
+
+    /// Sets the value of a storage slot of the account, creating the account if it does not
+    /// exist. Setting a slot to zero removes it from the storage.
+    pub fn set_storage(&mut self, address: Address, index: U256, value: U256) {
+        let storage = &mut self.accounts.entry(address).or_default().storage;
+        if value.is_zero() {
+            storage.remove(&index);
+        } else {
+            storage.insert(index, value);
+        }
+    }
+
+    /// Returns the state root of the supplied state.
+    ///
+    /// This must be used as the `stateRoot` of the header passed to [InMemoryDb::into_input].
+    pub fn state_root(&self) -> B256 {
+        self.state_trie().0
+    }
+
+    /// Converts the state into an [EvmInput] for the given header.
+    ///
+    /// The resulting input does not contain any ancestors, so the `BLOCKHASH` opcode cannot be
+    /// used. It returns an error if the `stateRoot` of the header does not match
+    /// [InMemoryDb::state_root].
+    pub fn into_input<H: EvmBlockHeader>(self, header: H) -> Result<EvmInput<H>> {
+        Ok(EvmInput::Block(self.into_block_input(header)?))
+    }
+
+    /// Converts the state into a [BlockInput] for the given header.
+    pub(crate) fn into_block_input<H: EvmBlockHeader>(self, header: H) -> Result<BlockInput<H>> {
+        let (state_root, state_nodes) = self.state_trie();
+        ensure!(
+            header.state_root() == &state_root,
+            "header stateRoot {} does not match the state root {}",
+            header.state_root(),
+            state_root
+        );
+        let state_trie = MerkleTrie::from_rlp_nodes(state_nodes).context("state trie invalid")?;
+
+        let mut storage_tries = BTreeMap::new();
+        let mut contracts = BTreeMap::new();
+        for account in self.accounts.into_values() {
+            let (storage_root, storage_nodes) = account.storage_trie();
+            let storage_trie =
+                MerkleTrie::from_rlp_nodes(storage_nodes).context("storage trie invalid")?;
+            storage_tries.insert(storage_root, storage_trie);
+            if !account.code.is_empty() {
+                contracts.insert(keccak256(&account.code), account.code);
+            }
+        }
+
+        Ok(BlockInput::from_parts(EvmInputParts {
+            header,
+            state_trie,
+            storage_tries: storage_tries.into_values().collect(),
+            contracts: contracts.into_values().collect(),
+            ancestors: vec![],
+            beacon_commit: None,
+        }))
+    }
+
+    /// Returns the root and all RLP-encoded nodes of the state trie.
+    fn state_trie(&self) -> (B256, Vec<Bytes>) {
+        build_trie(self.accounts.iter().map(|(address, account)| {
+            let state_account = StateAccount {
+                nonce: account.nonce,
+                balance: account.balance,
+                storage_root: account.storage_trie().0,
+                code_hash: account.code_hash(),
+            };
+            (keccak256(address), alloy_rlp::encode(state_account))
+        }))
+    }
+}
+
+impl InMemoryAccount {
+    fn code_hash(&self) -> B256 {
+        if self.code.is_empty() {
+            KECCAK_EMPTY
+        } else {
+            keccak256(&self.code)
+        }
+    }
+
+    /// Returns the root and all RLP-encoded nodes of the storage trie.
+    fn storage_trie(&self) -> (B256, Vec<Bytes>) {
+        build_trie(self.storage.iter().map(|(index, value)| {
+            (
+                keccak256(index.to_be_bytes::<32>()),
+                alloy_rlp::encode(value),
+            )
+        }))
+    }
+}
+
+/// Builds the complete Merkle Patricia trie of the given leaves and returns its root and all its
+/// RLP-encoded nodes, starting with the root node.
+fn build_trie(leaves: impl IntoIterator<Item = (B256, Vec<u8>)>) -> (B256, Vec<Bytes>) {
+    // the hash builder requires the leaves to be sorted by their key
+    let leaves: BTreeMap<_, _> = leaves
+        .into_iter()
+        .map(|(key, value)| (Nibbles::unpack(key), value))
+        .collect();
+    match leaves.len() {
+        0 => return (EMPTY_ROOT_HASH, vec![]),
+        1 => {
+            // the root of a trie with a single leaf is the leaf node itself
+            let (key, value) = leaves.into_iter().next().unwrap();
+            let path = Bytes::copy_from_slice(&key.encode_path_leaf(true));
+            let node = Bytes::from(alloy_rlp::encode(vec![path, value.into()]));
+            return (keccak256(&node), vec![node]);
+        }
+        _ => {}
+    }
+
+    // retain the proofs of all leaves to get all the nodes of the trie
+    let proof_keys = leaves.keys().cloned().collect();
+    let mut hasher = HashBuilder::default().with_proof_retainer(proof_keys);
+    for (key, value) in &leaves {
+        hasher.add_leaf(key.clone(), value);
+    }
+    let root = hasher.root();
+
+    // the nodes are sorted by their path, so the root node comes first
+    let nodes = hasher
+        .take_proof_nodes()
+        .into_nodes_sorted()
+        .into_iter()
+        .map(|(_, node)| node)
+        .collect();
+
+    (root, nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn empty_state_root() {
+        assert_eq!(InMemoryDb::new().state_root(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn state_root() {
+        let address = address!("0000000000000000000000000000000000000042");
+        let mut db = InMemoryDb::new();
+        db.set_balance(address, U256::from(1));
+        db.set_storage(address, U256::ZERO, U256::from(42));
+
+        let (root, nodes) = db.state_trie();
+        let trie = MerkleTrie::from_rlp_nodes(nodes).unwrap();
+        assert_eq!(trie.hash_slow(), root);
+
+        let account: StateAccount = trie.get_rlp(keccak256(address)).unwrap().unwrap();
+        assert_eq!(account.balance, U256::from(1));
+        assert_eq!(account.code_hash, KECCAK_EMPTY);
+        assert_ne!(account.storage_root, EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn zero_storage() {
+        let address = address!("0000000000000000000000000000000000000042");
+        let mut db = InMemoryDb::new();
+        db.set_storage(address, U256::ZERO, U256::from(42));
+        db.set_storage(address, U256::ZERO, U256::ZERO);
+
+        let (root, _) = db.accounts[&address].storage_trie();
+        assert_eq!(root, EMPTY_ROOT_HASH);
+    }
+}
@@ -12,13 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! [Database] implementations.
+//! [Database] implementations and other sources of EVM state.
 //!
 //! [Database]: revm::Database
 mod alloy;
+mod memory;
 mod proof;
 mod provider;
 
 pub use alloy::AlloyDb;
-pub use proof::ProofDb;
-pub(crate) use provider::{ProviderConfig, ProviderDb};
+pub use memory::InMemoryDb;
+pub use proof::{AccessReport, ProofDb, ProofProgress};
+pub use provider::RetryError;
+pub(crate) use provider::{ProgressCallback, ProviderConfig, ProviderDb};
@@ -12,8 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::{provider::ProviderDb, AlloyDb};
-use crate::MerkleTrie;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{
+    provider::{ProviderConfig, ProviderDb},
+    AlloyDb,
+};
+use crate::{mpt::EMPTY_ROOT_HASH, MerkleTrie, StateAccount};
 use alloy::{
     eips::eip2930::{AccessList, AccessListItem},
     network::{primitives::BlockTransactionsKind, BlockResponse, Network},
@@ -22,14 +27,51 @@ use alloy::{
     transports::Transport,
 };
 use alloy_primitives::{
+    keccak256,
     map::{hash_map, AddressHashMap, B256HashMap, B256HashSet, HashSet},
     Address, BlockNumber, Bytes, StorageKey, StorageValue, B256, U256,
 };
-use anyhow::{ensure, Context, Result};
+use anyhow::{bail, ensure, Context, Result};
+use futures_util::{stream, StreamExt, TryStreamExt};
 use revm::{
     primitives::{AccountInfo, Bytecode},
     Database,
 };
+use serde::{Deserialize, Serialize};
+
+/// The progress of fetching the EIP-1186 proofs for the accessed state.
+///
+/// This is reported to the callback configured with [EvmEnvBuilder::on_progress] after each
+/// `eth_getProof` response, when the input is created.
+///
+/// [EvmEnvBuilder::on_progress]: crate::host::EvmEnvBuilder::on_progress
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProofProgress {
+    /// Number of accounts whose proof has been fetched.
+    pub accounts_fetched: usize,
+    /// Total number of accounts whose proof needs to be fetched.
+    pub accounts_total: usize,
+    /// Number of storage slots whose proof has been fetched.
+    pub slots_fetched: usize,
+    /// Total number of storage slots whose proof needs to be fetched.
+    pub slots_total: usize,
+}
+
+/// The state accessed during preflight and thus included in the input.
+///
+/// This is returned by [EvmEnv::access_report] and can be used to audit which state a proven
+/// computation depends on, or to debug missing state in the guest.
+///
+/// [EvmEnv::access_report]: crate::EvmEnv::access_report
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct AccessReport {
+    /// The accessed accounts and their accessed storage slots.
+    pub accounts: BTreeMap<Address, BTreeSet<B256>>,
+    /// The code hashes of all contracts whose bytecode was accessed.
+    pub code_hashes: BTreeSet<B256>,
+}
 
 /// A simple revm [Database] wrapper that records all DB queries.
 pub struct ProofDb<D> {
@@ -73,11 +115,28 @@ impl<D: Database> ProofDb<D> {
         add_proof(&mut self.proofs, proof)
     }
 
+    /// Returns the accessed accounts and their accessed storage keys.
+    pub fn accounts(&self) -> &AddressHashMap<B256HashSet> {
+        &self.accounts
+    }
+
     /// Returns the referenced contracts
     pub fn contracts(&self) -> &B256HashMap<Bytes> {
         &self.contracts
     }
 
+    /// Returns an [AccessReport] of the accessed accounts, storage slots and contracts.
+    pub fn access_report(&self) -> AccessReport {
+        AccessReport {
+            accounts: self
+                .accounts
+                .iter()
+                .map(|(address, keys)| (*address, keys.iter().copied().collect()))
+                .collect(),
+            code_hashes: self.contracts.keys().copied().collect(),
+        }
+    }
+
     /// Returns the underlying [Database].
     pub fn inner(&self) -> &D {
         &self.inner
@@ -112,6 +171,46 @@ impl<T: Transport + Clone, N: Network, P: Provider<T, N>> ProofDb<AlloyDb<T, N,
         Ok(())
     }
 
+    /// Records all accounts and storage keys of the `access_list` as accessed and fetches their
+    /// EIP-1186 proofs.
+    ///
+    /// In contrast to [ProofDb::add_access_list], the listed state becomes part of the input,
+    /// even if it is never accessed during the execution.
+    pub async fn include_access_list(&mut self, access_list: AccessList) -> Result<()> {
+        for AccessListItem {
+            address,
+            storage_keys,
+        } in &access_list.0
+        {
+            self.accounts
+                .entry(*address)
+                .or_default()
+                .extend(storage_keys.iter().copied());
+        }
+
+        self.add_access_list(access_list).await
+    }
+
+    /// Returns a new [ProofDb] with the same recorded queries, but without any of the fetched
+    /// proofs.
+    ///
+    /// The new DB shares the provider, but bypasses any cache, so that creating the input from it
+    /// fetches all the data again. This is used to detect nondeterministic preflights.
+    pub(crate) fn replay(&self) -> ProofDb<AlloyDb<T, N, &P>> {
+        let mut config = self.inner.config().clone();
+        config.cache_dir = None;
+        config.on_progress = None;
+        config.verify_determinism = false;
+
+        ProofDb {
+            accounts: self.accounts.clone(),
+            contracts: self.contracts.clone(),
+            block_hash_numbers: self.block_hash_numbers.clone(),
+            proofs: Default::default(),
+            inner: AlloyDb::new(self.inner.provider(), config, self.inner.block_hash()),
+        }
+    }
+
     /// Returns the proof (hash chain) of all `blockhash` calls recorded by the [Database].
     pub async fn ancestor_proof(
         &self,
@@ -122,9 +221,12 @@ impl<T: Transport + Clone, N: Network, P: Provider<T, N>> ProofDb<AlloyDb<T, N,
             assert!(block_hash_min_number <= block_number);
 
             let provider = self.inner.provider();
+            let config = self.inner.config();
             for number in (block_hash_min_number..block_number).rev() {
-                let rpc_block = provider
-                    .get_block_by_number(number.into(), BlockTransactionsKind::Hashes)
+                let rpc_block = config
+                    .retry(|| {
+                        provider.get_block_by_number(number.into(), BlockTransactionsKind::Hashes)
+                    })
                     .await
                     .context("eth_getBlockByNumber failed")?
                     .with_context(|| format!("block {} not found", number))?;
@@ -137,66 +239,143 @@ impl<T: Transport + Clone, N: Network, P: Provider<T, N>> ProofDb<AlloyDb<T, N,
 
     /// Returns the merkle proofs (sparse [MerkleTrie]) for the state and all storage queries
     /// recorded by the [Database].
+    ///
+    /// If no accounts have been accessed, the returned state trie is empty.
     pub async fn state_proof(&mut self) -> Result<(MerkleTrie, Vec<MerkleTrie>)> {
-        ensure!(
-            !self.accounts.is_empty(),
-            "no accounts accessed: use Contract::preflight"
-        );
-
-        let proofs = &mut self.proofs;
-        for (address, storage_keys) in &self.accounts {
-            let account_proof = proofs.get(address);
-            let storage_keys: Vec<_> = storage_keys
-                .iter()
-                .cloned()
-                .filter(filter_existing_keys(account_proof))
-                .collect();
-
-            if account_proof.is_none() || !storage_keys.is_empty() {
-                log::trace!("PROOF: address={}, #keys={}", address, storage_keys.len());
-                let proof = self
-                    .inner
-                    .get_eip1186_proof(*address, storage_keys)
-                    .await
-                    .context("eth_getProof failed")?;
+        // the accessed accounts are final, so all missing proofs can be determined upfront
+        let requests: Vec<_> = self
+            .accounts
+            .iter()
+            .filter_map(|(address, storage_keys)| {
+                let account_proof = self.proofs.get(address);
+                let storage_keys: Vec<_> = storage_keys
+                    .iter()
+                    .cloned()
+                    .filter(filter_existing_keys(account_proof))
+                    .collect();
+
+                (account_proof.is_none() || !storage_keys.is_empty())
+                    .then_some((*address, storage_keys))
+            })
+            .collect();
+
+        let mut progress = ProofProgress {
+            accounts_total: requests.len(),
+            slots_total: requests.iter().map(|(_, keys)| keys.len()).sum(),
+            ..Default::default()
+        };
+        let on_progress = self.inner.config().on_progress.clone();
+        let report = |progress: ProofProgress| {
+            if let Some(callback) = &on_progress {
+                (callback.0)(progress);
+            }
+        };
+        report(progress);
+
+        let inner = &self.inner;
+        let config = inner.config();
+        let batches = into_batches(requests, config);
+        let responses: Vec<Vec<_>> = stream::iter(batches)
+            .map(|mut batch| async move {
+                let addresses: Vec<_> = batch.iter().map(|(address, _)| *address).collect();
+                let proofs = if batch.len() == 1 {
+                    let (address, storage_keys) = batch.pop().unwrap();
+                    log::trace!("PROOF: address={}, #keys={}", address, storage_keys.len());
+                    vec![inner
+                        .get_eip1186_proof(address, storage_keys)
+                        .await
+                        .context("eth_getProof failed")?]
+                } else {
+                    log::trace!("PROOF: batch of {} accounts", batch.len());
+                    inner
+                        .get_eip1186_proofs_batched(batch)
+                        .await
+                        .context("eth_getProof failed")?
+                };
                 ensure!(
-                    &proof.address == address,
+                    proofs.iter().map(|proof| proof.address).eq(addresses),
                     "eth_getProof response does not match request"
                 );
-                add_proof(proofs, proof).context("invalid eth_getProof response")?;
-            }
+                Ok(proofs)
+            })
+            .buffer_unordered(config.eip1186_proof_concurrency)
+            .inspect_ok(|proofs| {
+                progress.accounts_fetched += proofs.len();
+                progress.slots_fetched += proofs
+                    .iter()
+                    .map(|proof| proof.storage_proof.len())
+                    .sum::<usize>();
+                report(progress);
+            })
+            .try_collect()
+            .await?;
+
+        // the proofs are indexed by address, so the order of the responses does not matter
+        let proofs = &mut self.proofs;
+        for proof in responses.into_iter().flatten() {
+            add_proof(proofs, proof).context("invalid eth_getProof response")?;
         }
 
-        let state_nodes = self
-            .accounts
-            .keys()
-            .filter_map(|address| proofs.get(address))
-            .flat_map(|proof| proof.account_proof.iter());
-        let state_trie = MerkleTrie::from_rlp_nodes(state_nodes).context("accountProof invalid")?;
+        build_tries(&self.accounts, proofs)
+    }
 
-        let mut storage_tries = B256HashMap::default();
+    /// Verifies the EIP-1186 proofs of all the accounts and storage slots recorded by the
+    /// [Database] against the given `state_root`.
+    ///
+    /// This must be called after [ProofDb::state_proof], which fetches all the required proofs.
+    pub fn verify_proofs(&self, state_root: B256) -> Result<()> {
         for (address, storage_keys) in &self.accounts {
-            // if no storage keys have been accessed, we don't need to prove anything
-            if storage_keys.is_empty() {
-                continue;
-            }
+            let Some(proof) = self.proofs.get(address) else {
+                bail!("no eth_getProof response for account {}", address);
+            };
+            verify_account_proof(*address, storage_keys, proof, state_root)?;
+        }
 
-            // safe unwrap: added a proof for each account in the previous loop
-            let storage_proofs = &proofs.get(address).unwrap().storage_proofs;
+        Ok(())
+    }
 
-            let storage_nodes = storage_keys
-                .iter()
-                .filter_map(|key| storage_proofs.get(key))
-                .flat_map(|proof| proof.proof.iter());
-            let storage_trie =
-                MerkleTrie::from_rlp_nodes(storage_nodes).context("storageProof invalid")?;
-            let storage_root_hash = storage_trie.hash_slow();
+    /// Validates the EIP-1186 proofs of all the accounts and storage slots recorded by the
+    /// [Database] against the given `state_root` and tries to recover the invalid ones.
+    ///
+    /// Some providers return proofs with missing intermediate nodes, e.g. when many storage keys
+    /// are requested in a single call. An invalid proof is first requested again for each storage
+    /// key individually. If it is still incomplete, the missing nodes are fetched by their hash
+    /// using `debug_dbGet`. This returns `true` if any proof has been replaced and an error if a
+    /// proof could not be recovered.
+    ///
+    /// This must be called after [ProofDb::state_proof], which fetches all the required proofs.
+    pub async fn recover_proofs(&mut self, state_root: B256) -> Result<bool> {
+        let mut recovered = false;
+        for (address, storage_keys) in &self.accounts {
+            let Some(proof) = self.proofs.get(address) else {
+                bail!("no eth_getProof response for account {}", address);
+            };
+            let Err(err) = verify_account_proof(*address, storage_keys, proof, state_root) else {
+                continue;
+            };
+            log::warn!(
+                "Invalid eth_getProof response, trying to recover: {:#}",
+                err
+            );
 
-            storage_tries.insert(storage_root_hash, storage_trie);
+            let proof = recover_account_proof(&self.inner, *address, storage_keys, state_root)
+                .await
+                .with_context(|| {
+                    format!(
+                        "failed to recover eth_getProof response for account {}",
+                        address
+                    )
+                })?;
+            self.proofs.insert(*address, proof);
+            recovered = true;
         }
-        let storage_tries = storage_tries.into_values().collect();
 
-        Ok((state_trie, storage_tries))
+        Ok(recovered)
+    }
+
+    /// Returns the sparse [MerkleTrie]s of all the proofs, without fetching any missing proofs.
+    pub(crate) fn build_tries(&self) -> Result<(MerkleTrie, Vec<MerkleTrie>)> {
+        build_tries(&self.accounts, &self.proofs)
     }
 }
 
@@ -247,6 +426,221 @@ impl<DB: Database> Database for ProofDb<DB> {
     }
 }
 
+/// Verifies the EIP-1186 proof of the account and the given storage keys against `state_root`.
+fn verify_account_proof(
+    address: Address,
+    storage_keys: &B256HashSet,
+    proof: &AccountProof,
+    state_root: B256,
+) -> Result<()> {
+    let state_trie = MerkleTrie::from_rlp_nodes(&proof.account_proof)
+        .with_context(|| format!("accountProof invalid for account {}", address))?;
+    ensure!(
+        state_trie.hash_slow() == state_root,
+        "accountProof for account {} does not match the stateRoot",
+        address
+    );
+    let address_hash = keccak256(address);
+    ensure!(
+        state_trie.is_resolved(address_hash),
+        "accountProof for account {} is incomplete",
+        address
+    );
+    let storage_root = state_trie
+        .get_rlp::<StateAccount>(address_hash)
+        .with_context(|| format!("accountProof invalid for account {}", address))?
+        .map_or(EMPTY_ROOT_HASH, |account| account.storage_root);
+
+    for key in storage_keys {
+        let Some(storage_proof) = proof.storage_proofs.get(key) else {
+            bail!("no storageProof for key {} of account {}", key, address);
+        };
+        let storage_trie = MerkleTrie::from_rlp_nodes(&storage_proof.proof)
+            .with_context(|| format!("storageProof invalid for account {}", address))?;
+        ensure!(
+            storage_trie.hash_slow() == storage_root,
+            "storageProof for key {} of account {} does not match the storageRoot",
+            key,
+            address
+        );
+        ensure!(
+            storage_trie.is_resolved(keccak256(key)),
+            "storageProof for key {} of account {} is incomplete",
+            key,
+            address
+        );
+    }
+
+    Ok(())
+}
+
+/// Fetches the EIP-1186 proof of the account and each of the given storage keys individually and
+/// completes them using [resolve_proof].
+async fn recover_account_proof<T, N, P>(
+    db: &AlloyDb<T, N, P>,
+    address: Address,
+    storage_keys: &B256HashSet,
+    state_root: B256,
+) -> Result<AccountProof>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N>,
+{
+    let response = db
+        .get_eip1186_proof(address, vec![])
+        .await
+        .context("eth_getProof failed")?;
+    ensure!(
+        response.address == address,
+        "eth_getProof response does not match request"
+    );
+    let address_hash = keccak256(address);
+    let account_proof = resolve_proof(db, response.account_proof, state_root, address_hash)
+        .await
+        .context("accountProof invalid")?;
+    let storage_root = MerkleTrie::from_rlp_nodes(&account_proof)?
+        .get_rlp::<StateAccount>(address_hash)?
+        .map_or(EMPTY_ROOT_HASH, |account| account.storage_root);
+
+    let mut storage_proofs = B256HashMap::default();
+    for key in storage_keys {
+        let response = db
+            .get_eip1186_proof(address, vec![*key])
+            .await
+            .context("eth_getProof failed")?;
+        let Some(proof) = response
+            .storage_proof
+            .into_iter()
+            .find(|proof| proof.key.as_b256() == *key)
+        else {
+            bail!("no storageProof for key {}", key);
+        };
+        let nodes = resolve_proof(db, proof.proof, storage_root, keccak256(key))
+            .await
+            .with_context(|| format!("storageProof invalid for key {}", key))?;
+        storage_proofs.insert(
+            *key,
+            StorageProof {
+                value: proof.value,
+                proof: nodes,
+            },
+        );
+    }
+
+    Ok(AccountProof {
+        account_proof,
+        storage_proofs,
+    })
+}
+
+/// Completes the MPT proof of `key` in the trie with the given `root` by fetching each missing
+/// node by its hash.
+async fn resolve_proof<T, N, P>(
+    db: &AlloyDb<T, N, P>,
+    mut nodes: Vec<Bytes>,
+    root: B256,
+    key: B256,
+) -> Result<Vec<Bytes>>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N>,
+{
+    // each iteration resolves one more node on the path of the key, so this always terminates
+    loop {
+        let trie = MerkleTrie::from_rlp_nodes(&nodes)?;
+        let missing = if trie.hash_slow() != root {
+            root
+        } else {
+            match trie.unresolved_digest(key) {
+                Some(digest) => digest,
+                None => return Ok(nodes),
+            }
+        };
+
+        log::debug!("NODE: hash={}", missing);
+        let node = db
+            .get_trie_node(missing)
+            .await
+            .with_context(|| format!("debug_dbGet failed for trie node {}", missing))?;
+        ensure!(
+            keccak256(&node) == missing,
+            "debug_dbGet returned an invalid trie node for {}",
+            missing
+        );
+        // the root node must always be the first node of a proof
+        if missing == root {
+            nodes.insert(0, node);
+        } else {
+            nodes.push(node);
+        }
+    }
+}
+
+/// Groups the `eth_getProof` requests into JSON-RPC batches, if enabled in the `config`.
+///
+/// Without batching, every account forms its own batch. Accounts with more keys than the chunk
+/// size require several calls and are never batched.
+fn into_batches(
+    requests: Vec<(Address, Vec<StorageKey>)>,
+    config: &ProviderConfig,
+) -> Vec<Vec<(Address, Vec<StorageKey>)>> {
+    if !config.batch_requests {
+        return requests.into_iter().map(|request| vec![request]).collect();
+    }
+
+    let (large, small): (Vec<_>, Vec<_>) = requests
+        .into_iter()
+        .partition(|(_, keys)| keys.len() > config.eip1186_proof_chunk_size);
+    let mut batches: Vec<_> = large.into_iter().map(|request| vec![request]).collect();
+    batches.extend(
+        small
+            .chunks(config.eip1186_proof_batch_size)
+            .map(<[_]>::to_vec),
+    );
+
+    batches
+}
+
+/// Builds the sparse [MerkleTrie]s for the state and the storage of the given `accounts`.
+fn build_tries(
+    accounts: &AddressHashMap<B256HashSet>,
+    proofs: &AddressHashMap<AccountProof>,
+) -> Result<(MerkleTrie, Vec<MerkleTrie>)> {
+    let state_nodes = accounts
+        .keys()
+        .filter_map(|address| proofs.get(address))
+        .flat_map(|proof| proof.account_proof.iter());
+    let state_trie = MerkleTrie::from_rlp_nodes(state_nodes).context("accountProof invalid")?;
+
+    // the storage tries are deduplicated and ordered by their root, so that the resulting input
+    // does not depend on the iteration order of the accessed accounts
+    let mut storage_tries = BTreeMap::new();
+    for (address, storage_keys) in accounts {
+        // if no storage keys have been accessed, we don't need to prove anything
+        if storage_keys.is_empty() {
+            continue;
+        }
+
+        // safe unwrap: a proof has been added for each account
+        let storage_proofs = &proofs.get(address).unwrap().storage_proofs;
+
+        let storage_nodes = storage_keys
+            .iter()
+            .filter_map(|key| storage_proofs.get(key))
+            .flat_map(|proof| proof.proof.iter());
+        let storage_trie =
+            MerkleTrie::from_rlp_nodes(storage_nodes).context("storageProof invalid")?;
+        let storage_root_hash = storage_trie.hash_slow();
+
+        storage_tries.insert(storage_root_hash, storage_trie);
+    }
+    let storage_tries = storage_tries.into_values().collect();
+
+    Ok((state_trie, storage_tries))
+}
+
 fn filter_existing_keys(account_proof: Option<&AccountProof>) -> impl Fn(&StorageKey) -> bool + '_ {
     move |key| {
         !account_proof
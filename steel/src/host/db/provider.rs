@@ -12,13 +12,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{
+    collections::hash_map::RandomState,
+    fmt, fs,
+    future::{Future, IntoFuture},
+    hash::{BuildHasher, Hasher},
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use super::ProofProgress;
+
 use alloy::{
-    network::Network, providers::Provider, rpc::types::EIP1186AccountProofResponse,
-    transports::Transport,
+    eips::BlockId,
+    network::Network,
+    providers::Provider,
+    rpc::{
+        client::{BatchRequest, Waiter},
+        types::EIP1186AccountProofResponse,
+    },
+    transports::{Transport, TransportResult},
 };
-use alloy_primitives::{Address, BlockHash, StorageKey};
+use alloy_primitives::{keccak256, Address, BlockHash, Bytes, StorageKey, B256};
 use anyhow::{ensure, Result};
 use revm::Database;
+use serde::{de::DeserializeOwned, Serialize};
 
 /// Additional configuration for a [Provider].
 #[derive(Clone, Debug)]
@@ -26,14 +46,161 @@ use revm::Database;
 pub struct ProviderConfig {
     /// Max number of storage keys to request in a single `eth_getProof` call.
     pub eip1186_proof_chunk_size: usize,
+    /// Max number of concurrent `eth_getProof` calls.
+    pub eip1186_proof_concurrency: usize,
+    /// Whether to combine the `eth_getProof` calls of several accounts into JSON-RPC batches.
+    pub batch_requests: bool,
+    /// Max number of `eth_getProof` calls in a single JSON-RPC batch.
+    pub eip1186_proof_batch_size: usize,
+    /// Whether to verify the `eth_getProof` responses against the state root during preflight.
+    pub verify_eip1186_proofs: bool,
+    /// Whether to build the input twice to verify that the preflight is deterministic.
+    pub verify_determinism: bool,
+    /// Max number of times a failed RPC call is retried.
+    pub rpc_retries: usize,
+    /// Initial delay before retrying a failed RPC call, doubled for each subsequent retry.
+    pub rpc_retry_backoff: Duration,
+    /// Directory to cache the RPC responses in, if any.
+    pub cache_dir: Option<PathBuf>,
+    /// Callback to report the progress of fetching the proofs, if any.
+    pub(crate) on_progress: Option<ProgressCallback>,
+    /// Size in bytes of the encoded input above which a warning is logged, if any.
+    pub input_size_warning: Option<usize>,
+    /// Whether to skip the validation of the proofs returned by the RPC node. Development only.
+    pub trust_rpc_state: bool,
+    /// Whether to include the `eth_createAccessList` result of each call in the input.
+    pub auto_access_list: bool,
 }
 
 impl Default for ProviderConfig {
     fn default() -> Self {
         Self {
             eip1186_proof_chunk_size: 1000,
+            eip1186_proof_concurrency: 16,
+            batch_requests: false,
+            eip1186_proof_batch_size: 100,
+            verify_eip1186_proofs: false,
+            verify_determinism: false,
+            rpc_retries: 0,
+            rpc_retry_backoff: Duration::from_millis(500),
+            cache_dir: None,
+            on_progress: None,
+            input_size_warning: None,
+            trust_rpc_state: false,
+            auto_access_list: false,
+        }
+    }
+}
+
+/// A shareable callback to report the [ProofProgress] to.
+#[derive(Clone)]
+pub(crate) struct ProgressCallback(pub(crate) Arc<dyn Fn(ProofProgress) + Send + Sync>);
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+impl ProviderConfig {
+    /// Executes the RPC call created by `call` and retries it on failure.
+    ///
+    /// The call is retried at most `rpc_retries` times using an exponential backoff with random
+    /// jitter, so it must only be used for idempotent calls.
+    pub(crate) async fn retry<R, E, F, Fut>(&self, mut call: F) -> Result<R, RetryError<E>>
+    where
+        E: std::error::Error + 'static,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<R, E>>,
+    {
+        let mut backoff = self.rpc_retry_backoff;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match call().await {
+                Ok(result) => return Ok(result),
+                Err(source) if attempts > self.rpc_retries => {
+                    return Err(RetryError { attempts, source })
+                }
+                Err(err) => {
+                    let delay = with_jitter(backoff);
+                    log::debug!("RPC call failed, retrying in {:?}: {}", delay, err);
+                    tokio::time::sleep(delay).await;
+                    backoff = backoff.saturating_mul(2);
+                }
+            }
+        }
+    }
+
+    /// Returns the result of the RPC call created by `call`, using the cache if configured.
+    ///
+    /// Responses are cached by `block_hash`, `method` and `params`. As the queried state of a
+    /// fixed block never changes, cached responses can be reused indefinitely. Cache misses fall
+    /// through to `call` and its result is written back to the cache.
+    pub(crate) async fn cached<R, E, F, Fut>(
+        &self,
+        block_hash: BlockHash,
+        method: &str,
+        params: impl Serialize,
+        call: F,
+    ) -> Result<R, E>
+    where
+        R: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<R, E>>,
+    {
+        let Some(cache_dir) = &self.cache_dir else {
+            return call().await;
+        };
+
+        // hash the params to get a valid file name of bounded length
+        let params = serde_json::to_vec(&params).expect("failed to serialize params");
+        let path = cache_dir.join(block_hash.to_string()).join(format!(
+            "{}_{}.json",
+            method,
+            keccak256(params)
+        ));
+
+        // treat unreadable or corrupted entries as cache misses
+        let cached = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok());
+        if let Some(result) = cached {
+            log::trace!("CACHE: {}", path.display());
+            return Ok(result);
+        }
+
+        let result = call().await?;
+        if let Err(err) = write_json(&path, &result) {
+            log::warn!("Failed to write cache {}: {}", path.display(), err);
         }
+
+        Ok(result)
+    }
+}
+
+/// Writes `value` as JSON to `path`, creating the parent directories if necessary.
+fn write_json(path: &Path, value: &impl Serialize) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
     }
+    fs::write(path, serde_json::to_vec(value)?)
+}
+
+/// Error of an RPC call that still failed after all retries.
+#[derive(Debug, thiserror::Error)]
+#[error("failed after {attempts} attempt(s)")]
+pub struct RetryError<E: std::error::Error + 'static> {
+    attempts: usize,
+    #[source]
+    source: E,
+}
+
+/// Returns a random duration between 50% and 150% of `duration`.
+fn with_jitter(duration: Duration) -> Duration {
+    // the random seed of the std hasher is sufficient for jitter and avoids an extra dependency
+    let random = RandomState::new().build_hasher().finish();
+    duration.mul_f64(0.5 + random as f64 / u64::MAX as f64)
 }
 
 /// A [Database] backed by a [Provider].
@@ -63,18 +230,30 @@ where
         // for certain RPC nodes it seemed beneficial when the keys are in the correct order
         keys.sort_unstable();
 
-        let mut iter = keys.chunks(self.config().eip1186_proof_chunk_size);
+        let config = self.config();
+        let mut iter = keys.chunks(config.eip1186_proof_chunk_size);
         // always make at least one call even if the keys are empty
-        let mut account_proof = self
-            .provider()
-            .get_proof(address, iter.next().unwrap_or_default().into())
-            .hash(hash)
+        let keys = iter.next().unwrap_or_default();
+        let mut account_proof = config
+            .cached(hash, "eth_getProof", (address, keys), || {
+                config.retry(|| {
+                    self.provider()
+                        .get_proof(address, keys.into())
+                        .hash(hash)
+                        .into_future()
+                })
+            })
             .await?;
         for keys in iter {
-            let proof = self
-                .provider()
-                .get_proof(address, keys.into())
-                .hash(hash)
+            let proof = config
+                .cached(hash, "eth_getProof", (address, keys), || {
+                    config.retry(|| {
+                        self.provider()
+                            .get_proof(address, keys.into())
+                            .hash(hash)
+                            .into_future()
+                    })
+                })
                 .await?;
             // only the keys have changed, the account proof should not change
             ensure!(
@@ -87,4 +266,137 @@ where
 
         Ok(account_proof)
     }
+
+    /// Get the EIP-1186 proofs of several accounts using a single JSON-RPC batch request.
+    ///
+    /// All keys of an account are requested in a single `eth_getProof` call, so the number of
+    /// keys must not exceed the configured chunk size. If the batch fails, e.g. because the RPC
+    /// node does not support batch requests, each proof is requested individually using
+    /// [ProviderDb::get_eip1186_proof] instead. Batched responses are not cached.
+    async fn get_eip1186_proofs_batched(
+        &self,
+        mut requests: Vec<(Address, Vec<StorageKey>)>,
+    ) -> Result<Vec<EIP1186AccountProofResponse>> {
+        let block_id = BlockId::from(self.block_hash());
+        for (_, keys) in &mut requests {
+            keys.sort_unstable();
+        }
+
+        let batched = async {
+            let mut batch = BatchRequest::new(self.provider().client());
+            let waiters = requests
+                .iter()
+                .map(|(address, keys)| {
+                    batch.add_call("eth_getProof", &(*address, keys.clone(), block_id))
+                })
+                .collect::<TransportResult<Vec<Waiter<EIP1186AccountProofResponse>>>>()?;
+            batch.send().await?;
+            futures_util::future::try_join_all(waiters).await
+        };
+        match batched.await {
+            Ok(proofs) => Ok(proofs),
+            Err(err) => {
+                log::debug!(
+                    "Batched eth_getProof failed, falling back to individual calls: {}",
+                    err
+                );
+                let mut proofs = Vec::with_capacity(requests.len());
+                for (address, keys) in requests {
+                    proofs.push(self.get_eip1186_proof(address, keys).await?);
+                }
+                Ok(proofs)
+            }
+        }
+    }
+
+    /// Get the RLP-encoded trie node with the given hash using `debug_dbGet`.
+    ///
+    /// This is only supported by nodes that store trie nodes by their hash in the database, e.g.
+    /// Geth using the hash-based state scheme.
+    async fn get_trie_node(&self, hash: B256) -> Result<Bytes> {
+        let config = self.config();
+        let node: Bytes = config
+            .cached(self.block_hash(), "debug_dbGet", hash, || {
+                config.retry(|| self.provider().raw_request("debug_dbGet".into(), (hash,)))
+            })
+            .await?;
+
+        Ok(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[tokio::test]
+    async fn retry() {
+        let config = ProviderConfig {
+            rpc_retries: 2,
+            rpc_retry_backoff: Duration::from_millis(1),
+            ..Default::default()
+        };
+
+        // succeeds with the last retry
+        let calls = Cell::new(0);
+        let result = config
+            .retry(|| {
+                calls.set(calls.get() + 1);
+                let result = if calls.get() < 3 {
+                    Err(std::fmt::Error)
+                } else {
+                    Ok(calls.get())
+                };
+                async move { result }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 3);
+
+        // fails after all retries
+        let calls = Cell::new(0);
+        let err = config
+            .retry(|| {
+                calls.set(calls.get() + 1);
+                async { Err::<(), _>(std::fmt::Error) }
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(calls.get(), 3);
+        assert_eq!(err.to_string(), "failed after 3 attempt(s)");
+    }
+
+    #[tokio::test]
+    async fn cached() {
+        let cache_dir = std::env::temp_dir().join(format!("steel-cache-{}", std::process::id()));
+        let config = ProviderConfig {
+            cache_dir: Some(cache_dir.clone()),
+            ..Default::default()
+        };
+        let hash = BlockHash::repeat_byte(0x01);
+
+        // a cache miss must execute the call
+        let result = config
+            .cached(hash, "test", 42, || async {
+                Ok::<_, std::fmt::Error>(1u64)
+            })
+            .await;
+        assert_eq!(result.unwrap(), 1);
+        // a cache hit must not execute the call
+        let result = config
+            .cached(hash, "test", 42, || async {
+                Err::<u64, _>(std::fmt::Error)
+            })
+            .await;
+        assert_eq!(result.unwrap(), 1);
+        // different params must not be served from the cache
+        let result = config
+            .cached(hash, "test", 43, || async {
+                Ok::<_, std::fmt::Error>(2u64)
+            })
+            .await;
+        assert_eq!(result.unwrap(), 2);
+
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
 }
@@ -0,0 +1,90 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::db::RetryError;
+use alloy::transports::TransportError;
+use std::sync::Arc;
+
+/// Errors returned when building an [EvmEnv] or converting it into an [EvmInput] on the host.
+///
+/// This implements [std::error::Error], so it converts into an [anyhow::Error] using `?`.
+///
+/// [EvmEnv]: crate::EvmEnv
+/// [EvmInput]: crate::EvmInput
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The requested block does not exist.
+    #[error("block {0} not found")]
+    BlockNotFound(String),
+    /// The RPC node returned no block for the `safe` or `finalized` tag.
+    ///
+    /// Some chains, e.g. certain L2s, and older clients do not implement these tags.
+    #[error("block tag {0} not supported by the RPC node, use `latest` or a block number instead")]
+    UnsupportedBlockTag(String),
+    /// The HTTP client of [EvmEnvBuilder::rpc] could not be created.
+    ///
+    /// [EvmEnvBuilder::rpc]: super::EvmEnvBuilder::rpc
+    #[error("failed to build HTTP client")]
+    HttpClient(#[source] Arc<reqwest::Error>),
+    /// The connection to the WebSocket or IPC endpoint could not be established.
+    #[error("failed to connect to {0}")]
+    Connection(&'static str, #[source] TransportError),
+    /// An RPC call still failed after all retries, e.g. because the node is unreachable.
+    #[error("{0} failed")]
+    Rpc(&'static str, #[source] RetryError<TransportError>),
+    /// The header returned by the RPC could not be converted into the [EvmBlockHeader].
+    ///
+    /// [EvmBlockHeader]: crate::EvmBlockHeader
+    #[error("header invalid: {0}")]
+    HeaderConversion(String),
+    /// The header returned by the RPC does not match the requested block.
+    #[error("header inconsistent: {0}")]
+    HeaderMismatch(&'static str),
+    /// The commitment could not be created, e.g. because the Beacon API failed.
+    #[error("failed to create commitment")]
+    Commitment(#[source] anyhow::Error),
+    /// The configured chain spec does not support the execution block.
+    #[error("chain spec invalid")]
+    ChainSpec(#[source] anyhow::Error),
+    /// The proofs of the accessed state could not be created.
+    #[error("failed to create proofs")]
+    Proof(#[source] anyhow::Error),
+    /// A call could not be preflighted, e.g. because it reverted.
+    #[error("{0} failed")]
+    Call(String, #[source] anyhow::Error),
+    /// No calls were given to preflight.
+    #[error("no calls to preflight")]
+    NoCalls,
+    /// The inputs of several preflights could not be merged.
+    #[error("failed to merge inputs")]
+    Merge(#[source] anyhow::Error),
+}
+
+/// Error returned when parsing a [BlockNumberOrTag] from a string.
+///
+/// [BlockNumberOrTag]: super::BlockNumberOrTag
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ParseBlockNumberOrTagError {
+    /// The `pending` tag was given, which cannot be committed to.
+    #[error("pending blocks are not supported, as they cannot be committed to")]
+    Pending,
+    /// The block number could not be parsed.
+    #[error("invalid block number: {0}")]
+    Number(#[from] std::num::ParseIntError),
+    /// The string is neither a supported tag nor a block number.
+    #[error("invalid block number or tag: {0}")]
+    Invalid(String),
+}
@@ -0,0 +1,372 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Size of values in the serde format used to pass inputs into the zkVM.
+use serde::{ser, Serialize};
+use std::fmt::Display;
+
+/// The size in bytes of a word of the zkVM serde format.
+const WORD_SIZE: usize = 4;
+
+/// Returns the number of bytes of `value` encoded by `risc0_zkvm::serde::to_vec`.
+///
+/// The zkVM serde format encodes every value as a sequence of 32-bit words: integers of up to 32
+/// bits use one word, 64-bit integers two and 128-bit integers four. Strings and byte arrays are
+/// prefixed with their length and padded to full words, sequences and maps are prefixed with their
+/// length and enum variants with their index. This computes the size without allocating the
+/// encoding.
+pub(crate) fn encoded_len<T: Serialize + ?Sized>(value: &T) -> Result<usize, Error> {
+    let mut counter = WordCounter(0);
+    value.serialize(&mut counter)?;
+    Ok(counter.0 * WORD_SIZE)
+}
+
+/// Error returned when a value cannot be encoded in the zkVM serde format.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub(crate) struct Error(String);
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+/// A [ser::Serializer] that only counts the number of words written.
+struct WordCounter(usize);
+
+impl WordCounter {
+    #[inline]
+    fn words(&mut self, n: usize) -> Result<(), Error> {
+        self.0 += n;
+        Ok(())
+    }
+
+    #[inline]
+    fn padded_bytes(&mut self, len: usize) -> Result<(), Error> {
+        // the length prefix followed by the bytes padded to full words
+        self.words(1 + len.div_ceil(WORD_SIZE))
+    }
+}
+
+impl ser::Serializer for &mut WordCounter {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, _: bool) -> Result<(), Error> {
+        self.words(1)
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<(), Error> {
+        self.words(1)
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<(), Error> {
+        self.words(1)
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<(), Error> {
+        self.words(1)
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<(), Error> {
+        self.words(2)
+    }
+
+    fn serialize_i128(self, _: i128) -> Result<(), Error> {
+        self.words(4)
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<(), Error> {
+        self.words(1)
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<(), Error> {
+        self.words(1)
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<(), Error> {
+        self.words(1)
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<(), Error> {
+        self.words(2)
+    }
+
+    fn serialize_u128(self, _: u128) -> Result<(), Error> {
+        self.words(4)
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<(), Error> {
+        self.words(1)
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<(), Error> {
+        self.words(2)
+    }
+
+    fn serialize_char(self, _: char) -> Result<(), Error> {
+        self.words(1)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.padded_bytes(v.len())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.padded_bytes(v.len())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.words(1)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        self.words(1)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<(), Error> {
+        self.words(1)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.words(1)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, Error> {
+        len.ok_or_else(|| Error("sequences of unknown length are not supported".into()))?;
+        self.words(1)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, Error> {
+        self.words(1)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, Error> {
+        len.ok_or_else(|| Error("maps of unknown length are not supported".into()))?;
+        self.words(1)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, Error> {
+        self.words(1)?;
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for &mut WordCounter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut WordCounter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut WordCounter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut WordCounter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut WordCounter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut WordCounter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut WordCounter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, B256};
+
+    #[test]
+    fn primitives() {
+        assert_eq!(encoded_len(&true).unwrap(), 4);
+        assert_eq!(encoded_len(&0u8).unwrap(), 4);
+        assert_eq!(encoded_len(&0u32).unwrap(), 4);
+        assert_eq!(encoded_len(&0u64).unwrap(), 8);
+        assert_eq!(encoded_len(&0u128).unwrap(), 16);
+        assert_eq!(encoded_len(&()).unwrap(), 0);
+        assert_eq!(encoded_len(&None::<u32>).unwrap(), 4);
+        assert_eq!(encoded_len(&Some(0u64)).unwrap(), 12);
+    }
+
+    #[test]
+    fn padded_bytes() {
+        // length word followed by the padded bytes
+        assert_eq!(encoded_len("").unwrap(), 4);
+        assert_eq!(encoded_len("abcde").unwrap(), 12);
+        assert_eq!(encoded_len(&Bytes::from_static(&[0; 4])).unwrap(), 8);
+        assert_eq!(encoded_len(&B256::ZERO).unwrap(), 36);
+    }
+
+    #[test]
+    fn containers() {
+        // sequences are prefixed with their length, tuples are not
+        assert_eq!(encoded_len(&vec![0u32; 3]).unwrap(), 16);
+        assert_eq!(encoded_len(&(0u32, 0u64)).unwrap(), 12);
+
+        #[derive(Serialize)]
+        enum Enum {
+            Unit,
+            Newtype(u32),
+        }
+        assert_eq!(encoded_len(&Enum::Unit).unwrap(), 4);
+        assert_eq!(encoded_len(&Enum::Newtype(0)).unwrap(), 8);
+    }
+}
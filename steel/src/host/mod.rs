@@ -15,15 +15,15 @@
 //! Functionality that is only needed for the host and not the guest.
 
 use crate::{
-    beacon::BeaconCommit,
+    beacon::{host::ProofCache, BeaconCommit},
     block::BlockInput,
     config::ChainSpec,
     ethereum::{EthBlockHeader, EthEvmEnv},
     history::HistoryCommit,
     host::db::ProviderDb,
-    ComposeInput, EvmBlockHeader, EvmEnv, EvmInput,
+    BlockHeaderCommit, Commitment, CommitmentVersion, ComposeInput, EvmBlockHeader, EvmEnv,
+    EvmInput,
 };
-use alloy::eips::eip1898::{HexStringMissingPrefixError, ParseBlockNumberError};
 use alloy::{
     network::{Ethereum, Network},
     providers::{Provider, RootProvider},
@@ -33,21 +33,33 @@ use alloy::{
         Transport,
     },
 };
-use alloy_primitives::B256;
-use anyhow::{ensure, Result};
+use alloy_primitives::{Bytes, Sealed, B256};
+use alloy_sol_types::SolValue;
+use anyhow::{bail, ensure, Context, Result};
 use core::fmt;
-use db::{AlloyDb, ProofDb};
+use db::{AccessReport, AlloyDb, ProofDb, ProviderConfig};
+use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::Display;
 use std::str::FromStr;
+use std::{fs, mem, path::Path};
 use url::Url;
 
+mod boxed;
 mod builder;
 pub mod db;
+mod error;
+mod guest_serde;
 
+pub use crate::contract::PreflightBatch;
+pub use boxed::BoxedProvider;
 pub use builder::EvmEnvBuilder;
+pub use error::{Error, ParseBlockNumberOrTagError};
 
-/// A block number (or tag - "latest", "safe", "finalized").
+/// A block number (or tag - "latest", "safe", "finalized", "earliest").
 /// This enum is used to specify which block to query when interacting with the blockchain.
+///
+/// The "pending" tag is not supported, as there is no block hash a pending block can be committed
+/// to.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub enum BlockNumberOrTag {
     /// The most recent block in the canonical chain observed by the client.
@@ -56,19 +68,28 @@ pub enum BlockNumberOrTag {
     /// The parent of the most recent block in the canonical chain observed by the client.
     /// This is equivalent to `Latest - 1`.
     Parent,
+    /// The block `N` blocks before the most recent block in the canonical chain observed by the
+    /// client. This is equivalent to `Latest - N`, e.g. to use a fixed confirmation depth.
+    Offset(u64),
     /// The most recent block considered "safe" by the client. This typically refers to a block
     /// that is sufficiently deep in the chain to be considered irreversible.
     Safe,
     /// The most recent finalized block in the chain. Finalized blocks are guaranteed to be
     /// part of the canonical chain.
     Finalized,
+    /// The earliest block in the chain, i.e. the genesis block.
+    Earliest,
     /// A specific block number in the canonical chain.
     Number(u64),
 }
 
 impl BlockNumberOrTag {
     /// Converts the `BlockNumberOrTag` into the corresponding RPC type.
-    async fn into_rpc_type<T, N, P>(self, provider: P) -> Result<AlloyBlockNumberOrTag>
+    async fn into_rpc_type<T, N, P>(
+        self,
+        provider: P,
+        config: &ProviderConfig,
+    ) -> Result<AlloyBlockNumberOrTag, Error>
     where
         T: Transport + Clone,
         N: Network,
@@ -76,21 +97,34 @@ impl BlockNumberOrTag {
     {
         let number = match self {
             BlockNumberOrTag::Latest => AlloyBlockNumberOrTag::Latest,
-            BlockNumberOrTag::Parent => {
-                let latest = provider.get_block_number().await?;
-                ensure!(latest > 0, "genesis does not have a parent");
-                AlloyBlockNumberOrTag::Number(latest - 1)
+            BlockNumberOrTag::Parent | BlockNumberOrTag::Offset(_) => {
+                // the parent is equivalent to an offset of 1
+                let n = if let BlockNumberOrTag::Offset(n) = self {
+                    n
+                } else {
+                    1
+                };
+                let latest = config
+                    .retry(|| provider.get_block_number())
+                    .await
+                    .map_err(|err| Error::Rpc("eth_blockNumber", err))?;
+                let number = latest
+                    .checked_sub(n)
+                    .ok_or_else(|| Error::BlockNotFound(self.to_string()))?;
+                AlloyBlockNumberOrTag::Number(number)
             }
             BlockNumberOrTag::Safe => AlloyBlockNumberOrTag::Safe,
             BlockNumberOrTag::Finalized => AlloyBlockNumberOrTag::Finalized,
+            BlockNumberOrTag::Earliest => AlloyBlockNumberOrTag::Earliest,
             BlockNumberOrTag::Number(n) => AlloyBlockNumberOrTag::Number(n),
         };
         Ok(number)
     }
 }
 
+/// Parses a block tag, `latest-N`, a `0x`-prefixed hex number or a decimal number.
 impl FromStr for BlockNumberOrTag {
-    type Err = ParseBlockNumberError;
+    type Err = ParseBlockNumberOrTagError;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         let block = match s {
@@ -98,12 +132,18 @@ impl FromStr for BlockNumberOrTag {
             "parent" => Self::Parent,
             "safe" => Self::Safe,
             "finalized" => Self::Finalized,
+            "earliest" => Self::Earliest,
+            "pending" => return Err(ParseBlockNumberOrTagError::Pending),
             _number => {
-                if let Some(hex_val) = s.strip_prefix("0x") {
+                if let Some(offset) = s.strip_prefix("latest-") {
+                    Self::Offset(offset.parse()?)
+                } else if let Some(hex_val) = s.strip_prefix("0x") {
                     let number = u64::from_str_radix(hex_val, 16);
                     Self::Number(number?)
+                } else if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+                    Self::Number(s.parse()?)
                 } else {
-                    return Err(HexStringMissingPrefixError::default().into());
+                    return Err(ParseBlockNumberOrTagError::Invalid(s.to_string()));
                 }
             }
         };
@@ -117,8 +157,10 @@ impl Display for BlockNumberOrTag {
             Self::Number(x) => write!(f, "0x{x:x}"),
             Self::Latest => f.write_str("latest"),
             Self::Parent => f.write_str("parent"),
+            Self::Offset(n) => write!(f, "latest-{n}"),
             Self::Safe => f.write_str("safe"),
             Self::Finalized => f.write_str("finalized"),
+            Self::Earliest => f.write_str("earliest"),
         }
     }
 }
@@ -131,12 +173,16 @@ type EthHostEvmEnv<D, C> = EthEvmEnv<ProofDb<D>, HostCommit<C>>;
 pub struct HostCommit<C> {
     inner: C,
     config_id: B256,
+    /// Slot of the Beacon block committed to, if any.
+    beacon_slot: Option<u64>,
+    /// ABI-encoded return data of all preflighted calls in execution order.
+    call_results: Vec<Bytes>,
 }
 
 impl EthHostEvmEnv<AlloyDb<Http<Client>, Ethereum, RootProvider<Http<Client>>>, ()> {
     /// Creates a new provable [EvmEnv] for Ethereum from an HTTP RPC endpoint.
     #[deprecated(since = "0.12.0", note = "use `EthEvmEnv::builder().rpc()` instead")]
-    pub async fn from_rpc(url: Url, number: BlockNumberOrTag) -> Result<Self> {
+    pub async fn from_rpc(url: Url, number: BlockNumberOrTag) -> Result<Self, Error> {
         EthEvmEnv::builder()
             .rpc(url)
             .block_number_or_tag(number)
@@ -150,12 +196,76 @@ where
     T: Transport + Clone,
     N: Network,
     P: Provider<T, N>,
-    H: EvmBlockHeader + TryFrom<<N as Network>::HeaderResponse>,
+    H: EvmBlockHeader,
+{
+    /// Creates a new provable [EvmEnv] from an [AlloyDb] and the sealed header of its block.
+    ///
+    /// This allows reusing a provider and a block that are managed elsewhere, e.g. in a larger
+    /// service that already fetched the header, without any additional RPC call. The header must
+    /// be the header of the block the `db` is pinned to. In general, [EvmEnv::builder] should be
+    /// preferred, as it also fetches and validates the header.
+    ///
+    /// ```rust,no_run
+    /// # use risc0_steel::{ethereum::{EthBlockHeader, EthEvmEnv}, host::db::AlloyDb, Contract};
+    /// # use alloy::network::primitives::BlockTransactionsKind;
+    /// # use alloy::providers::{Provider, ProviderBuilder};
+    /// # use alloy_primitives::{address, Sealable};
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// # alloy_sol_types::sol! {
+    /// #     interface IERC20 {
+    /// #         function totalSupply() external view returns (uint256);
+    /// #     }
+    /// # }
+    /// let url = "https://ethereum-rpc.publicnode.com".parse()?;
+    /// let provider = ProviderBuilder::new().on_http(url);
+    /// let block = provider
+    ///     .get_block_by_number(Default::default(), BlockTransactionsKind::Hashes)
+    ///     .await?
+    ///     .unwrap();
+    /// let header = EthBlockHeader::try_from(block.header)?.seal_slow();
+    ///
+    /// // the provider is cloned, so that its connection pool is shared
+    /// let db = AlloyDb::from_provider(provider.clone(), header.seal());
+    /// let mut env = EthEvmEnv::from_db(db, header)?;
+    ///
+    /// let usdt = address!("dAC17F958D2ee523a2206206994597C13D831ec7");
+    /// Contract::preflight(usdt, &mut env)
+    ///     .call_builder(&IERC20::totalSupplyCall {})
+    ///     .call()
+    ///     .await?;
+    /// let input = env.into_input().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_db(db: AlloyDb<T, N, P>, header: Sealed<H>) -> Result<Self, Error> {
+        if header.seal() != db.block_hash() {
+            return Err(Error::HeaderMismatch(
+                "header does not match the block of the database",
+            ));
+        }
+        let commit = HostCommit {
+            inner: (),
+            config_id: ChainSpec::DEFAULT_DIGEST,
+            beacon_slot: None,
+            call_results: Vec::new(),
+        };
+
+        Ok(EvmEnv::new(ProofDb::new(db), header, commit))
+    }
+}
+
+impl<T, N, P, H> HostEvmEnv<AlloyDb<T, N, P>, H, ()>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N>,
+    H: EvmBlockHeader + TryFrom<<N as Network>::HeaderResponse> + Clone + Serialize,
     <H as TryFrom<<N as Network>::HeaderResponse>>::Error: Display,
 {
     /// Creates a new provable [EvmEnv] from an alloy [Provider].
     #[deprecated(since = "0.12.0", note = "use `EvmEnv::builder().provider()` instead")]
-    pub async fn from_provider(provider: P, number: BlockNumberOrTag) -> Result<Self> {
+    pub async fn from_provider(provider: P, number: BlockNumberOrTag) -> Result<Self, Error> {
         EvmEnv::builder()
             .provider(provider)
             .block_number_or_tag(number)
@@ -164,25 +274,114 @@ where
     }
 
     /// Converts the environment into a [EvmInput] committing to an execution block hash.
-    pub async fn into_input(self) -> Result<EvmInput<H>> {
-        let input = BlockInput::from_proof_db(self.db.unwrap(), self.header).await?;
+    pub async fn into_input(self) -> Result<EvmInput<H>, Error> {
+        let db = self.db.unwrap();
+        let threshold = db.inner().config().input_size_warning;
+        let input = BlockInput::from_proof_db(db, self.header)
+            .await
+            .map_err(Error::Proof)?;
 
-        Ok(EvmInput::Block(input))
+        Ok(EvmInput::Block(input).check_size(threshold))
+    }
+
+    /// Converts the environment into a [EvmInput] like `into_input` and also returns the
+    /// results of all preflighted calls, see [EvmEnv::call_results].
+    pub async fn into_input_with_results(mut self) -> Result<(EvmInput<H>, Vec<Bytes>), Error> {
+        let results = mem::take(&mut self.commit.call_results);
+        Ok((self.into_input().await?, results))
     }
 }
 
 impl<D, H: EvmBlockHeader, C> HostEvmEnv<D, H, C> {
+    /// Returns the slot of the Beacon block the environment commits to.
+    ///
+    /// This is only available for environments committing to a Beacon block root, i.e. created
+    /// with [EvmEnvBuilder::beacon_api], and can be used to log which Beacon block the proof will
+    /// be anchored to. Returns `None` otherwise.
+    pub fn beacon_slot(&self) -> Option<u64> {
+        self.commit.beacon_slot
+    }
+
+    /// Returns the ABI-encoded return data of all calls preflighted so far.
+    ///
+    /// The results of calls executed with a [CallBuilder], [MultiCall] or [PreflightBatch] are
+    /// recorded in the order of execution, one entry per call. As the guest executes the same
+    /// calls on the same state, these are exactly the results it will compute, so they can be
+    /// used to preview the expected journal before proving. Each entry can be decoded using the
+    /// `abi_decode_returns` function of the corresponding [SolCall].
+    ///
+    /// [CallBuilder]: crate::CallBuilder
+    /// [MultiCall]: crate::MultiCall
+    /// [SolCall]: alloy_sol_types::SolCall
+    pub fn call_results(&self) -> &[Bytes] {
+        &self.commit.call_results
+    }
+
+    /// Records the ABI-encoded return data of a preflighted call.
+    pub(crate) fn push_call_result(&mut self, output: Bytes) {
+        self.commit.call_results.push(output);
+    }
+
+    /// Returns a report of all accounts, storage slots and contracts accessed so far.
+    ///
+    /// After the preflight, this is exactly the state that is included in the [EvmInput] and
+    /// thus the state the proven computation depends on. It can be used by auditors to confirm
+    /// that a guest does not read any unexpected state, or to debug missing state in the guest.
+    pub fn access_report(&self) -> AccessReport
+    where
+        D: revm::Database,
+    {
+        self.db().access_report()
+    }
+
+    /// Creates a [PreflightBatch] to preflight calls to several contracts in a single step.
+    ///
+    /// All registered calls are executed together, so that the state they access is recorded in
+    /// this environment before it is converted into a single [EvmInput].
+    pub fn preflight_batch(&mut self) -> PreflightBatch<'_, D, H, C> {
+        PreflightBatch::new(self)
+    }
+
+    /// Sets the chain ID and specification ID from the given chain spec.
+    ///
+    /// This will panic when there is no valid specification ID for the current block. Use
+    /// [Self::try_with_chain_spec] to handle this case gracefully.
+    pub fn with_chain_spec(self, chain_spec: &ChainSpec) -> Self {
+        self.try_with_chain_spec(chain_spec).unwrap()
+    }
+
     /// Sets the chain ID and specification ID from the given chain spec.
     ///
-    /// This will panic when there is no valid specification ID for the current block.
-    pub fn with_chain_spec(mut self, chain_spec: &ChainSpec) -> Self {
+    /// Returns an error when there is no valid specification ID for the current block, e.g. when
+    /// the block precedes the first fork configured in the chain spec.
+    pub fn try_with_chain_spec(mut self, chain_spec: &ChainSpec) -> Result<Self> {
         self.cfg_env.chain_id = chain_spec.chain_id();
-        self.cfg_env.handler_cfg.spec_id = chain_spec
-            .active_fork(self.header.number(), self.header.timestamp())
-            .unwrap();
+        self.cfg_env.handler_cfg.spec_id =
+            chain_spec.active_fork(self.header.number(), self.header.timestamp())?;
         self.commit.config_id = chain_spec.digest();
 
-        self
+        Ok(self)
+    }
+
+    /// Returns the `configID` the environment commits to.
+    ///
+    /// This is the digest of the chain spec set with [EvmEnv::with_chain_spec], or the value set
+    /// with [EvmEnv::set_config_id].
+    pub fn config_id(&self) -> B256 {
+        self.commit.config_id
+    }
+
+    /// Sets the `configID` the environment commits to, without changing the chain ID or the
+    /// specification ID.
+    ///
+    /// **Advanced:** This is only meant for custom commitment schemes that derive the `configID`
+    /// from something other than a [ChainSpec]. In general, [EvmEnv::with_chain_spec] should be
+    /// used instead. It must be called after [EvmEnv::with_chain_spec], which overwrites the
+    /// `configID`, and the guest must set the same value, as the `configID` is not part of the
+    /// [EvmInput].
+    #[stability::unstable(feature = "config-id")]
+    pub fn set_config_id(&mut self, config_id: B256) {
+        self.commit.config_id = config_id;
     }
 }
 
@@ -192,13 +391,23 @@ where
     P: Provider<T, Ethereum>,
 {
     /// Converts the environment into a [EvmInput] committing to a Beacon Chain block root.
-    pub async fn into_input(self) -> Result<EvmInput<EthBlockHeader>> {
-        let input = BlockInput::from_proof_db(self.db.unwrap(), self.header).await?;
+    pub async fn into_input(self) -> Result<EvmInput<EthBlockHeader>, Error> {
+        let db = self.db.unwrap();
+        let threshold = db.inner().config().input_size_warning;
+        let input = BlockInput::from_proof_db(db, self.header)
+            .await
+            .map_err(Error::Proof)?;
+
+        Ok(EvmInput::Beacon(ComposeInput::new(input, self.commit.inner)).check_size(threshold))
+    }
 
-        Ok(EvmInput::Beacon(ComposeInput::new(
-            input,
-            self.commit.inner,
-        )))
+    /// Converts the environment into a [EvmInput] like `into_input` and also returns the
+    /// results of all preflighted calls, see [EvmEnv::call_results].
+    pub async fn into_input_with_results(
+        mut self,
+    ) -> Result<(EvmInput<EthBlockHeader>, Vec<Bytes>), Error> {
+        let results = mem::take(&mut self.commit.call_results);
+        Ok((self.into_input().await?, results))
     }
 }
 
@@ -210,13 +419,24 @@ where
     /// Converts the environment into a [EvmInput] recursively committing to multiple Beacon Chain
     /// block roots.
     #[stability::unstable(feature = "history")]
-    pub async fn into_input(self) -> Result<EvmInput<EthBlockHeader>> {
-        let input = BlockInput::from_proof_db(self.db.unwrap(), self.header).await?;
+    pub async fn into_input(self) -> Result<EvmInput<EthBlockHeader>, Error> {
+        let db = self.db.unwrap();
+        let threshold = db.inner().config().input_size_warning;
+        let input = BlockInput::from_proof_db(db, self.header)
+            .await
+            .map_err(Error::Proof)?;
 
-        Ok(EvmInput::History(ComposeInput::new(
-            input,
-            self.commit.inner,
-        )))
+        Ok(EvmInput::History(ComposeInput::new(input, self.commit.inner)).check_size(threshold))
+    }
+
+    /// Converts the environment into a [EvmInput] like `into_input` and also returns the
+    /// results of all preflighted calls, see [EvmEnv::call_results].
+    #[stability::unstable(feature = "history")]
+    pub async fn into_input_with_results(
+        mut self,
+    ) -> Result<(EvmInput<EthBlockHeader>, Vec<Bytes>), Error> {
+        let results = mem::take(&mut self.commit.call_results);
+        Ok((self.into_input().await?, results))
     }
 }
 
@@ -230,11 +450,462 @@ where
         since = "0.14.0",
         note = "use `EvmEnv::builder().beacon_api()` instead"
     )]
-    pub async fn into_beacon_input(self, url: Url) -> Result<EvmInput<EthBlockHeader>> {
+    pub async fn into_beacon_input(self, url: Url) -> Result<EvmInput<EthBlockHeader>, Error> {
+        let (commit, _) = BeaconCommit::from_header(
+            self.header(),
+            self.db().inner().provider(),
+            url,
+            &ProofCache::default(),
+        )
+        .await
+        .map_err(Error::Commitment)?;
+        let db = self.db.unwrap();
+        let threshold = db.inner().config().input_size_warning;
+        let input = BlockInput::from_proof_db(db, self.header)
+            .await
+            .map_err(Error::Proof)?;
+
+        Ok(EvmInput::Beacon(ComposeInput::new(input, commit)).check_size(threshold))
+    }
+}
+
+impl<H> EvmInput<H> {
+    /// Serializes the input into bytes.
+    ///
+    /// This allows running the preflight once and proving the input later without any RPC
+    /// access. The input is encoded as JSON of its serde representation, which contains the
+    /// [INPUT_FORMAT_VERSION], so that [EvmInput::from_bytes] rejects inputs of an incompatible
+    /// version with a descriptive error. It is independent of the format used to pass the input
+    /// into the zkVM.
+    ///
+    /// [INPUT_FORMAT_VERSION]: crate::INPUT_FORMAT_VERSION
+    pub fn to_bytes(&self) -> Result<Vec<u8>>
+    where
+        H: Serialize,
+    {
+        serde_json::to_vec(self).context("failed to serialize input")
+    }
+
+    /// Returns the size in bytes of the input as passed to the guest.
+    ///
+    /// This is the length of the encoding of `risc0_zkvm::serde::to_vec`, which is used when
+    /// writing the input to the `ExecutorEnv`. The size grows with the amount of accessed state.
+    /// It can be used to detect inputs that become too large to be proven efficiently, before the
+    /// proving fails, e.g. to split the computation using proof composition instead. See also
+    /// [EvmEnvBuilder::input_size_warning].
+    pub fn encoded_len(&self) -> usize
+    where
+        H: Serialize,
+    {
+        guest_serde::encoded_len(self).expect("failed to serialize input")
+    }
+
+    /// Returns the warning to log if the encoded input is larger than the given `threshold`.
+    fn size_warning(&self, threshold: Option<usize>) -> Option<String>
+    where
+        H: Serialize,
+    {
+        let threshold = threshold?;
+        let len = self.encoded_len();
+        (len > threshold).then(|| {
+            format!(
+                "input size of {} bytes exceeds the threshold of {} bytes: \
+                consider splitting the computation",
+                len, threshold
+            )
+        })
+    }
+
+    /// Logs a warning if the encoded input is larger than the given `threshold`.
+    fn check_size(self, threshold: Option<usize>) -> Self
+    where
+        H: Serialize,
+    {
+        if let Some(warning) = self.size_warning(threshold) {
+            log::warn!("{}", warning);
+        }
+        self
+    }
+
+    /// Deserializes an input from bytes created by [EvmInput::to_bytes].
+    ///
+    /// An error is returned if the input was serialized using a different [INPUT_FORMAT_VERSION].
+    ///
+    /// [INPUT_FORMAT_VERSION]: crate::INPUT_FORMAT_VERSION
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self>
+    where
+        H: DeserializeOwned,
+    {
+        serde_json::from_slice(bytes).context("failed to deserialize input")
+    }
+
+    /// Writes the input to the file at `path`, using the format of [EvmInput::to_bytes].
+    pub fn to_file(&self, path: impl AsRef<Path>) -> Result<()>
+    where
+        H: Serialize,
+    {
+        let path = path.as_ref();
+        fs::write(path, self.to_bytes()?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Reads an input from the file at `path` written by [EvmInput::to_file].
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self>
+    where
+        H: DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl<H: EvmBlockHeader + Clone> EvmInput<H> {
+    /// Returns the header of the execution block.
+    fn header(&self) -> &H {
+        match self {
+            EvmInput::Block(input) => input.header(),
+            EvmInput::Beacon(ComposeInput { input, .. }) => input.header(),
+            EvmInput::History(ComposeInput { input, .. }) => input.header(),
+        }
+    }
+
+    /// Returns the number of the execution block.
+    pub fn block_number(&self) -> u64 {
+        self.header().number()
+    }
+
+    /// Returns the hash of the execution block.
+    ///
+    /// Note that this is only part of the [Commitment] for inputs committing to an execution block
+    /// hash. Other inputs commit to a Beacon block root containing this block.
+    pub fn block_hash(&self) -> B256 {
+        self.header().hash_slow()
+    }
+
+    /// Verifies that the `journal` of a receipt starts with the [Commitment] of this input.
+    ///
+    /// The journal is expected to begin with the ABI-encoded [Commitment], e.g. as the first field
+    /// of a committed `sol!` struct. This allows a verifier to detect receipts that were produced
+    /// from a different input or for a different configuration before trusting their journal. The
+    /// expected `config_id` is the [ChainSpec::digest] of the chain spec used in the guest, or
+    /// [ChainSpec::spec_digest] if the guest uses [EvmEnv::bind_spec_id]. It does not verify the
+    /// receipt itself.
+    pub fn verify_journal(&self, journal: &[u8], config_id: B256) -> Result<()> {
+        ensure!(
+            journal.len() >= Commitment::ABI_ENCODED_SIZE,
+            "journal too short"
+        );
+        let commitment: Commitment =
+            SolValue::abi_decode(&journal[..Commitment::ABI_ENCODED_SIZE], true)
+                .context("journal does not start with a commitment")?;
+
+        ensure!(
+            commitment.configID == config_id,
+            "config ID mismatch: journal {}, expected {}",
+            commitment.configID,
+            config_id
+        );
+        let expected = self.commitment(config_id)?;
+        ensure!(
+            commitment == expected,
+            "commitment mismatch: journal {:?}, expected {:?}",
+            commitment,
+            expected
+        );
+
+        Ok(())
+    }
+
+    /// Computes the [Commitment] of this input without validating its state.
+    ///
+    /// This returns the same commitment as the guest committing to the given `config_id`, e.g.
+    /// [ChainSpec::digest] for `into_env().with_chain_spec(chain_spec)`, so that it can be
+    /// checked before spending time on proving. It returns an error if the commitment of a Beacon
+    /// or history input is invalid.
+    pub fn commitment(&self, config_id: B256) -> Result<Commitment> {
+        match self {
+            EvmInput::Block(_) => Ok(Commitment::new(
+                CommitmentVersion::Block as u16,
+                self.block_number(),
+                self.block_hash(),
+                config_id,
+            )),
+            EvmInput::Beacon(ComposeInput { input, commit }) => commit
+                .clone()
+                .try_commit(&input.header().clone().seal_slow(), config_id),
+            EvmInput::History(ComposeInput { input, commit }) => commit
+                .clone()
+                .try_commit(&input.header().clone().seal_slow(), config_id),
+        }
+    }
+}
+
+/// How a [Commitment] can be validated by an on-chain verifier, e.g. using `Steel.sol`.
+///
+/// See [Commitment::verification_strategy].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationStrategy {
+    /// The committed block hash can be validated using the `BLOCKHASH` opcode, which is limited
+    /// to the 256 most recent blocks.
+    BlockHash,
+    /// The committed Beacon block root can be validated using the EIP-4788 Beacon roots contract,
+    /// which stores the roots of roughly the last 27 hours.
+    BeaconRoots,
+    /// The commitment is too old to be validated. A new commitment to a recent block must be
+    /// created, e.g. a history commitment, which keeps the execution on the historical block.
+    History,
+    /// The committed block is not yet available to a verifier at the given block.
+    Pending,
+}
+
+impl Commitment {
+    /// The max number of blocks the `BLOCKHASH` opcode can look back.
+    const BLOCKHASH_WINDOW: u64 = 256;
+    /// The max age in seconds of a timestamp stored in the EIP-4788 Beacon roots contract.
+    const BEACON_ROOTS_WINDOW: u64 = 12 * 8191;
+
+    /// Returns how the commitment can be validated by a verifier executing at the `latest` block.
+    ///
+    /// This applies the same constraints as `Steel.validateCommitment`: Commitments to a block
+    /// hash are valid for 256 blocks, while commitments to a Beacon block root, including history
+    /// commitments, are valid for 8191 slots. Returns an error for an unknown commitment version.
+    pub fn verification_strategy<H: EvmBlockHeader>(
+        &self,
+        latest: &H,
+    ) -> Result<VerificationStrategy> {
+        let (id, version) = self.decode_id();
+        let id: u64 = id.try_into().ok().context("invalid commitment ID")?;
+        let strategy = match version {
+            v if v == CommitmentVersion::Block as u16 => match latest.number().checked_sub(id) {
+                None | Some(0) => VerificationStrategy::Pending,
+                Some(age) if age > Self::BLOCKHASH_WINDOW => VerificationStrategy::History,
+                Some(_) => VerificationStrategy::BlockHash,
+            },
+            v if v == CommitmentVersion::Beacon as u16 => {
+                match latest.timestamp().checked_sub(id) {
+                    None => VerificationStrategy::Pending,
+                    Some(age) if age > Self::BEACON_ROOTS_WINDOW => VerificationStrategy::History,
+                    Some(_) => VerificationStrategy::BeaconRoots,
+                }
+            }
+            v => bail!("unknown commitment version: {}", v),
+        };
+
+        Ok(strategy)
+    }
+
+    /// Verifies the commitment against a trusted `digest` and the expected `config_id`.
+    ///
+    /// This allows off-chain verifiers, e.g. indexers consuming Steel journals, to validate a
+    /// commitment without the Solidity verifier. The `digest` must be obtained from a trusted
+    /// source: For commitments to a block hash, it is the hash of the block with the number
+    /// encoded in the ID; for commitments to a Beacon block root, it is the parent Beacon block
+    /// root stored for the timestamp encoded in the ID, i.e. the `parentBeaconBlockRoot` of the
+    /// execution block with this timestamp. The expected `config_id` is the [ChainSpec::digest]
+    /// of the chain spec used in the guest, or [ChainSpec::spec_digest] if the guest uses
+    /// [EvmEnv::bind_spec_id]. Returns an error if the digest or the `configID` do not match, or
+    /// for an unknown commitment version.
+    pub fn verify_against(&self, digest: B256, config_id: B256) -> Result<()> {
+        let (_, version) = self.decode_id();
+        ensure!(
+            version == CommitmentVersion::Block as u16
+                || version == CommitmentVersion::Beacon as u16,
+            "unknown commitment version: {}",
+            version
+        );
+        ensure!(
+            self.digest == digest,
+            "commitment digest {} does not match the trusted digest {}",
+            self.digest,
+            digest
+        );
+        ensure!(
+            self.configID == config_id,
+            "commitment config ID {} does not match the expected {}",
+            self.configID,
+            config_id
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_number_or_tag_roundtrip() {
+        let tests = [
+            BlockNumberOrTag::Latest,
+            BlockNumberOrTag::Parent,
+            BlockNumberOrTag::Offset(0),
+            BlockNumberOrTag::Offset(32),
+            BlockNumberOrTag::Safe,
+            BlockNumberOrTag::Finalized,
+            BlockNumberOrTag::Earliest,
+            BlockNumberOrTag::Number(0),
+            BlockNumberOrTag::Number(u64::MAX),
+        ];
+        for block in tests {
+            assert_eq!(
+                block.to_string().parse::<BlockNumberOrTag>().unwrap(),
+                block
+            );
+        }
+    }
+
+    #[test]
+    fn block_number_or_tag_offset() {
+        assert_eq!(
+            "latest-32".parse::<BlockNumberOrTag>().unwrap(),
+            BlockNumberOrTag::Offset(32)
+        );
+        assert!("latest-".parse::<BlockNumberOrTag>().is_err());
+        assert!("latest-0x20".parse::<BlockNumberOrTag>().is_err());
+    }
+
+    #[test]
+    fn block_number_or_tag_number() {
+        assert_eq!(
+            "19000000".parse::<BlockNumberOrTag>().unwrap(),
+            BlockNumberOrTag::Number(19_000_000)
+        );
+        assert_eq!(
+            "0x121eac0".parse::<BlockNumberOrTag>().unwrap(),
+            BlockNumberOrTag::Number(19_000_000)
+        );
+        assert_eq!(
+            "0".parse::<BlockNumberOrTag>().unwrap(),
+            BlockNumberOrTag::Number(0)
+        );
+        assert_eq!(
+            "finalized".parse::<BlockNumberOrTag>().unwrap(),
+            BlockNumberOrTag::Finalized
+        );
+        // numbers that do not fit into a u64
+        assert!("18446744073709551616".parse::<BlockNumberOrTag>().is_err());
+        for invalid in ["", "0x", "-1", "+1", "1.0", "1e6", "0xzz", "latests", " 1"] {
+            assert!(
+                invalid.parse::<BlockNumberOrTag>().is_err(),
+                "{:?} should be invalid",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn block_number_or_tag_pending() {
+        let err = "pending".parse::<BlockNumberOrTag>().unwrap_err();
+        assert!(err.to_string().contains("pending"));
+    }
+
+    #[test]
+    fn verification_strategy() {
+        let latest = EthBlockHeader::new(alloy_consensus::Header {
+            number: 1000,
+            timestamp: 1_000_000,
+            ..Default::default()
+        });
+
+        let block = |number| {
+            Commitment::new(
+                CommitmentVersion::Block as u16,
+                number,
+                B256::ZERO,
+                B256::ZERO,
+            )
+        };
+        let strategy = |commit: Commitment| commit.verification_strategy(&latest).unwrap();
+        assert_eq!(strategy(block(999)), VerificationStrategy::BlockHash);
+        assert_eq!(strategy(block(744)), VerificationStrategy::BlockHash);
+        assert_eq!(strategy(block(743)), VerificationStrategy::History);
+        assert_eq!(strategy(block(1000)), VerificationStrategy::Pending);
+
+        let beacon = |timestamp| {
+            Commitment::new(
+                CommitmentVersion::Beacon as u16,
+                timestamp,
+                B256::ZERO,
+                B256::ZERO,
+            )
+        };
+        assert_eq!(
+            strategy(beacon(1_000_000)),
+            VerificationStrategy::BeaconRoots
+        );
+        assert_eq!(
+            strategy(beacon(1_000_000 - 12 * 8191)),
+            VerificationStrategy::BeaconRoots
+        );
+        assert_eq!(
+            strategy(beacon(1_000_000 - 12 * 8191 - 1)),
+            VerificationStrategy::History
+        );
+        assert_eq!(strategy(beacon(1_000_001)), VerificationStrategy::Pending);
+
+        let unknown = Commitment::new(u16::MAX, 0, B256::ZERO, B256::ZERO);
+        assert!(unknown.verification_strategy(&latest).is_err());
+    }
+
+    #[test]
+    fn verify_against() {
+        let config_id = crate::ethereum::ETH_MAINNET_CHAIN_SPEC.digest();
+        let digest = B256::repeat_byte(0x01);
         let commit =
-            BeaconCommit::from_header(self.header(), self.db().inner().provider(), url).await?;
-        let input = BlockInput::from_proof_db(self.db.unwrap(), self.header).await?;
+            |version: CommitmentVersion| Commitment::new(version as u16, 42, digest, config_id);
+
+        for version in [CommitmentVersion::Block, CommitmentVersion::Beacon] {
+            commit(version).verify_against(digest, config_id).unwrap();
+        }
+
+        let commitment = commit(CommitmentVersion::Block);
+        assert!(commitment.verify_against(B256::ZERO, config_id).is_err());
+        let other_config_id = crate::ethereum::ETH_SEPOLIA_CHAIN_SPEC.digest();
+        assert!(commitment.verify_against(digest, other_config_id).is_err());
+
+        let unknown = Commitment::new(u16::MAX, 42, digest, config_id);
+        assert!(unknown.verify_against(digest, config_id).is_err());
+    }
+
+    /// Returns an input for the default header without any state.
+    fn empty_input(beacon_commit: Option<BeaconCommit>) -> EvmInput<EthBlockHeader> {
+        crate::EvmInputParts {
+            header: crate::serde::RlpHeader::new(Default::default()),
+            state_trie: Default::default(),
+            storage_tries: vec![],
+            contracts: vec![],
+            ancestors: vec![],
+            beacon_commit,
+        }
+        .into()
+    }
+
+    #[test]
+    fn size_warning() {
+        let input = empty_input(None);
+        let len = input.encoded_len();
+        // the header alone is encoded as a length-prefixed RLP byte string
+        assert!(len > alloy_rlp::encode(input.header().inner()).len());
+
+        assert!(input.size_warning(None).is_none());
+        assert!(input.size_warning(Some(len)).is_none());
+        let warning = input.size_warning(Some(len - 1)).unwrap();
+        assert!(warning.contains(&format!("input size of {} bytes", len)));
+    }
+
+    #[test]
+    fn invalid_beacon_commitment() {
+        // an empty proof can never be valid for a leaf of the Beacon block
+        let input = empty_input(Some(BeaconCommit::new(vec![], 0)));
+        let config_id = crate::ethereum::ETH_MAINNET_CHAIN_SPEC.digest();
 
-        Ok(EvmInput::Beacon(ComposeInput::new(input, commit)))
+        let err = input.commitment(config_id).unwrap_err();
+        assert!(err.to_string().contains("invalid beacon inclusion proof"));
+        let journal = Commitment::new(CommitmentVersion::Beacon as u16, 0, B256::ZERO, config_id)
+            .abi_encode();
+        let err = input.verify_journal(&journal, config_id).unwrap_err();
+        assert!(err.to_string().contains("invalid beacon inclusion proof"));
     }
 }
@@ -16,16 +16,22 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 use ::serde::{Deserialize, Serialize};
-use alloy_primitives::{uint, BlockNumber, Sealable, Sealed, B256, U256};
+use alloy_primitives::{uint, BlockNumber, Bytes, Sealable, Sealed, B256, U256};
 use alloy_sol_types::SolValue;
+use anyhow::{bail, ensure};
+use beacon::BeaconCommit;
 use config::ChainSpec;
 use revm::primitives::{BlockEnv, CfgEnvWithHandlerCfg, SpecId};
 
+mod account;
+pub mod arbitrum;
 pub mod beacon;
 mod block;
 pub mod config;
 mod contract;
+pub mod contracts;
 pub mod ethereum;
+pub mod gnosis;
 #[cfg(feature = "unstable-history")]
 pub mod history;
 #[cfg(not(feature = "unstable-history"))]
@@ -34,12 +40,14 @@ mod history;
 pub mod host;
 mod merkle;
 mod mpt;
+pub mod polygon;
 pub mod serde;
 mod state;
 
+pub use account::Account;
 pub use beacon::BeaconInput;
 pub use block::BlockInput;
-pub use contract::{CallBuilder, Contract};
+pub use contract::{CallBuilder, CallError, CallEstimate, Contract, MultiCall};
 pub use mpt::MerkleTrie;
 pub use state::{StateAccount, StateDb};
 
@@ -49,8 +57,11 @@ pub use history::HistoryInput;
 pub(crate) use history::HistoryInput;
 
 /// The serializable input to derive and validate an [EvmEnv] from.
+///
+/// Its serde representation starts with the [INPUT_FORMAT_VERSION], so that deserializing an input
+/// created by an incompatible version of Steel, e.g. in the guest, fails with a descriptive error.
 #[non_exhaustive]
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
 pub enum EvmInput<H> {
     /// Input committing to the corresponding execution block hash.
     Block(BlockInput<H>),
@@ -60,6 +71,82 @@ pub enum EvmInput<H> {
     History(HistoryInput<H>),
 }
 
+/// The version of the serde representation of [EvmInput].
+///
+/// It must be incremented whenever the serde representation of [EvmInput] changes. Previous
+/// releases serialized the input without any version, i.e. starting with the index of the enum
+/// variant. Versions therefore start after the largest index, so that such inputs are rejected.
+pub const INPUT_FORMAT_VERSION: u32 = 3;
+
+/// The serde representation of [EvmInput] without its version.
+#[derive(Serialize)]
+enum EvmInputRef<'a, H> {
+    Block(&'a BlockInput<H>),
+    Beacon(&'a BeaconInput<H>),
+    History(&'a HistoryInput<H>),
+}
+
+/// The owned counterpart of [EvmInputRef] for deserialization.
+#[derive(Deserialize)]
+enum EvmInputOwned<H> {
+    Block(BlockInput<H>),
+    Beacon(BeaconInput<H>),
+    History(HistoryInput<H>),
+}
+
+impl<H: Serialize> Serialize for EvmInput<H> {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let input = match self {
+            EvmInput::Block(input) => EvmInputRef::Block(input),
+            EvmInput::Beacon(input) => EvmInputRef::Beacon(input),
+            EvmInput::History(input) => EvmInputRef::History(input),
+        };
+        (INPUT_FORMAT_VERSION, input).serialize(serializer)
+    }
+}
+
+impl<'de, H: Deserialize<'de>> Deserialize<'de> for EvmInput<H> {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use ::serde::de;
+
+        struct VersionedVisitor<H>(std::marker::PhantomData<H>);
+
+        impl<'de, H: Deserialize<'de>> de::Visitor<'de> for VersionedVisitor<H> {
+            type Value = EvmInput<H>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("an EvmInput tagged with its format version")
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let version: u32 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                // check the version first, as the rest cannot be decoded in a different format
+                if version != INPUT_FORMAT_VERSION {
+                    return Err(de::Error::custom(format_args!(
+                        "unsupported input format version {}: Steel v{} expects version {}",
+                        version,
+                        env!("CARGO_PKG_VERSION"),
+                        INPUT_FORMAT_VERSION
+                    )));
+                }
+                let input = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+                Ok(match input {
+                    EvmInputOwned::Block(input) => EvmInput::Block(input),
+                    EvmInputOwned::Beacon(input) => EvmInput::Beacon(input),
+                    EvmInputOwned::History(input) => EvmInput::History(input),
+                })
+            }
+        }
+
+        deserializer.deserialize_tuple(2, VersionedVisitor(std::marker::PhantomData))
+    }
+}
+
 impl<H: EvmBlockHeader> EvmInput<H> {
     /// Converts the input into a [EvmEnv] for execution.
     ///
@@ -72,12 +159,108 @@ impl<H: EvmBlockHeader> EvmInput<H> {
             EvmInput::History(input) => input.into_env(),
         }
     }
+
+    /// Verifies the internal consistency of the input without executing any calls.
+    ///
+    /// This performs the same state and ancestor checks as [EvmInput::into_env], but returns an
+    /// error instead of panicking. This allows checking an input from an untrusted source, e.g. a
+    /// cache, on the host before proving it. It does not verify the Beacon commitment.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            EvmInput::Block(input) => input.validate(),
+            EvmInput::Beacon(ComposeInput { input, .. }) => input.validate(),
+            EvmInput::History(ComposeInput { input, .. }) => input.validate(),
+        }
+    }
+
+    /// Merges another input for the same block and commitment into this input.
+    ///
+    /// This allows combining inputs, whose preflight has been performed separately, into a single
+    /// input. It returns an error if the inputs do not correspond to the same block and commitment.
+    pub fn merge(self, other: Self) -> anyhow::Result<Self>
+    where
+        H: Clone,
+    {
+        let merged = match (self, other) {
+            (EvmInput::Block(input), EvmInput::Block(other)) => {
+                EvmInput::Block(input.merge(other)?)
+            }
+            (EvmInput::Beacon(input), EvmInput::Beacon(other)) => {
+                EvmInput::Beacon(input.merge(other)?)
+            }
+            (EvmInput::History(input), EvmInput::History(other)) => {
+                EvmInput::History(input.merge(other)?)
+            }
+            _ => bail!("input type mismatch"),
+        };
+
+        Ok(merged)
+    }
+}
+
+/// The structured parts of an [EvmInput].
+///
+/// This provides a stable public representation of the input that can be used to implement custom
+/// (de)serialization, e.g. to store inputs in a different format. A [MerkleTrie] can be represented
+/// using [MerkleTrie::to_rlp_nodes] and [MerkleTrie::from_rlp_nodes]. The guest should still
+/// receive the [EvmInput] in its canonical serde format.
+#[derive(Clone)]
+pub struct EvmInputParts<H> {
+    /// Header of the execution block.
+    pub header: H,
+    /// Sparse state trie containing all accessed accounts.
+    pub state_trie: MerkleTrie,
+    /// Sparse storage tries of all accessed accounts.
+    pub storage_tries: Vec<MerkleTrie>,
+    /// Bytecode of all accessed contracts.
+    pub contracts: Vec<Bytes>,
+    /// Headers of the ancestors of the execution block, starting with its parent.
+    pub ancestors: Vec<H>,
+    /// Commitment to a Beacon block root, if the input is a [EvmInput::Beacon].
+    pub beacon_commit: Option<BeaconCommit>,
+}
+
+impl<H> From<EvmInputParts<H>> for EvmInput<H> {
+    fn from(mut parts: EvmInputParts<H>) -> Self {
+        match parts.beacon_commit.take() {
+            Some(commit) => EvmInput::Beacon(ComposeInput {
+                input: BlockInput::from_parts(parts),
+                commit,
+            }),
+            None => EvmInput::Block(BlockInput::from_parts(parts)),
+        }
+    }
+}
+
+impl<H> TryFrom<EvmInput<H>> for EvmInputParts<H> {
+    type Error = anyhow::Error;
+
+    fn try_from(input: EvmInput<H>) -> Result<Self, Self::Error> {
+        match input {
+            EvmInput::Block(input) => Ok(input.into_parts()),
+            EvmInput::Beacon(ComposeInput { input, commit }) => Ok(EvmInputParts {
+                beacon_commit: Some(commit),
+                ..input.into_parts()
+            }),
+            EvmInput::History(_) => bail!("history input cannot be converted into parts"),
+        }
+    }
 }
 
 /// A trait linking the block header to a commitment.
 pub trait BlockHeaderCommit<H: EvmBlockHeader> {
     /// Creates a verifiable [Commitment] of the `header`.
     fn commit(self, header: &Sealed<H>, config_id: B256) -> Commitment;
+
+    /// Creates a verifiable [Commitment] of the `header`, returning an error if it is invalid.
+    ///
+    /// The default implementation calls [BlockHeaderCommit::commit] and therefore panics instead.
+    fn try_commit(self, header: &Sealed<H>, config_id: B256) -> anyhow::Result<Commitment>
+    where
+        Self: Sized,
+    {
+        Ok(self.commit(header, config_id))
+    }
 }
 
 /// A generalized input type consisting of a block-based input and a commitment wrapper.
@@ -110,6 +293,30 @@ impl<H: EvmBlockHeader, C: BlockHeaderCommit<H>> ComposeInput<H, C> {
     }
 }
 
+impl<H: EvmBlockHeader + Clone, C: BlockHeaderCommit<H> + Clone> ComposeInput<H, C> {
+    /// Merges the state of another composed input with the same commitment into this input.
+    ///
+    /// It returns an error if the inputs do not correspond to the same block and commitment or if
+    /// the commitment of either input is invalid.
+    pub fn merge(self, other: Self) -> anyhow::Result<Self> {
+        ensure!(
+            self.input.header().hash_slow() == other.input.header().hash_slow(),
+            "block hash mismatch"
+        );
+        let header = self.input.header().clone().seal_slow();
+        ensure!(
+            self.commit.clone().try_commit(&header, B256::ZERO)?
+                == other.commit.try_commit(&header, B256::ZERO)?,
+            "commitment mismatch"
+        );
+
+        Ok(Self {
+            input: self.input.merge(other.input)?,
+            commit: self.commit,
+        })
+    }
+}
+
 /// Alias for readability, do not make public.
 pub(crate) type GuestEvmEnv<H> = EvmEnv<StateDb, H, Commitment>;
 
@@ -119,6 +326,8 @@ pub struct EvmEnv<D, H, C> {
     cfg_env: CfgEnvWithHandlerCfg,
     header: Sealed<H>,
     commit: C,
+    /// Whether the [SpecId] is bound to the `configID`, see [EvmEnv::bind_spec_id].
+    spec_bound: bool,
 }
 
 impl<D, H: EvmBlockHeader, C> EvmEnv<D, H, C> {
@@ -133,15 +342,60 @@ impl<D, H: EvmBlockHeader, C> EvmEnv<D, H, C> {
             cfg_env,
             header,
             commit,
+            spec_bound: false,
         }
     }
 
     /// Returns the sealed header of the environment.
+    ///
+    /// In the guest, this is the header of the execution block, whose hash is verified as part of
+    /// the [Commitment]. Its values, like the number, timestamp or base fee, can therefore be used
+    /// as proven values, e.g. for time-based calculations, instead of passing them as untrusted
+    /// input. For Ethereum, all fields of the header can be accessed directly, e.g.
+    /// `env.header().withdrawals_root` or `env.header().blob_gas_used`.
     #[inline]
     pub fn header(&self) -> &Sealed<H> {
         &self.header
     }
 
+    /// Returns the EVM block environment derived from the header.
+    ///
+    /// This contains the values exposed to contracts by the block opcodes, like the base fee, the
+    /// gas limit, the blob base fee or `prevrandao`, exactly as used for the execution of calls.
+    /// As they are derived from the header, they are bound to the [Commitment] and can be
+    /// committed, e.g. in the journal, as proven values, independent of the type of the header.
+    pub fn block_env(&self) -> BlockEnv {
+        let mut blk_env = BlockEnv::default();
+        self.header.inner().fill_block_env(&mut blk_env);
+        blk_env
+    }
+
+    /// Returns the [SpecId] used for the execution.
+    ///
+    /// This is derived from the [ChainSpec] passed to `with_chain_spec` and the header of the
+    /// block. If no chain spec has been set, [SpecId::LATEST] is used.
+    #[inline]
+    pub fn spec_id(&self) -> SpecId {
+        self.cfg_env.handler_cfg.spec_id
+    }
+
+    /// Asserts that the fork `spec_id` is active for the execution, i.e. that the [SpecId] of
+    /// the environment is at least `spec_id`.
+    ///
+    /// As the [SpecId] is derived from the chain spec, whose digest is part of the [Commitment],
+    /// this can be used to ensure that a guest relying on certain features (e.g. EIP-4844) does
+    /// not produce a valid proof for a block where these features are not available. It panics
+    /// if the fork is not active.
+    #[inline]
+    pub fn assert_fork_at_least(&self, spec_id: SpecId) {
+        assert!(
+            SpecId::enabled(self.spec_id(), spec_id),
+            "Fork {:?} is not active: {:?}",
+            spec_id,
+            self.spec_id()
+        );
+    }
+
     fn db(&self) -> &D {
         // safe unwrap: self cannot be borrowed without a DB
         self.db.as_ref().unwrap()
@@ -157,17 +411,112 @@ impl<D, H: EvmBlockHeader, C> EvmEnv<D, H, C> {
 impl<D, H: EvmBlockHeader> EvmEnv<D, H, Commitment> {
     /// Sets the chain ID and specification ID from the given chain spec.
     ///
-    /// This will panic when there is no valid specification ID for the current block.
-    pub fn with_chain_spec(mut self, chain_spec: &ChainSpec) -> Self {
+    /// This will panic when there is no valid specification ID for the current block. Use
+    /// [Self::try_with_chain_spec] to handle this case gracefully.
+    pub fn with_chain_spec(self, chain_spec: &ChainSpec) -> Self {
+        self.try_with_chain_spec(chain_spec).unwrap()
+    }
+
+    /// Sets the chain ID and specification ID from the given chain spec.
+    ///
+    /// Returns an error when there is no valid specification ID for the current block, e.g. when
+    /// the block precedes the first fork configured in the chain spec.
+    pub fn try_with_chain_spec(mut self, chain_spec: &ChainSpec) -> anyhow::Result<Self> {
         self.cfg_env.chain_id = chain_spec.chain_id();
-        self.cfg_env.handler_cfg.spec_id = chain_spec
-            .active_fork(self.header.number(), self.header.timestamp())
-            .unwrap();
+        self.cfg_env.handler_cfg.spec_id =
+            chain_spec.active_fork(self.header.number(), self.header.timestamp())?;
         self.commit.configID = chain_spec.digest();
+        self.spec_bound = false;
+
+        Ok(self)
+    }
+
+    /// Asserts that the environment has been configured with the given chain spec.
+    ///
+    /// The digest of the chain spec is committed as the `configID` of the [Commitment], so that
+    /// the computation is only valid for that chain spec. As an environment without an explicit
+    /// [EvmEnv::with_chain_spec] commits to [ChainSpec::DEFAULT_DIGEST], this can be used in the
+    /// guest to ensure that the expected configuration is actually used. If the [SpecId] is bound
+    /// using [EvmEnv::bind_spec_id], the `configID` is compared to [ChainSpec::spec_digest]
+    /// instead. It panics if the chain spec does not match.
+    ///
+    /// This does not replace the check by the verifier: The verifier must still confirm that the
+    /// `configID` of the committed [Commitment] matches the expected value, which can be computed
+    /// on the host and hard-coded in a verifier contract using
+    /// `Steel.validateCommitment(commitment, configID)`. This is e.g.
+    /// `ETH_MAINNET_CHAIN_SPEC.digest()`, or `ETH_MAINNET_CHAIN_SPEC.spec_digest(SpecId::CANCUN)`
+    /// for a guest binding the [SpecId] of a Cancun block.
+    #[inline]
+    pub fn assert_chain_spec(&self, chain_spec: &ChainSpec) {
+        let config_id = if self.spec_bound {
+            chain_spec.spec_digest(self.spec_id())
+        } else {
+            chain_spec.digest()
+        };
+        assert_eq!(self.commit.configID, config_id, "Chain spec does not match");
+        assert_eq!(
+            self.cfg_env.chain_id,
+            chain_spec.chain_id(),
+            "Chain ID does not match"
+        );
+    }
+
+    /// Sets the `configID` of the [Commitment], without changing the chain ID or the
+    /// specification ID.
+    ///
+    /// **Advanced:** This is only meant for custom commitment schemes that derive the `configID`
+    /// from something other than a [ChainSpec]. In general, [EvmEnv::with_chain_spec] should be
+    /// used instead. It must be called after [EvmEnv::with_chain_spec], which overwrites the
+    /// `configID`.
+    #[stability::unstable(feature = "config-id")]
+    pub fn set_config_id(&mut self, config_id: B256) {
+        self.commit.configID = config_id;
+        self.spec_bound = false;
+    }
 
+    /// Binds the [SpecId] used for the execution to the `configID` of the [Commitment].
+    ///
+    /// By default, the `configID` only commits to the chain spec, so that environments of blocks
+    /// before and after a hard fork commit to the same `configID`. After calling this method, the
+    /// `configID` is [ChainSpec::spec_digest] of the resolved [SpecId] instead. This allows a
+    /// verifier, e.g. of several proofs linked using proof composition, to confirm that all steps
+    /// were executed using the same fork rules with [EvmEnv::assert_same_config].
+    ///
+    /// It must be called after [EvmEnv::with_chain_spec], which resets the `configID`. Calling it
+    /// again has no effect, as the [SpecId] is only bound once.
+    pub fn bind_spec_id(mut self) -> Self {
+        if !self.spec_bound {
+            self.commit.configID = config::bind_spec_id(self.commit.configID, self.spec_id());
+            self.spec_bound = true;
+        }
         self
     }
 
+    /// Asserts that the environment commits to the same `configID` as the given [Commitment].
+    ///
+    /// This can be used in the guest to check that the commitment of a previous, linked proof
+    /// was created with the same configuration. If both environments use [EvmEnv::bind_spec_id],
+    /// this includes the [SpecId] used for the execution. It panics if the `configID` does not
+    /// match.
+    #[inline]
+    pub fn assert_same_config(&self, other: &Commitment) {
+        assert_eq!(
+            self.commit.configID, other.configID,
+            "Config ID does not match"
+        );
+    }
+
+    /// Returns the verified Beacon block root, if the environment commits to a Beacon block root.
+    ///
+    /// For inputs such as [EvmInput::Beacon], this is the root that the EIP-4788 beacon roots
+    /// contract returns for the timestamp encoded in the [Commitment]. For inputs committing to an
+    /// execution block hash, `None` is returned.
+    #[inline]
+    pub fn beacon_block_root(&self) -> Option<B256> {
+        let (_, version) = self.commit.decode_id();
+        (version == CommitmentVersion::Beacon as u16).then_some(self.commit.digest)
+    }
+
     /// Returns the [Commitment] used to validate the environment.
     #[inline]
     pub fn commitment(&self) -> &Commitment {
@@ -259,6 +608,39 @@ impl Commitment {
         (decoded, version)
     }
 
+    /// Checks that this commitment refers to a later block than the `earlier` commitment.
+    ///
+    /// This is useful when linking the commitments of several composed proofs into a time series,
+    /// e.g. in the guest, to detect journals that are out of order or refer to the same block.
+    /// Both commitments must have the same version and `configID`, as only then their IDs, i.e.
+    /// the block number or the Beacon block timestamp, are comparable. For commitments using
+    /// [EvmEnv::bind_spec_id], this also requires both blocks to be executed with the same
+    /// [SpecId]. The ID of this commitment must then be strictly greater than the ID of `earlier`.
+    pub fn ensure_after(&self, earlier: &Commitment) -> anyhow::Result<()> {
+        let (id, version) = self.decode_id();
+        let (earlier_id, earlier_version) = earlier.decode_id();
+        ensure!(
+            version == earlier_version,
+            "commitment versions differ: {} and {}",
+            earlier_version,
+            version
+        );
+        ensure!(
+            self.configID == earlier.configID,
+            "commitment config IDs differ: {} and {}",
+            earlier.configID,
+            self.configID
+        );
+        ensure!(
+            id > earlier_id,
+            "commitment ID {} is not after {}",
+            id,
+            earlier_id
+        );
+
+        Ok(())
+    }
+
     /// ABI-encodes the commitment.
     #[inline]
     pub fn abi_encode(&self) -> Vec<u8> {
@@ -286,6 +668,7 @@ impl std::fmt::Debug for Commitment {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ForkCondition;
     use alloy_primitives::B256;
 
     #[test]
@@ -304,6 +687,204 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ensure_after() {
+        let config_id = B256::repeat_byte(0x01);
+        let commitment = |version: CommitmentVersion, id: u64| {
+            Commitment::new(version as u16, id, B256::ZERO, config_id)
+        };
+
+        let earlier = commitment(CommitmentVersion::Block, 10);
+        commitment(CommitmentVersion::Block, 11)
+            .ensure_after(&earlier)
+            .unwrap();
+        // the same block or an earlier block must be rejected
+        assert!(earlier.ensure_after(&earlier).is_err());
+        assert!(commitment(CommitmentVersion::Block, 9)
+            .ensure_after(&earlier)
+            .is_err());
+        // block numbers and timestamps are not comparable
+        assert!(commitment(CommitmentVersion::Beacon, 11)
+            .ensure_after(&earlier)
+            .is_err());
+        // commitments of different chains are not comparable
+        let other_chain = Commitment::new(0, 11, B256::ZERO, B256::ZERO);
+        assert!(other_chain.ensure_after(&earlier).is_err());
+    }
+
+    #[test]
+    fn block_env() {
+        let header = alloy_consensus::Header {
+            number: 42,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(7),
+            ..Default::default()
+        };
+        let header = crate::serde::RlpHeader::new(header).seal_slow();
+        let env = EvmEnv::new((), header, Commitment::default());
+
+        let blk_env = env.block_env();
+        assert_eq!(blk_env.number, U256::from(42));
+        assert_eq!(blk_env.gas_limit, U256::from(30_000_000));
+        assert_eq!(blk_env.basefee, U256::from(7));
+    }
+
+    fn shanghai_env() -> EvmEnv<(), crate::ethereum::EthBlockHeader, Commitment> {
+        let header = crate::serde::RlpHeader::new(alloy_consensus::Header::default()).seal_slow();
+        EvmEnv::new((), header, Commitment::default())
+            .with_chain_spec(&ChainSpec::new_single(1, SpecId::SHANGHAI))
+    }
+
+    #[test]
+    fn try_with_chain_spec() {
+        let header = crate::serde::RlpHeader::new(alloy_consensus::Header::default()).seal_slow();
+        let spec = ChainSpec {
+            chain_id: 1,
+            forks: [(SpecId::CANCUN, ForkCondition::Timestamp(1))].into(),
+        };
+        let err = EvmEnv::new((), header, Commitment::default())
+            .try_with_chain_spec(&spec)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "no supported fork of chain 1 for block 0 with timestamp 0"
+        );
+    }
+
+    #[test]
+    fn assert_fork_at_least() {
+        let env = shanghai_env();
+        assert_eq!(env.spec_id(), SpecId::SHANGHAI);
+        env.assert_fork_at_least(SpecId::MERGE);
+        env.assert_fork_at_least(SpecId::SHANGHAI);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not active")]
+    fn assert_fork_at_least_inactive() {
+        shanghai_env().assert_fork_at_least(SpecId::CANCUN);
+    }
+
+    #[test]
+    fn assert_chain_spec() {
+        shanghai_env().assert_chain_spec(&ChainSpec::new_single(1, SpecId::SHANGHAI));
+    }
+
+    #[test]
+    #[should_panic(expected = "Chain spec does not match")]
+    fn assert_chain_spec_mismatch() {
+        shanghai_env().assert_chain_spec(&ChainSpec::new_single(1, SpecId::CANCUN));
+    }
+
+    /// Returns two environments of the same chain spec executed before and after Cancun.
+    fn fork_envs() -> (
+        ChainSpec,
+        [EvmEnv<(), crate::ethereum::EthBlockHeader, Commitment>; 2],
+    ) {
+        let spec = ChainSpec {
+            chain_id: 1,
+            forks: [
+                (SpecId::SHANGHAI, ForkCondition::Timestamp(0)),
+                (SpecId::CANCUN, ForkCondition::Timestamp(10)),
+            ]
+            .into(),
+        };
+        let envs = [0, 10].map(|timestamp| {
+            let header = crate::serde::RlpHeader::new(alloy_consensus::Header {
+                timestamp,
+                ..Default::default()
+            })
+            .seal_slow();
+            EvmEnv::new((), header, Commitment::default()).with_chain_spec(&spec)
+        });
+
+        (spec, envs)
+    }
+
+    #[test]
+    fn bind_spec_id() {
+        let (spec, [shanghai, cancun]) = fork_envs();
+        // without binding the spec, both environments commit to the same config
+        shanghai.assert_same_config(cancun.commitment());
+
+        let shanghai = shanghai.bind_spec_id();
+        assert_eq!(
+            shanghai.commitment().configID,
+            spec.spec_digest(SpecId::SHANGHAI)
+        );
+        let cancun = cancun.bind_spec_id();
+        assert_eq!(
+            cancun.commitment().configID,
+            spec.spec_digest(SpecId::CANCUN)
+        );
+        // binding again must not change the configID
+        let cancun = cancun.bind_spec_id();
+        assert_eq!(
+            cancun.commitment().configID,
+            spec.spec_digest(SpecId::CANCUN)
+        );
+        cancun.assert_chain_spec(&spec);
+
+        // setting the chain spec again resets the binding
+        let shanghai = shanghai.with_chain_spec(&spec);
+        assert_eq!(shanghai.commitment().configID, spec.digest());
+        shanghai.assert_chain_spec(&spec);
+    }
+
+    #[test]
+    #[should_panic(expected = "Chain spec does not match")]
+    fn assert_chain_spec_bound_mismatch() {
+        let (_, [shanghai, _]) = fork_envs();
+        shanghai
+            .bind_spec_id()
+            .assert_chain_spec(&ChainSpec::new_single(1, SpecId::SHANGHAI));
+    }
+
+    #[test]
+    fn ensure_after_bound() {
+        let (_, [shanghai, cancun]) = fork_envs();
+        let cancun = cancun.bind_spec_id().into_commitment();
+        let later = Commitment::new(0, 1, B256::ZERO, cancun.configID);
+        later.ensure_after(&cancun).unwrap();
+        // commitments executed with different forks are not comparable
+        let shanghai = shanghai.bind_spec_id().into_commitment();
+        assert!(later.ensure_after(&shanghai).is_err());
+    }
+
+    #[test]
+    fn set_config_id() {
+        let (spec, [mut env, _]) = fork_envs();
+        let config_id = B256::repeat_byte(0x42);
+        env.set_config_id(config_id);
+        assert_eq!(env.commitment().configID, config_id);
+        // the chain spec still determines the execution
+        assert_eq!(env.spec_id(), SpecId::SHANGHAI);
+        assert_eq!(env.cfg_env.chain_id, spec.chain_id());
+    }
+
+    #[test]
+    #[should_panic(expected = "Config ID does not match")]
+    fn assert_same_config_spec_mismatch() {
+        let (_, [shanghai, cancun]) = fork_envs();
+        let previous = shanghai.bind_spec_id().into_commitment();
+        cancun.bind_spec_id().assert_same_config(&previous);
+    }
+
+    #[test]
+    fn beacon_block_root() {
+        let root = B256::repeat_byte(0x01);
+        let header = crate::serde::RlpHeader::new(alloy_consensus::Header::default()).seal_slow();
+
+        let commit = Commitment::new(CommitmentVersion::Beacon as u16, 1, root, B256::ZERO);
+        let env = EvmEnv::new((), header.clone(), commit);
+        assert_eq!(env.beacon_block_root(), Some(root));
+
+        let commit = Commitment::new(CommitmentVersion::Block as u16, 1, root, B256::ZERO);
+        let env = EvmEnv::new((), header, commit);
+        assert_eq!(env.beacon_block_root(), None);
+    }
+
     #[test]
     fn versioned_id() {
         let tests = vec![(u64::MAX, u16::MAX), (u64::MAX, 0), (0, u16::MAX), (0, 0)];
@@ -313,4 +894,32 @@ mod tests {
             assert_eq!((id.to(), version), test);
         }
     }
+
+    #[test]
+    fn input_format_version() {
+        let input: EvmInput<crate::ethereum::EthBlockHeader> = EvmInputParts {
+            header: crate::serde::RlpHeader::new(alloy_consensus::Header::default()),
+            state_trie: MerkleTrie::default(),
+            storage_tries: vec![],
+            contracts: vec![],
+            ancestors: vec![],
+            beacon_commit: None,
+        }
+        .into();
+        let bytes = bincode::serialize(&input).unwrap();
+        assert_eq!(bytes[..4], INPUT_FORMAT_VERSION.to_le_bytes());
+        let decoded: EvmInput<crate::ethereum::EthBlockHeader> =
+            bincode::deserialize(&bytes).unwrap();
+        assert_eq!(bincode::serialize(&decoded).unwrap(), bytes);
+
+        // an unversioned input starts with the variant index, e.g. 1 for a Beacon input
+        let mut unversioned = bytes;
+        unversioned[..4].copy_from_slice(&1u32.to_le_bytes());
+        let err = bincode::deserialize::<EvmInput<crate::ethereum::EthBlockHeader>>(&unversioned)
+            .err()
+            .unwrap();
+        assert!(err
+            .to_string()
+            .contains("unsupported input format version 1"));
+    }
 }
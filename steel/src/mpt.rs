@@ -14,7 +14,7 @@
 
 use std::fmt::Debug;
 
-use alloy_primitives::{b256, keccak256, map::B256HashMap, B256};
+use alloy_primitives::{b256, keccak256, map::B256HashMap, Bytes, B256};
 use alloy_rlp::{BufMut, Decodable, Encodable, Header, PayloadView, EMPTY_STRING_CODE};
 use nybbles::Nibbles;
 use serde::{Deserialize, Serialize};
@@ -48,6 +48,25 @@ impl MerkleTrie {
         }
     }
 
+    /// Returns whether the inclusion or exclusion of the key can be guaranteed.
+    ///
+    /// If this returns `true`, [MerkleTrie::get] will not panic for this key.
+    #[inline]
+    pub fn is_resolved(&self, key: impl AsRef<[u8]>) -> bool {
+        self.0
+            .unresolved_digest(Nibbles::unpack(key).as_slice())
+            .is_none()
+    }
+
+    /// Returns the digest of the first unresolved node on the path of the key, if any.
+    ///
+    /// This is the node that is missing to guarantee the inclusion or exclusion of the key.
+    #[cfg(feature = "host")]
+    #[inline]
+    pub(crate) fn unresolved_digest(&self, key: impl AsRef<[u8]>) -> Option<B256> {
+        self.0.unresolved_digest(Nibbles::unpack(key).as_slice())
+    }
+
     /// Returns the number of full nodes in the trie.
     ///
     /// A full node is a node that needs to be fully encoded to compute the root hash.
@@ -67,6 +86,41 @@ impl MerkleTrie {
         }
     }
 
+    /// Returns the RLP-encoded nodes of the trie, starting with the root node.
+    ///
+    /// Nodes that are shorter than 32 bytes are embedded in their parent node and therefore not
+    /// returned separately. This is the inverse of [MerkleTrie::from_rlp_nodes].
+    pub fn to_rlp_nodes(&self) -> Vec<Bytes> {
+        let mut nodes = vec![self.0.rlp_encoded().into()];
+        self.0.children_rlp_nodes(&mut nodes);
+        nodes
+    }
+
+    /// Merges two sparse representations of the same trie.
+    ///
+    /// The resulting trie contains the resolved nodes of both tries. It panics when the root hashes
+    /// of the two tries do not match.
+    pub fn merge(self, other: MerkleTrie) -> MerkleTrie {
+        assert_eq!(
+            self.hash_slow(),
+            other.hash_slow(),
+            "MPT: Root hash mismatch"
+        );
+        MerkleTrie(self.0.merge(other.0))
+    }
+
+    /// Creates a new trie that only consists of the given root hash.
+    ///
+    /// Such a trie can be used to commit to a root without resolving any of its keys.
+    #[inline]
+    pub fn from_digest(digest: B256) -> Self {
+        if digest == EMPTY_ROOT_HASH {
+            MerkleTrie(Node::Null)
+        } else {
+            MerkleTrie(Node::Digest(digest))
+        }
+    }
+
     /// Creates a new trie from the given RLP encoded nodes.
     ///
     /// The first node provided must always be the root node. The remaining nodes can be in any
@@ -130,6 +184,69 @@ impl Node {
         }
     }
 
+    /// Returns the digest of the first unresolved node on the path of the key, if any.
+    /// If this returns `None`, the inclusion or exclusion of the key can be shown in the sparse
+    /// trie.
+    fn unresolved_digest(&self, key_nibs: &[u8]) -> Option<B256> {
+        match self {
+            Node::Null | Node::Leaf(..) => None,
+            Node::Extension(prefix, child) => key_nibs
+                .strip_prefix(prefix.as_slice())
+                .and_then(|remaining| child.unresolved_digest(remaining)),
+            Node::Branch(children) => match key_nibs.split_first() {
+                Some((idx, remaining)) => children[*idx as usize]
+                    .as_deref()
+                    .and_then(|node| node.unresolved_digest(remaining)),
+                None => None,
+            },
+            Node::Digest(digest) => Some(*digest),
+        }
+    }
+
+    /// Appends the RLP encoding of all descendants of this node that are referenced by hash.
+    fn children_rlp_nodes(&self, out: &mut Vec<Bytes>) {
+        let mut push = |child: &Node| {
+            if !matches!(child, Node::Digest(_)) {
+                let rlp = child.rlp_encoded();
+                if rlp.len() >= 32 {
+                    out.push(rlp.into());
+                }
+                child.children_rlp_nodes(out);
+            }
+        };
+        match self {
+            Node::Null | Node::Leaf(..) | Node::Digest(_) => {}
+            Node::Extension(_, child) => push(child.as_ref()),
+            Node::Branch(children) => children
+                .iter()
+                .flatten()
+                .for_each(|child| push(child.as_ref())),
+        }
+    }
+
+    /// Merges the nodes of two sparse tries with the same hash, replacing unresolved digests.
+    fn merge(self, other: Node) -> Node {
+        match (self, other) {
+            (Node::Digest(_), node) | (node, Node::Digest(_)) => node,
+            (Node::Extension(prefix, child), Node::Extension(_, other_child)) => {
+                Node::Extension(prefix, Box::new(child.merge(*other_child)))
+            }
+            (Node::Branch(mut children), Node::Branch(other_children)) => {
+                for (child, other_child) in children.iter_mut().zip(other_children) {
+                    if let Some(other_child) = other_child {
+                        *child = Some(match child.take() {
+                            Some(node) => Box::new(node.merge(*other_child)),
+                            None => other_child,
+                        });
+                    }
+                }
+                Node::Branch(children)
+            }
+            // nodes with the same hash are identical, if they are fully resolved
+            (node, _) => node,
+        }
+    }
+
     /// Returns the number of full nodes in the trie.
     /// A full node is a node that needs to be fully encoded to compute the root hash.
     fn size(&self) -> usize {
@@ -404,6 +521,12 @@ mod tests {
 
         assert_eq!(mpt.hash_slow(), B256::ZERO);
         assert_eq!(mpt.size(), 0);
+
+        assert_eq!(MerkleTrie::from_digest(B256::ZERO), mpt);
+        assert_eq!(
+            MerkleTrie::from_digest(EMPTY_ROOT_HASH),
+            MerkleTrie(Node::Null)
+        );
     }
 
     #[test]
@@ -495,6 +618,42 @@ mod tests {
         mpt.get([]);
     }
 
+    #[test]
+    pub fn is_resolved() {
+        let mut children: [Option<Box<Node>>; 16] = Default::default();
+        children[0] = Some(Box::new(Node::Digest(B256::ZERO)));
+        children[1] = Some(Box::new(Node::Leaf(
+            Nibbles::from_nibbles([1; 63]),
+            vec![1].into(),
+        )));
+        let mpt = MerkleTrie(Node::Branch(children));
+
+        assert!(!mpt.is_resolved(B256::repeat_byte(0x00)));
+        assert!(mpt.is_resolved(B256::repeat_byte(0x11)));
+        assert!(mpt.is_resolved(B256::repeat_byte(0x22)));
+        assert!(MerkleTrie::default().is_resolved(B256::ZERO));
+    }
+
+    #[test]
+    #[cfg(feature = "host")]
+    pub fn unresolved_digest() {
+        let digest = B256::repeat_byte(0xff);
+        let mut children: [Option<Box<Node>>; 16] = Default::default();
+        children[0] = Some(Box::new(Node::Extension(
+            Nibbles::from_nibbles([0]),
+            Box::new(Node::Digest(digest)),
+        )));
+        let mpt = MerkleTrie(Node::Branch(children));
+
+        assert_eq!(mpt.unresolved_digest(B256::repeat_byte(0x00)), Some(digest));
+        assert_eq!(mpt.unresolved_digest(B256::repeat_byte(0x01)), None);
+        assert_eq!(mpt.unresolved_digest(B256::repeat_byte(0x11)), None);
+        assert_eq!(
+            MerkleTrie::from_digest(digest).unresolved_digest(B256::ZERO),
+            Some(digest)
+        );
+    }
+
     #[test]
     pub fn mpt_short() {
         // 4 leaves with 1-byte long keys, the resulting root node should be shorter than 32 bytes
@@ -550,6 +709,56 @@ mod tests {
         )
         .unwrap();
         assert_eq!(mpt.hash_slow(), exp_hash);
+
+        // converting the trie back into RLP nodes must not change it
+        assert_eq!(MerkleTrie::from_rlp_nodes(mpt.to_rlp_nodes()).unwrap(), mpt);
+    }
+
+    #[test]
+    pub fn merge_sparse_mpt() {
+        const NUM_LEAVES: usize = 64;
+
+        let leaves: BTreeMap<_, _> = (0..NUM_LEAVES)
+            .map(|i| {
+                let key = U256::from(i);
+                (
+                    Nibbles::unpack(keccak256(key.to_be_bytes::<32>())),
+                    alloy_rlp::encode(key),
+                )
+            })
+            .collect();
+
+        // creates a sparse trie only containing the proofs for the given keys
+        let sparse_mpt = |proof_keys: Vec<Nibbles>| {
+            let mut hasher =
+                HashBuilder::default().with_proof_retainer(proof_keys.into_iter().collect());
+            leaves
+                .iter()
+                .for_each(|(k, v)| hasher.add_leaf(k.clone(), v));
+            hasher.root();
+            MerkleTrie::from_rlp_nodes(
+                hasher
+                    .take_proof_nodes()
+                    .into_nodes_sorted()
+                    .into_iter()
+                    .map(|node| node.1),
+            )
+            .unwrap()
+        };
+
+        let even = sparse_mpt(leaves.keys().step_by(2).cloned().collect());
+        let odd = sparse_mpt(leaves.keys().skip(1).step_by(2).cloned().collect());
+        let all = sparse_mpt(leaves.keys().cloned().collect());
+
+        let merged = even.merge(odd);
+        assert_eq!(merged, all);
+        for i in 0..NUM_LEAVES {
+            let key = U256::from(i);
+            assert_eq!(
+                merged.get(keccak256(key.to_be_bytes::<32>())),
+                Some(&alloy_rlp::encode(key)[..])
+            );
+        }
     }
 
     #[test]
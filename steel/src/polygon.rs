@@ -0,0 +1,225 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Type aliases and specifications for Polygon PoS.
+//!
+//! Polygon PoS blocks are sealed by the Bor consensus engine, which uses the same block header
+//! format as Ethereum, so the block hash can be computed as usual. However, some fields have a
+//! different meaning: The `extraData` contains the signature of the block producer, the `mixHash`
+//! is always zero, and the `difficulty` reflects the position of the producer in the current
+//! span. As Polygon PoS never went through the Merge, the `DIFFICULTY` opcode still returns the
+//! difficulty of the header instead of the `mixHash`. The [PolygonBlockHeader] takes care of
+//! this difference.
+//!
+//! **Note:** The following features are not supported or only approximated:
+//! - Polygon PoS does not support EIP-4844 blobs, so `BLOBHASH` and `BLOBBASEFEE` always return
+//!   zero, even though the Cancun rules are active since the Napoli hard fork.
+//! - Hard forks after Napoli are not part of the [POLYGON_MAINNET_CHAIN_SPEC], so more recent
+//!   blocks are executed using the Cancun rules.
+//! - The state sync transactions and Bor system contracts are not considered, as they do not
+//!   affect the state of a block that has already been produced.
+use std::{collections::BTreeMap, ops::Deref, sync::LazyLock};
+
+use crate::{
+    config::{ChainSpec, ForkCondition},
+    serde::RlpHeader,
+    EvmBlockHeader, EvmEnv, EvmInput,
+};
+use alloy_primitives::{BlockNumber, B256, U256};
+use alloy_rlp::{BufMut, Decodable, Encodable};
+use revm::primitives::{BlockEnv, SpecId};
+
+/// The Polygon PoS mainnet [ChainSpec].
+///
+/// Bor does not distinguish between the Merge and Shanghai, so both are activated with the
+/// Shanghai hard fork.
+pub static POLYGON_MAINNET_CHAIN_SPEC: LazyLock<ChainSpec> = LazyLock::new(|| ChainSpec {
+    chain_id: 137,
+    forks: BTreeMap::from([
+        (SpecId::PETERSBURG, ForkCondition::Block(0)),
+        (SpecId::ISTANBUL, ForkCondition::Block(3395000)),
+        (SpecId::BERLIN, ForkCondition::Block(14750000)),
+        (SpecId::LONDON, ForkCondition::Block(23850000)),
+        (SpecId::SHANGHAI, ForkCondition::Block(50523000)),
+        // the Napoli hard fork
+        (SpecId::CANCUN, ForkCondition::Block(54876000)),
+    ]),
+});
+
+/// [EvmEnv] for Polygon PoS.
+pub type PolygonEvmEnv<D, C> = EvmEnv<D, PolygonBlockHeader, C>;
+
+/// [EvmInput] for Polygon PoS.
+pub type PolygonEvmInput = EvmInput<PolygonBlockHeader>;
+
+/// [EvmBlockHeader] for Polygon PoS.
+pub type PolygonBlockHeader = RlpHeader<PolygonHeader>;
+
+/// A Polygon PoS block header sealed by Bor.
+///
+/// This is an Ethereum header, whose `difficulty` is returned by the `DIFFICULTY` opcode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolygonHeader(alloy_consensus::Header);
+
+impl PolygonHeader {
+    /// Returns the underlying Ethereum header.
+    #[inline]
+    pub fn inner(&self) -> &alloy_consensus::Header {
+        &self.0
+    }
+}
+
+impl Deref for PolygonHeader {
+    type Target = alloy_consensus::Header;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<alloy_consensus::Header> for PolygonHeader {
+    #[inline]
+    fn from(header: alloy_consensus::Header) -> Self {
+        PolygonHeader(header)
+    }
+}
+
+impl Encodable for PolygonHeader {
+    #[inline]
+    fn encode(&self, out: &mut dyn BufMut) {
+        self.0.encode(out)
+    }
+
+    #[inline]
+    fn length(&self) -> usize {
+        self.0.length()
+    }
+}
+
+impl Decodable for PolygonHeader {
+    #[inline]
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        alloy_consensus::Header::decode(buf).map(PolygonHeader)
+    }
+}
+
+impl EvmBlockHeader for PolygonBlockHeader {
+    #[inline]
+    fn parent_hash(&self) -> &B256 {
+        &self.inner().parent_hash
+    }
+    #[inline]
+    fn number(&self) -> BlockNumber {
+        self.inner().number
+    }
+    #[inline]
+    fn timestamp(&self) -> u64 {
+        self.inner().timestamp
+    }
+    #[inline]
+    fn state_root(&self) -> &B256 {
+        &self.inner().state_root
+    }
+
+    #[inline]
+    fn fill_block_env(&self, blk_env: &mut BlockEnv) {
+        let header = self.inner();
+
+        blk_env.number = U256::from(header.number);
+        blk_env.coinbase = header.beneficiary;
+        blk_env.timestamp = U256::from(header.timestamp);
+        blk_env.gas_limit = U256::from(header.gas_limit);
+        blk_env.basefee = U256::from(header.base_fee_per_gas.unwrap_or_default());
+        blk_env.difficulty = header.difficulty;
+        // revm uses PREVRANDAO for the DIFFICULTY opcode after the Merge, but Bor still returns
+        // the difficulty
+        blk_env.prevrandao = Some(header.difficulty.into());
+        if let Some(excess_blob_gas) = header.excess_blob_gas {
+            blk_env.set_blob_excess_gas_and_price(excess_blob_gas)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn active_fork() {
+        let spec = &*POLYGON_MAINNET_CHAIN_SPEC;
+
+        assert_eq!(spec.active_fork(0, 0).unwrap(), SpecId::PETERSBURG);
+        assert_eq!(spec.active_fork(23850000, 0).unwrap(), SpecId::LONDON);
+        assert_eq!(spec.active_fork(50523000, 0).unwrap(), SpecId::SHANGHAI);
+        assert_eq!(spec.active_fork(54876000, 0).unwrap(), SpecId::CANCUN);
+    }
+
+    #[test]
+    fn block_env() {
+        let header = PolygonBlockHeader::new(
+            alloy_consensus::Header {
+                number: 60000000,
+                difficulty: U256::from(22),
+                ..Default::default()
+            }
+            .into(),
+        );
+
+        let mut blk_env = BlockEnv::default();
+        header.fill_block_env(&mut blk_env);
+        assert_eq!(blk_env.difficulty, U256::from(22));
+        assert_eq!(blk_env.prevrandao, Some(U256::from(22).into()));
+    }
+
+    #[tokio::test]
+    #[ignore = "queries actual RPC nodes"]
+    async fn view_call() {
+        use crate::Contract;
+        use alloy::providers::ProviderBuilder;
+
+        alloy_sol_types::sol! {
+            interface IERC20 {
+                function totalSupply() external view returns (uint256);
+            }
+        }
+        // native USDC on Polygon PoS
+        let usdc = address!("3c499c542cEF5E3811e1192ce70d8cC03d5c3359");
+        let provider = ProviderBuilder::new().on_http("https://polygon-rpc.com".parse().unwrap());
+
+        let mut env = PolygonEvmEnv::builder()
+            .provider(provider)
+            .build()
+            .await
+            .unwrap()
+            .with_chain_spec(&POLYGON_MAINNET_CHAIN_SPEC);
+        let block_hash = env.header().seal();
+        let preflight = Contract::preflight(usdc, &mut env)
+            .call_builder(&IERC20::totalSupplyCall {})
+            .call()
+            .await
+            .unwrap();
+        let input = env.into_input().await.unwrap();
+
+        let env = input
+            .into_env()
+            .with_chain_spec(&POLYGON_MAINNET_CHAIN_SPEC);
+        let result = Contract::new(usdc, &env)
+            .call_builder(&IERC20::totalSupplyCall {})
+            .call();
+        assert_eq!(result._0, preflight._0);
+        assert_eq!(env.commitment().digest, block_hash);
+    }
+}
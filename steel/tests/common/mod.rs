@@ -85,7 +85,9 @@ pub struct CallOptions {
     from: Option<Address>,
     gas: Option<u64>,
     gas_price: Option<U256>,
+    value: Option<U256>,
     access_list: Option<AccessList>,
+    blob_base_fee: Option<u128>,
 }
 
 #[allow(dead_code)]
@@ -111,6 +113,12 @@ impl CallOptions {
             ..Default::default()
         }
     }
+    pub fn with_value(value: U256) -> Self {
+        Self {
+            value: Some(value),
+            ..Default::default()
+        }
+    }
     pub fn with_access_list(access_list: AccessList) -> Self {
         Self {
             access_list: Some(access_list),
@@ -118,6 +126,13 @@ impl CallOptions {
         }
     }
 
+    pub fn with_blob_base_fee(blob_base_fee: u128) -> Self {
+        Self {
+            blob_base_fee: Some(blob_base_fee),
+            ..Default::default()
+        }
+    }
+
     fn apply<E, C>(&self, mut builder: CallBuilder<E, C>) -> CallBuilder<E, C> {
         if let Some(from) = self.from {
             builder = builder.from(from);
@@ -128,6 +143,12 @@ impl CallOptions {
         if let Some(gas_price) = self.gas_price {
             builder = builder.gas_price(gas_price);
         }
+        if let Some(value) = self.value {
+            builder = builder.value(value);
+        }
+        if let Some(blob_base_fee) = self.blob_base_fee {
+            builder = builder.blob_base_fee_override(blob_base_fee);
+        }
         builder
     }
 }
@@ -107,7 +107,7 @@ async fn anvil_input() -> anyhow::Result<EthEvmInput> {
         .call()
         .await?;
 
-    env.into_input().await
+    Ok(env.into_input().await?)
 }
 
 /// Executes `Pair.a()` and `Pair.b()` on the input just as the guest would.
@@ -137,7 +137,7 @@ async fn rpc_usdt_beacon_input() -> anyhow::Result<EthEvmInput> {
         .call()
         .await?;
 
-    env.into_input().await
+    Ok(env.into_input().await?)
 }
 
 /// Loads the data from an existing JSON file, or creates it.
@@ -160,7 +160,13 @@ async fn load_or_create<'a, T: serde::ser::Serialize + serde::de::DeserializeOwn
 
 /// Returns the JSON representation of the inner `BlockInput`.
 fn get_block_input_mut(input: &mut Value) -> &mut Value {
-    let (key, value) = input.as_object_mut().unwrap().into_iter().next().unwrap();
+    // the first element is the format version
+    let (key, value) = input[1]
+        .as_object_mut()
+        .unwrap()
+        .into_iter()
+        .next()
+        .unwrap();
     match key.as_str() {
         "Block" => value,
         "Beacon" => &mut value["input"],
@@ -387,7 +393,7 @@ async fn corrupt_beacon_proof() {
 
     // get the JSON representation of the block header for the state
     let mut input_value = to_value(&input).unwrap();
-    let proof_value = &mut input_value["Beacon"]["commit"]["proof"];
+    let proof_value = &mut input_value[1]["Beacon"]["commit"]["proof"];
 
     // corrupt the first element in the Merkle path to something non-zero
     proof_value[0] = to_value(B256::with_last_byte(0x01)).unwrap();
@@ -409,7 +415,7 @@ async fn corrupt_beacon_proof_length() {
 
     // get the JSON representation of the block header for the state
     let mut input_value = to_value(&input).unwrap();
-    let proof_value = &mut input_value["Beacon"]["commit"]["proof"];
+    let proof_value = &mut input_value[1]["Beacon"]["commit"]["proof"];
 
     // corrupt the proof by appending a new value
     let proof = proof_value.as_array_mut().unwrap();
@@ -439,7 +445,7 @@ mod history {
             .call()
             .await?;
 
-        env.into_input().await
+        Ok(env.into_input().await?)
     }
 
     #[test(tokio::test)]
@@ -454,7 +460,7 @@ mod history {
 
         // get the JSON representation of the block header for the state
         let mut input_value = to_value(&input).unwrap();
-        let state_commit = &mut input_value["History"]["commit"]["state_commits"][0];
+        let state_commit = &mut input_value[1]["History"]["commit"]["state_commits"][0];
         let proof_value = &mut state_commit["state_commit"]["proof"];
 
         // corrupt the first element in the Merkle path to something non-zero
@@ -478,7 +484,7 @@ mod history {
 
         // get the JSON representation of the block header for the state
         let mut input_value = to_value(&input).unwrap();
-        let state_commit = &mut input_value["History"]["commit"]["state_commits"][0];
+        let state_commit = &mut input_value[1]["History"]["commit"]["state_commits"][0];
         let state_trie_value = &mut state_commit["state"]["state_trie"];
 
         // corrupt the trie by getting the first child node and deleting it
@@ -503,7 +509,7 @@ mod history {
 
         // get the JSON representation of the block header for the state
         let mut input_value = to_value(&input).unwrap();
-        let state_commit = &mut input_value["History"]["commit"]["state_commits"][0];
+        let state_commit = &mut input_value[1]["History"]["commit"]["state_commits"][0];
         let storage_trie_value = &mut state_commit["state"]["storage_trie"];
 
         // corrupt the trie by getting the first child node and deleting it
@@ -526,7 +532,7 @@ mod history {
 
         // get the JSON representation of the block header for the state
         let mut input_value = to_value(&input).unwrap();
-        let evm_commit = &mut input_value["History"]["commit"]["evm_commit"];
+        let evm_commit = &mut input_value[1]["History"]["commit"]["evm_commit"];
 
         // corrupt the EVM commit by changing the first element in the proof to something non-zero
         let proof_value = &mut evm_commit["proof"];
@@ -547,7 +553,7 @@ mod history {
 
         // get the JSON representation of the block header for the state
         let mut input_value = to_value(&input).unwrap();
-        let evm_commit = &mut input_value["History"]["commit"]["evm_commit"];
+        let evm_commit = &mut input_value[1]["History"]["commit"]["evm_commit"];
 
         // corrupt the EVM commit by changing its timestamp
         let timestamp_value = &mut evm_commit["timestamp"];
@@ -0,0 +1,106 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests preflighting and executing the Governor helpers against a deployed Governor contract.
+#![cfg(feature = "host")]
+
+use crate::common::ANVIL_CHAIN_SPEC;
+use alloy::{
+    providers::{ext::AnvilApi, ProviderBuilder},
+    uint,
+};
+use alloy_primitives::{address, bytes, Address, Bytes, U256};
+use risc0_steel::{
+    contracts::governor::{Governor, ProposalState},
+    ethereum::EthEvmEnv,
+};
+use test_log::test;
+
+#[allow(dead_code)]
+mod common;
+
+const GOVERNOR_ADDRESS: Address = address!("00000000000000000000000000000000000000F1");
+
+/// Runtime bytecode of a mock Governor that answers the view calls from storage:
+/// - `state(uint256)` returns slot 0
+/// - `proposalSnapshot(uint256)` returns slot 1
+/// - `proposalVotes(uint256)` returns slots 2, 3 and 4
+/// - `quorum(uint256 timepoint)` returns the slot `timepoint`
+///
+/// The proposal ID is ignored, so the mock only tracks a single proposal.
+const GOVERNOR_BYTECODE: Bytes = bytes!("60003560e01c80633e4f49e6146100365780632d63f69314610042578063544ffc9c1461004e578063f8ce560a1461006657600080fd5b60005460005260206000f35b60015460005260206000f35b60025460005260035460205260045460405260606000f35b6004355460005260206000f3");
+
+const PROPOSAL_ID: U256 = uint!(42_U256);
+const SNAPSHOT: U256 = uint!(100_U256);
+
+#[test(tokio::test)]
+async fn governor_proposal() {
+    let provider =
+        ProviderBuilder::new().on_anvil_with_config(|anvil| anvil.args(["--hardfork", "cancun"]));
+    let node_info = provider.anvil_node_info().await.unwrap();
+    log::info!("Anvil started: {:?}", node_info);
+
+    provider
+        .anvil_set_code(GOVERNOR_ADDRESS, GOVERNOR_BYTECODE)
+        .await
+        .unwrap();
+    let storage = [
+        (uint!(0_U256), U256::from(ProposalState::Succeeded as u8)),
+        (uint!(1_U256), SNAPSHOT),
+        (uint!(2_U256), uint!(70_U256)), // against votes
+        (uint!(3_U256), uint!(30_U256)), // for votes
+        (uint!(4_U256), uint!(20_U256)), // abstain votes
+        (SNAPSHOT, uint!(50_U256)),      // quorum at the snapshot
+    ];
+    for (slot, value) in storage {
+        provider
+            .anvil_set_storage_at(GOVERNOR_ADDRESS, slot, value.into())
+            .await
+            .unwrap();
+    }
+    // mine a new block containing those state changes
+    provider.evm_mine(None).await.unwrap();
+
+    let mut env = EthEvmEnv::builder()
+        .provider(provider)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+
+    // preflight all calls on the host
+    let mut governor = Governor::preflight(GOVERNOR_ADDRESS, &mut env);
+    let state = governor.state(PROPOSAL_ID).await.unwrap();
+    assert_eq!(state, ProposalState::Succeeded);
+    let votes = governor.proposal_votes(PROPOSAL_ID).await.unwrap();
+    assert_eq!(votes.againstVotes, uint!(70_U256));
+    assert_eq!(votes.forVotes, uint!(30_U256));
+    assert_eq!(votes.abstainVotes, uint!(20_U256));
+    let quorum = governor.quorum(SNAPSHOT).await.unwrap();
+    assert_eq!(quorum, uint!(50_U256));
+    // both the for and the abstain votes count towards the quorum
+    assert!(governor.reached_quorum(PROPOSAL_ID).await.unwrap());
+
+    // the very same calls must succeed in the guest
+    let input = env.into_input().await.unwrap();
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let governor = Governor::new(GOVERNOR_ADDRESS, &env);
+    assert_eq!(governor.state(PROPOSAL_ID), state);
+    let guest_votes = governor.proposal_votes(PROPOSAL_ID);
+    assert_eq!(guest_votes.againstVotes, votes.againstVotes);
+    assert_eq!(guest_votes.forVotes, votes.forVotes);
+    assert_eq!(guest_votes.abstainVotes, votes.abstainVotes);
+    assert_eq!(governor.quorum(SNAPSHOT), quorum);
+    assert!(governor.reached_quorum(PROPOSAL_ID));
+}
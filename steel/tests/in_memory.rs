@@ -0,0 +1,179 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests executing calls against an explicitly supplied state without any RPC.
+#![cfg(feature = "host")]
+
+use crate::common::ANVIL_CHAIN_SPEC;
+use alloy_primitives::{address, bytes, Address, Bytes, U256};
+use risc0_steel::{
+    ethereum::{EthBlockHeader, EthEvmInput},
+    host::db::InMemoryDb,
+    Account, BlockInput, CallError, Contract, MultiCall,
+};
+
+#[allow(dead_code)]
+mod common;
+
+const CONTRACT: Address = address!("000000000000000000000000000000000000c0de");
+const ACCOUNT: Address = address!("0000000000000000000000000000000000000042");
+
+/// Runtime bytecode returning the value of storage slot `0`.
+const SLOAD_CODE: Bytes = bytes!("60005460005260206000f3");
+
+/// Runtime bytecode reverting with `Error("boom")`.
+const REVERT_CODE: Bytes =
+    bytes!("6308c379a060e01b6000526020600452600460245263626f6f6d60e01b60445260646000fd");
+
+alloy::sol!(
+    // the runtime bytecode ignores the calldata, so any function can be called to get the word
+    #[derive(Debug, PartialEq, Eq)]
+    interface IWord {
+        function word() external view returns (uint256);
+    }
+);
+
+fn test_input() -> EthEvmInput {
+    let mut db = InMemoryDb::new();
+    db.set_code(CONTRACT, SLOAD_CODE);
+    db.set_storage(CONTRACT, U256::ZERO, U256::from(42));
+    db.set_balance(ACCOUNT, U256::from(1000));
+    db.set_nonce(ACCOUNT, 1);
+
+    let header = EthBlockHeader::new(alloy_consensus::Header {
+        number: 1,
+        gas_limit: 30_000_000,
+        state_root: db.state_root(),
+        ..Default::default()
+    });
+    db.into_input(header).unwrap()
+}
+
+fn revert_input() -> EthEvmInput {
+    let mut db = InMemoryDb::new();
+    db.set_code(CONTRACT, REVERT_CODE);
+    let header = EthBlockHeader::new(alloy_consensus::Header {
+        number: 1,
+        gas_limit: 30_000_000,
+        state_root: db.state_root(),
+        ..Default::default()
+    });
+    db.into_input(header).unwrap()
+}
+
+#[test]
+fn call() {
+    let env = test_input().into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let result = Contract::new(CONTRACT, &env)
+        .call_builder(&IWord::wordCall {})
+        .call();
+    assert_eq!(result._0, U256::from(42));
+}
+
+#[test]
+fn account() {
+    let env = test_input().into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let account = Account::new(ACCOUNT, &env);
+    assert_eq!(account.balance(), U256::from(1000));
+    assert_eq!(account.nonce(), 1);
+}
+
+#[test]
+fn state_root_mismatch() {
+    let mut db = InMemoryDb::new();
+    db.set_balance(ACCOUNT, U256::from(1000));
+    let header = EthBlockHeader::new(alloy_consensus::Header::default());
+    assert!(
+        db.into_input(header).is_err(),
+        "header without the state root should fail"
+    );
+}
+
+#[test]
+fn block_input_from_state() {
+    let mut db = InMemoryDb::new();
+    db.set_balance(ACCOUNT, U256::from(1000));
+    let header = EthBlockHeader::new(alloy_consensus::Header {
+        number: 1,
+        gas_limit: 30_000_000,
+        state_root: db.state_root(),
+        ..Default::default()
+    });
+
+    let input = BlockInput::from_state(header, db).unwrap();
+    let env = EthEvmInput::Block(input)
+        .into_env()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    assert_eq!(Account::new(ACCOUNT, &env).balance(), U256::from(1000));
+}
+
+#[test]
+fn out_of_gas() {
+    let env = test_input().into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let err = Contract::new(CONTRACT, &env)
+        .call_builder(&IWord::wordCall {})
+        .gas(21_100)
+        .try_call()
+        .expect_err("call should run out of gas");
+    assert_eq!(err, CallError::OutOfGas);
+}
+
+#[test]
+fn revert_reason() {
+    let env = revert_input().into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let err = Contract::new(CONTRACT, &env)
+        .call_builder(&IWord::wordCall {})
+        .try_call()
+        .expect_err("call should revert");
+    assert!(err.revert_data().is_some());
+    assert_eq!(err.to_string(), "reverted: boom");
+}
+
+#[test]
+fn multi_call_revert_reason() {
+    let env = revert_input().into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let err = MultiCall::new(&env, [(CONTRACT, IWord::wordCall {})])
+        .try_call()
+        .expect_err("call should revert");
+    assert_eq!(err.to_string(), "reverted: boom");
+}
+
+#[test]
+fn bound_commitment() {
+    let input = test_input();
+    let env = input
+        .clone()
+        .into_env()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC)
+        .bind_spec_id();
+    env.assert_chain_spec(&ANVIL_CHAIN_SPEC);
+    let config_id = ANVIL_CHAIN_SPEC.spec_digest(env.spec_id());
+    let commitment = env.into_commitment();
+    assert_eq!(commitment.configID, config_id);
+
+    // the host helpers accept the bound configID
+    assert_eq!(input.commitment(config_id).unwrap(), commitment);
+    let journal = commitment.abi_encode();
+    input.verify_journal(&journal, config_id).unwrap();
+    commitment
+        .verify_against(input.block_hash(), config_id)
+        .unwrap();
+
+    // but reject the unbound digest of the chain spec
+    let unbound = ANVIL_CHAIN_SPEC.digest();
+    assert!(input.verify_journal(&journal, unbound).is_err());
+    assert!(commitment
+        .verify_against(input.block_hash(), unbound)
+        .is_err());
+}
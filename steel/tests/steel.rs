@@ -14,20 +14,60 @@
 
 #![cfg(feature = "host")]
 
-use std::fmt::Debug;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
 
 use alloy::{
-    providers::{ext::AnvilApi, Provider, ProviderBuilder},
-    rpc::types::TransactionRequest,
-    transports::http::{Client, Http},
+    eips::{
+        eip2930::{AccessList, AccessListItem},
+        eip4844::calc_blob_gasprice,
+        BlockNumberOrTag,
+    },
+    network::{primitives::BlockTransactionsKind, Ethereum, Network},
+    providers::{
+        ext::AnvilApi, Provider, ProviderBuilder, ProviderLayer, RootProvider, RpcWithBlock,
+    },
+    rpc::{
+        client::RpcClient,
+        types::{EIP1186AccountProofResponse, TransactionRequest},
+    },
+    transports::{
+        http::{Client, Http},
+        Transport,
+    },
     uint,
 };
-use alloy_primitives::{address, b256, bytes, hex, Address, Bytes, U256};
+use alloy_primitives::{
+    address, b256, bytes, hex, keccak256, Address, Bytes, Sealable, StorageKey, B256, U256,
+};
 use alloy_sol_types::SolCall;
 use common::{CallOptions, ANVIL_CHAIN_SPEC};
-use risc0_steel::{ethereum::EthEvmEnv, Contract};
+use revm::{
+    interpreter::{opcode, Interpreter},
+    primitives::{SpecId, KECCAK_EMPTY},
+};
+use risc0_steel::{
+    config::{ChainSpec, ForkCondition},
+    ethereum::{EthBlockHeader, EthEvmEnv, EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
+    host::{
+        self,
+        db::{AlloyDb, ProofProgress},
+        Error, EvmEnvBuilder,
+    },
+    Account, Contract, EvmInputParts, MultiCall, INPUT_FORMAT_VERSION,
+};
 use sha2::{Digest, Sha256};
 use test_log::test;
+use url::Url;
 
 mod common;
 
@@ -116,7 +156,7 @@ alloy::sol!(
 );
 
 /// Returns an Anvil provider with the deployed [SteelTest] contract.
-async fn test_provider() -> impl Provider<Http<Client>> {
+async fn test_provider() -> impl Provider<Http<Client>> + Clone {
     let provider = ProviderBuilder::new()
         .with_recommended_fillers()
         .on_anvil_with_wallet_and_config(|anvil| anvil.args(["--hardfork", "cancun"]));
@@ -128,6 +168,61 @@ async fn test_provider() -> impl Provider<Http<Client>> {
     provider
 }
 
+/// Preflights `SteelTest.testMuliContractCalls` with the given builder and creates the input.
+///
+/// This is the common fixture of all tests of builder options that affect the input.
+async fn multi_contract_calls_input<T, P>(
+    builder: EvmEnvBuilder<P, EthBlockHeader, ()>,
+) -> Result<EthEvmInput, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + 'static,
+{
+    let mut env = builder.build().await?.with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let result = Contract::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .call_builder(&SteelTest::testMuliContractCallsCall {})
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result._0, uint!(84_U256));
+
+    env.into_input().await
+}
+
+/// Address used to deploy minimal runtime bytecode that returns a single word.
+const WORD_CONTRACT: Address = address!("000000000000000000000000000000000000c0de");
+alloy::sol!(
+    // the runtime bytecode ignores the calldata, so any function can be called to get the word
+    #[derive(Debug, PartialEq, Eq)]
+    interface IWord {
+        function word() external view returns (uint256);
+    }
+);
+
+alloy::sol!(
+    // used with runtime bytecode that returns two words, ignoring the calldata
+    #[derive(Debug, PartialEq, Eq)]
+    interface IPair {
+        struct Pair {
+            uint256 value;
+            address owner;
+        }
+
+        function pair() external view returns (uint256 value, address owner);
+        function pairStruct() external view returns (Pair memory pair);
+    }
+);
+
+/// Returns an Anvil provider with the given runtime bytecode deployed at [WORD_CONTRACT].
+async fn word_provider(code: Bytes) -> impl Provider<Http<Client>> + Clone {
+    let provider = test_provider().await;
+    provider.anvil_set_code(WORD_CONTRACT, code).await.unwrap();
+    // mine a new block containing the new code
+    provider.evm_mine(None).await.unwrap();
+
+    provider
+}
+
 #[test(tokio::test)]
 async fn ec_recover() {
     let result = common::eth_call(
@@ -258,6 +353,56 @@ async fn origin() {
     assert_eq!(result._0, from);
 }
 
+#[test(tokio::test)]
+async fn caller() {
+    let from = address!("0000000000000000000000000000000000000042");
+    let result = common::eth_call(
+        // CALLER PUSH0 MSTORE PUSH1 0x20 PUSH0 RETURN
+        word_provider(bytes!("335f5260205ff3")).await,
+        WORD_CONTRACT,
+        IWord::wordCall {},
+        CallOptions::with_from(from),
+    )
+    .await;
+    assert_eq!(result._0, U256::from_be_slice(from.as_slice()));
+}
+
+#[test(tokio::test)]
+async fn call_cache() {
+    let (a, b) = (Address::repeat_byte(0x0a), Address::repeat_byte(0x0b));
+    // CALLER PUSH0 MSTORE PUSH1 0x20 PUSH0 RETURN
+    let mut env = EthEvmEnv::builder()
+        .provider(word_provider(bytes!("335f5260205ff3")).await)
+        .chain_spec(&ANVIL_CHAIN_SPEC)
+        .build()
+        .await
+        .unwrap();
+    let mut contract = Contract::preflight(WORD_CONTRACT, &mut env);
+    for from in [a, b] {
+        contract
+            .call_builder(&IWord::wordCall {})
+            .from(from)
+            .call()
+            .await
+            .unwrap();
+    }
+    let input = env.into_input().await.unwrap();
+
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let contract = Contract::new(WORD_CONTRACT, &env).with_call_cache();
+    let call = |from| {
+        contract
+            .call_builder(&IWord::wordCall {})
+            .from(from)
+            .call()
+            ._0
+    };
+    assert_eq!(call(a), U256::from_be_slice(a.as_slice()));
+    assert_eq!(call(a), U256::from_be_slice(a.as_slice()));
+    // calls with different parameters must not share the cached result
+    assert_eq!(call(b), U256::from_be_slice(b.as_slice()));
+}
+
 #[test(tokio::test)]
 async fn gasprice() {
     let gas_price = uint!(42_U256);
@@ -271,6 +416,102 @@ async fn gasprice() {
     assert_eq!(result._0, gas_price);
 }
 
+#[test(tokio::test)]
+async fn callvalue() {
+    let value = uint!(42_U256);
+    // CALLVALUE PUSH0 MSTORE PUSH1 0x20 PUSH0 RETURN
+    let provider = word_provider(bytes!("345f5260205ff3")).await;
+    // by default, the contract calls itself, so it must be able to pay the value
+    provider
+        .anvil_set_balance(WORD_CONTRACT, value)
+        .await
+        .unwrap();
+    provider.evm_mine(None).await.unwrap();
+
+    let result = common::eth_call(
+        provider,
+        WORD_CONTRACT,
+        IWord::wordCall {},
+        CallOptions::with_value(value),
+    )
+    .await;
+    assert_eq!(result._0, value);
+}
+
+#[test(tokio::test)]
+async fn gas_above_block_gas_limit() {
+    // the block gas limit of Anvil is 30M, calls must not be validated against it
+    let result = common::eth_call(
+        test_provider().await,
+        STEEL_TEST_CONTRACT,
+        SteelTest::testMuliContractCallsCall {},
+        CallOptions::with_gas(100_000_000),
+    )
+    .await;
+    assert_eq!(result._0, uint!(84_U256));
+}
+
+#[test(tokio::test)]
+async fn gas_limit_from_header() {
+    let gas_limit = 50_000_000;
+    // GAS PUSH0 MSTORE PUSH1 0x20 PUSH0 RETURN
+    let provider = word_provider(bytes!("5a5f5260205ff3")).await;
+    provider
+        .anvil_set_block_gas_limit(U256::from(gas_limit))
+        .await
+        .unwrap();
+    provider.evm_mine(None).await.unwrap();
+
+    // by default, the gas limit of the block must be used
+    let result = common::eth_call(
+        provider.clone(),
+        WORD_CONTRACT,
+        IWord::wordCall {},
+        CallOptions::new(),
+    )
+    .await;
+    let expected = common::eth_call(
+        provider,
+        WORD_CONTRACT,
+        IWord::wordCall {},
+        CallOptions::with_gas(gas_limit),
+    )
+    .await;
+    assert_eq!(result._0, expected._0);
+    assert!(result._0 > U256::from(30_000_000));
+}
+
+#[test(tokio::test)]
+async fn tuple_and_struct_returns() {
+    // PUSH1 0x2a PUSH0 MSTORE ADDRESS PUSH1 0x20 MSTORE PUSH1 0x40 PUSH0 RETURN
+    let provider = word_provider(bytes!("602a5f523060205260405ff3")).await;
+
+    let result = common::eth_call(
+        provider.clone(),
+        WORD_CONTRACT,
+        IPair::pairCall {},
+        CallOptions::new(),
+    )
+    .await;
+    assert_eq!(result.value, uint!(42_U256));
+    assert_eq!(result.owner, WORD_CONTRACT);
+
+    let result = common::eth_call(
+        provider,
+        WORD_CONTRACT,
+        IPair::pairStructCall {},
+        CallOptions::new(),
+    )
+    .await;
+    assert_eq!(
+        result.pair,
+        IPair::Pair {
+            value: uint!(42_U256),
+            owner: WORD_CONTRACT
+        }
+    );
+}
+
 #[test(tokio::test)]
 async fn load_empty_storage() {
     let result = common::eth_call(
@@ -295,6 +536,35 @@ async fn multi_contract_calls() {
     assert_eq!(result._0, uint!(84_U256));
 }
 
+#[test(tokio::test)]
+async fn call_with_code_hashes() {
+    let mut env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let call = SteelTest::testMuliContractCallsCall {};
+    Contract::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .call_builder(&call)
+        .call()
+        .await
+        .unwrap();
+    let input = env.into_input().await.unwrap();
+
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let (result, code_hashes) = Contract::new(STEEL_TEST_CONTRACT, &env)
+        .call_builder(&call)
+        .call_with_code_hashes();
+    assert_eq!(result._0, uint!(84_U256));
+    // the test contract and the Value contracts deployed by its constructor
+    let expected: BTreeMap<_, _> = std::iter::once(STEEL_TEST_CONTRACT)
+        .chain((1..=3).map(|nonce| STEEL_TEST_CONTRACT.create(nonce)))
+        .map(|address| (address, Account::new(address, &env).code_hash()))
+        .collect();
+    assert_eq!(code_hashes, expected);
+}
+
 #[test(tokio::test)]
 async fn call_eoa() {
     let mut env = EthEvmEnv::builder()
@@ -311,6 +581,135 @@ async fn call_eoa() {
         .expect_err("calling an EOA should fail");
 }
 
+#[test(tokio::test)]
+async fn verify_eip1186_proofs() {
+    let builder = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .verify_eip1186_proofs(true);
+    multi_contract_calls_input(builder)
+        .await
+        .expect("valid proofs should verify");
+}
+
+/// A [ProviderLayer] removing the last node of the `accountProof` of [STEEL_TEST_CONTRACT] from
+/// all `eth_getProof` responses.
+struct TruncateProofLayer;
+
+impl<P, T, N> ProviderLayer<P, T, N> for TruncateProofLayer
+where
+    P: Provider<T, N>,
+    T: Transport + Clone,
+    N: Network,
+{
+    type Provider = TruncateProof<P>;
+
+    fn layer(&self, inner: P) -> Self::Provider {
+        TruncateProof(inner)
+    }
+}
+
+struct TruncateProof<P>(P);
+
+impl<P, T, N> Provider<T, N> for TruncateProof<P>
+where
+    P: Provider<T, N>,
+    T: Transport + Clone,
+    N: Network,
+{
+    fn root(&self) -> &RootProvider<T, N> {
+        self.0.root()
+    }
+
+    fn get_proof(
+        &self,
+        address: Address,
+        keys: Vec<StorageKey>,
+    ) -> RpcWithBlock<T, (Address, Vec<StorageKey>), EIP1186AccountProofResponse> {
+        fn truncate(mut proof: EIP1186AccountProofResponse) -> EIP1186AccountProofResponse {
+            if proof.address == STEEL_TEST_CONTRACT {
+                proof.account_proof.pop();
+            }
+            proof
+        }
+        self.client()
+            .request("eth_getProof", (address, keys))
+            .map_resp(truncate as fn(_) -> _)
+            .into()
+    }
+}
+
+#[test(tokio::test)]
+async fn verify_eip1186_proofs_truncated() {
+    let provider = test_provider().await;
+    let transport = provider.client().transport().clone().boxed();
+    let truncating = ProviderBuilder::new()
+        .layer(TruncateProofLayer)
+        .on_client(RpcClient::new(transport, true));
+
+    let builder = EthEvmEnv::builder()
+        .provider(truncating)
+        .verify_eip1186_proofs(true);
+    let err = multi_contract_calls_input(builder).await.err().unwrap();
+    assert!(matches!(err, Error::Proof(_)));
+    // the error must name the account with the invalid proof
+    let message = format!("{:#}", anyhow::Error::from(err));
+    assert!(
+        message.contains(&STEEL_TEST_CONTRACT.to_string()),
+        "{}",
+        message
+    );
+}
+
+#[test(tokio::test)]
+async fn verify_determinism() {
+    let provider = test_provider().await;
+    let block_number = provider.get_block_number().await.unwrap();
+    let builder = EthEvmEnv::builder()
+        .provider(provider)
+        .block_number(block_number);
+
+    let checked = multi_contract_calls_input(builder.clone().verify_determinism(true))
+        .await
+        .expect("preflight should be deterministic");
+    // the check must not change the resulting input
+    let unchecked = multi_contract_calls_input(builder).await.unwrap();
+    assert_eq!(checked.to_bytes().unwrap(), unchecked.to_bytes().unwrap());
+}
+
+#[test(tokio::test)]
+async fn blob_base_fee() {
+    // BLOBBASEFEE PUSH0 MSTORE PUSH1 0x20 PUSH0 RETURN
+    let provider = word_provider(bytes!("4a5f5260205ff3")).await;
+    let header = provider
+        .get_block_by_number(BlockNumberOrTag::Latest, BlockTransactionsKind::Hashes)
+        .await
+        .unwrap()
+        .unwrap()
+        .header;
+    let expected = calc_blob_gasprice(header.excess_blob_gas.unwrap());
+
+    let result = common::eth_call(
+        provider,
+        WORD_CONTRACT,
+        IWord::wordCall {},
+        CallOptions::new(),
+    )
+    .await;
+    assert_eq!(result._0, U256::from(expected));
+}
+
+#[test(tokio::test)]
+async fn blob_base_fee_override() {
+    let result = common::eth_call(
+        word_provider(bytes!("4a5f5260205ff3")).await,
+        WORD_CONTRACT,
+        IWord::wordCall {},
+        CallOptions::with_blob_base_fee(42),
+    )
+    .await;
+    assert_eq!(result._0, uint!(42_U256));
+}
+
 #[test(tokio::test)]
 async fn no_preflight() {
     let env = EthEvmEnv::builder()
@@ -319,57 +718,1002 @@ async fn no_preflight() {
         .await
         .unwrap()
         .with_chain_spec(&ANVIL_CHAIN_SPEC);
-    match env.into_input().await {
-        Ok(_) => panic!("calling into_input without a preflight should fail"),
-        Err(err) => assert_eq!(
-            err.to_string(),
-            "no accounts accessed: use Contract::preflight"
-        ),
-    }
+    let block_hash = env.header().seal();
+    let block_number = env.header().number;
+
+    // without any state access, the input must still commit to the block hash
+    let input = env.into_input().await.unwrap();
+    assert_eq!(input.block_hash(), block_hash);
+    assert_eq!(input.block_number(), block_number);
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let commitment = env.commitment();
+    assert_eq!(commitment.digest, block_hash);
+    assert_eq!(commitment.id, U256::from(block_number));
 }
 
-alloy::sol!(
-    // docker run -i ethereum/solc:0.8.26 - --optimize --bin
-    #[sol(rpc, bytecode="60a0604052348015600e575f80fd5b5060405161012a38038061012a833981016040819052602b91604b565b60808190525f5b6080518110156045576001808255016032565b50506061565b5f60208284031215605a575f80fd5b5051919050565b60805160b46100765f395f6047015260b45ff3fe6080604052348015600e575f80fd5b50600436106026575f3560e01c8063380eb4e014602a575b5f80fd5b60306042565b60405190815260200160405180910390f35b5f805b7f0000000000000000000000000000000000000000000000000000000000000000811015607a57805491909101906001016045565b509056fea26469706673582212203687b75eefdd9cc7ceedb243aa360bd9e1b4cab1930149a371efef74ce18bdf164736f6c634300081a0033")]
-    #[derive(Debug, PartialEq, Eq)]
-    contract SlotsTest {
-        uint256 internal immutable N;
+#[test(tokio::test)]
+async fn verify_journal() {
+    let env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let input = env.into_input().await.unwrap();
+    let commitment = input
+        .clone()
+        .into_env()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC)
+        .into_commitment();
+    let config_id = ANVIL_CHAIN_SPEC.digest();
+    assert_eq!(input.commitment(config_id).unwrap(), commitment);
 
-        constructor(uint256 n) {
-            N = n;
-            for (uint256 i = 0; i < N; i++) {
-                assembly { sstore(i, 1) }
-            }
-        }
+    // the commitment can be followed by arbitrary data
+    let mut journal = commitment.abi_encode();
+    journal.extend_from_slice(&[0xff; 32]);
+    input.verify_journal(&journal, config_id).unwrap();
 
-        function sload() external view returns (uint256 sum) {
-            for (uint256 i = 0; i < N; i++) {
-                assembly { sum := add(sum, sload(i)) }
+    input
+        .verify_journal(&journal, ETH_MAINNET_CHAIN_SPEC.digest())
+        .expect_err("journal for a different chain spec must be rejected");
+    journal[32] ^= 0xff;
+    input
+        .verify_journal(&journal, config_id)
+        .expect_err("journal with a different block hash must be rejected");
+    input
+        .verify_journal(&journal[..64], config_id)
+        .expect_err("truncated journal must be rejected");
+}
+
+#[test(tokio::test)]
+async fn block_hash() {
+    let provider = test_provider().await;
+    let block = provider
+        .get_block_by_number(BlockNumberOrTag::Latest, BlockTransactionsKind::Hashes)
+        .await
+        .unwrap()
+        .unwrap();
+    let hash = block.header.hash;
+    provider.evm_mine(None).await.unwrap();
+
+    let mut env = EthEvmEnv::builder()
+        .provider(provider)
+        .block_hash(hash)
+        .build()
+        .await
+        .unwrap();
+    Contract::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .call_builder(&SteelTest::testMuliContractCallsCall {})
+        .call()
+        .await
+        .unwrap();
+    let input = env.into_input().await.unwrap();
+    assert_eq!(input.block_hash(), hash);
+    assert_eq!(input.block_number(), block.header.number);
+}
+
+#[test(tokio::test)]
+async fn block_hash_not_found() {
+    let result = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .block_hash(B256::repeat_byte(0x01))
+        .build()
+        .await;
+    assert!(matches!(result, Err(Error::BlockNotFound(_))));
+}
+
+/// Starts a JSON-RPC server over HTTP that responds to every request with `null`.
+fn null_rpc_server() -> Url {
+    fn handle(stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        loop {
+            // parse the headers up to the empty line
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line)? == 0 {
+                    return Ok(());
+                }
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
             }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+
+            let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let response =
+                serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": null })
+                    .to_string();
+            write!(
+                writer,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            )?;
         }
     }
-);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let _ = handle(stream.unwrap());
+        }
+    });
+
+    url.parse().unwrap()
+}
 
 #[test(tokio::test)]
-async fn prefetch_access_list() {
-    const NUM_SLOTS: U256 = uint!(1_250_U256);
+async fn unsupported_block_tag() {
+    let url = null_rpc_server();
+    for tag in [
+        host::BlockNumberOrTag::Safe,
+        host::BlockNumberOrTag::Finalized,
+    ] {
+        let result = EthEvmEnv::builder()
+            .rpc(url.clone())
+            .block_number_or_tag(tag)
+            .build()
+            .await;
+        assert!(matches!(result, Err(Error::UnsupportedBlockTag(_))));
+    }
+}
+
+/// A [ProviderLayer] counting the `eth_getProof` calls sent through it.
+struct ProofCounterLayer(Arc<AtomicUsize>);
+
+impl<P, T, N> ProviderLayer<P, T, N> for ProofCounterLayer
+where
+    P: Provider<T, N>,
+    T: Transport + Clone,
+    N: Network,
+{
+    type Provider = ProofCounter<P>;
+
+    fn layer(&self, inner: P) -> Self::Provider {
+        ProofCounter {
+            inner,
+            count: self.0.clone(),
+        }
+    }
+}
+
+struct ProofCounter<P> {
+    inner: P,
+    count: Arc<AtomicUsize>,
+}
 
+impl<P, T, N> Provider<T, N> for ProofCounter<P>
+where
+    P: Provider<T, N>,
+    T: Transport + Clone,
+    N: Network,
+{
+    fn root(&self) -> &RootProvider<T, N> {
+        self.inner.root()
+    }
+
+    fn get_proof(
+        &self,
+        address: Address,
+        keys: Vec<StorageKey>,
+    ) -> RpcWithBlock<T, (Address, Vec<StorageKey>), EIP1186AccountProofResponse> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        self.inner.get_proof(address, keys)
+    }
+}
+
+#[test(tokio::test)]
+async fn boxed_provider() {
     let provider = test_provider().await;
-    let instance = SlotsTest::deploy(&provider, NUM_SLOTS).await.unwrap();
-    let address = *instance.address();
-    let call = SlotsTest::sloadCall {};
+    // create a new provider using a type-erased transport to the same Anvil instance
+    let transport = provider.client().transport().clone().boxed();
+    let count = Arc::new(AtomicUsize::new(0));
+    let layered = ProviderBuilder::new()
+        .layer(ProofCounterLayer(count.clone()))
+        .on_client(RpcClient::new(transport, true));
 
-    let mut access_list = {
-        let tx = TransactionRequest::default()
-            .from(address)
-            .to(address)
-            .input(call.abi_encode().into());
-        let access_list_with_gas_used = provider.create_access_list(&tx).await.unwrap();
-        access_list_with_gas_used.access_list
-    };
-    // remove one storage proof from the access list
-    access_list.0.first_mut().unwrap().storage_keys.pop();
-    let options = CallOptions::with_access_list(access_list);
+    let mut env = EthEvmEnv::builder()
+        .boxed_provider(layered)
+        .chain_spec(&ANVIL_CHAIN_SPEC)
+        .build()
+        .await
+        .unwrap();
+    let result = Contract::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .call_builder(&SteelTest::testChainidCall {})
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result._0, uint!(31337_U256));
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+    env.into_input().await.unwrap();
+    assert!(
+        count.load(Ordering::SeqCst) > 0,
+        "the proofs must be fetched through the provider layer"
+    );
+}
 
-    common::eth_call(provider, address, call, options).await;
+#[test(tokio::test)]
+async fn from_db() {
+    let provider = test_provider().await;
+    let block = provider
+        .get_block_by_number(BlockNumberOrTag::Latest, BlockTransactionsKind::Hashes)
+        .await
+        .unwrap()
+        .unwrap();
+    let header = EthBlockHeader::try_from(block.header).unwrap().seal_slow();
+
+    let db = AlloyDb::from_provider(provider.clone(), B256::ZERO);
+    let err = EthEvmEnv::from_db(db, header.clone()).err().unwrap();
+    assert!(matches!(err, Error::HeaderMismatch(_)));
+
+    let db = AlloyDb::from_provider(provider, header.seal());
+    let mut env = EthEvmEnv::from_db(db, header.clone())
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let preflight = Contract::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .call_builder(&SteelTest::testChainidCall {})
+        .call()
+        .await
+        .unwrap();
+    let input = env.into_input().await.unwrap();
+    assert_eq!(input.block_hash(), header.seal());
+
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let result = Contract::new(STEEL_TEST_CONTRACT, &env)
+        .call_builder(&SteelTest::testChainidCall {})
+        .call();
+    assert_eq!(result._0, preflight._0);
+}
+
+#[test(tokio::test)]
+async fn builder_chain_spec() {
+    let env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .chain_spec(&ANVIL_CHAIN_SPEC)
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(env.spec_id(), SpecId::CANCUN);
+
+    // a chain spec without an active fork for the block must be rejected
+    let spec = ChainSpec {
+        chain_id: 31337,
+        forks: [(SpecId::CANCUN, ForkCondition::Timestamp(u64::MAX))].into(),
+    };
+    let result = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .chain_spec(&spec)
+        .build()
+        .await;
+    assert!(matches!(result, Err(Error::ChainSpec(_))));
+}
+
+#[test(tokio::test)]
+async fn account() {
+    let address = address!("000000000000000000000000000000000000ba1a");
+    let provider = test_provider().await;
+    provider
+        .anvil_set_balance(address, uint!(42_U256))
+        .await
+        .unwrap();
+    provider.evm_mine(None).await.unwrap();
+    let block_number = provider.get_block_number().await.unwrap();
+    // change the balance in a later block, which must not affect the proven historical balance
+    provider
+        .anvil_set_balance(address, uint!(43_U256))
+        .await
+        .unwrap();
+    provider.evm_mine(None).await.unwrap();
+    let code = provider.get_code_at(STEEL_TEST_CONTRACT).await.unwrap();
+
+    let mut env = EthEvmEnv::builder()
+        .provider(provider)
+        .block_number(block_number)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let balance = Account::preflight(address, &mut env)
+        .balance()
+        .await
+        .unwrap();
+    assert_eq!(balance, uint!(42_U256));
+    let mut contract = Account::preflight(STEEL_TEST_CONTRACT, &mut env);
+    let info = contract.info().await.unwrap();
+    // the nonce of a contract starts at 1 and SteelTest creates three contracts
+    assert_eq!(info.nonce, 4);
+    assert_eq!(info.code_hash, keccak256(&code));
+    let input = env.into_input().await.unwrap();
+
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    assert_eq!(Account::new(address, &env).balance(), balance);
+    let contract = Account::new(STEEL_TEST_CONTRACT, &env);
+    assert_eq!(contract.nonce(), info.nonce);
+    assert_eq!(contract.code_hash(), info.code_hash);
+}
+
+#[test(tokio::test)]
+async fn contract_checked() {
+    let mut env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let err = Contract::preflight_checked(Address::ZERO, &mut env)
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        format!("contract {} has no code", Address::ZERO)
+    );
+    let mut contract = Contract::preflight_checked(STEEL_TEST_CONTRACT, &mut env)
+        .await
+        .unwrap();
+    contract
+        .call_builder(&SteelTest::testChainidCall {})
+        .call()
+        .await
+        .unwrap();
+    let input = env.into_input().await.unwrap();
+
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let contract = Contract::new_checked(STEEL_TEST_CONTRACT, &env);
+    assert_ne!(contract.code_hash(), KECCAK_EMPTY);
+    assert_eq!(
+        contract.code_hash(),
+        Account::new(STEEL_TEST_CONTRACT, &env).code_hash()
+    );
+}
+
+#[test(tokio::test)]
+async fn account_storage() {
+    // the value of a Value contract is stored in slot 0
+    let value42 = STEEL_TEST_CONTRACT.create(2);
+    let empty_slot = uint!(1_U256);
+
+    let mut env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let mut account = Account::preflight(value42, &mut env);
+    assert_eq!(account.storage(U256::ZERO).await.unwrap(), uint!(42_U256));
+    assert_eq!(account.storage(empty_slot).await.unwrap(), U256::ZERO);
+    let input = env.into_input().await.unwrap();
+
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let account = Account::new(value42, &env);
+    assert_eq!(account.storage(U256::ZERO), uint!(42_U256));
+    assert_eq!(account.storage(empty_slot), U256::ZERO);
+}
+
+#[test(tokio::test)]
+async fn access_list() {
+    // the value of a Value contract is stored in slot 0
+    let value42 = STEEL_TEST_CONTRACT.create(2);
+    let access_list = AccessList(vec![AccessListItem {
+        address: value42,
+        storage_keys: vec![B256::ZERO],
+    }]);
+
+    let mut env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    Contract::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .call_builder(&SteelTest::testChainidCall {})
+        .access_list(access_list)
+        .await
+        .unwrap()
+        .call()
+        .await
+        .unwrap();
+    let input = env.into_input().await.unwrap();
+
+    // the listed state is available in the guest, even though the call never accessed it
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    assert_eq!(
+        Account::new(value42, &env).storage(U256::ZERO),
+        uint!(42_U256)
+    );
+}
+
+#[test(tokio::test)]
+async fn access_report() {
+    let value42 = STEEL_TEST_CONTRACT.create(2);
+
+    let mut env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    Account::preflight(value42, &mut env)
+        .storage(U256::ZERO)
+        .await
+        .unwrap();
+    let report = env.access_report();
+    assert_eq!(
+        report.accounts,
+        BTreeMap::from([(value42, BTreeSet::from([B256::ZERO]))])
+    );
+    assert!(report.code_hashes.is_empty());
+
+    let code_hash = Account::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .code_hash()
+        .await
+        .unwrap();
+    Contract::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .call_builder(&SteelTest::testChainidCall {})
+        .call()
+        .await
+        .unwrap();
+    let report = env.access_report();
+    assert!(report.accounts.contains_key(&STEEL_TEST_CONTRACT));
+    assert!(report.code_hashes.contains(&code_hash));
+}
+
+#[test(tokio::test)]
+async fn estimate() {
+    let mut env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let estimate = Contract::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .call_builder(&SteelTest::testMuliContractCallsCall {})
+        .estimate()
+        .await
+        .unwrap();
+    assert!(estimate.gas_used > 0);
+    // SteelTest and the three Value contracts are accessed, each Value reading one slot
+    assert!(estimate.accounts_touched >= 4);
+    assert_eq!(estimate.slots_touched, 3);
+
+    // the accessed state must be recorded like for a regular preflight call
+    let input = env.into_input().await.unwrap();
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let result = Contract::new(STEEL_TEST_CONTRACT, &env)
+        .call_builder(&SteelTest::testMuliContractCallsCall {})
+        .call();
+    assert_eq!(result._0, uint!(84_U256));
+}
+
+#[test(tokio::test)]
+async fn multicall() {
+    // the Value contracts are deployed by the constructor of SteelTest
+    let calls: Vec<_> = (1..=3)
+        .map(|nonce| (STEEL_TEST_CONTRACT.create(nonce), Value::valueCall {}))
+        .collect();
+
+    let mut env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let preflight_result = MultiCall::preflight(&mut env, calls.clone())
+        .call()
+        .await
+        .unwrap();
+    let input = env.into_input().await.unwrap();
+
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let result = MultiCall::new(&env, calls).call();
+
+    let values = |returns: Vec<Value::valueReturn>| returns.into_iter().map(|r| r.value).collect();
+    let preflight_values: Vec<U256> = values(preflight_result);
+    assert_eq!(preflight_values, values(result));
+    assert_eq!(
+        preflight_values,
+        [uint!(0_U256), uint!(42_U256), uint!(42_U256)]
+    );
+}
+
+#[test(tokio::test)]
+async fn into_input_with_results() {
+    let value_contract = STEEL_TEST_CONTRACT.create(2);
+
+    let mut env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    Contract::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .call_builder(&SteelTest::testChainidCall {})
+        .call()
+        .await
+        .unwrap();
+    MultiCall::preflight(&mut env, [(value_contract, Value::valueCall {})])
+        .call()
+        .await
+        .unwrap();
+    let (input, results) = env.into_input_with_results().await.unwrap();
+    assert_eq!(results.len(), 2);
+
+    // the recorded results must match the results computed in the guest
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let chain_id = Contract::new(STEEL_TEST_CONTRACT, &env)
+        .call_builder(&SteelTest::testChainidCall {})
+        .call();
+    let value = Contract::new(value_contract, &env)
+        .call_builder(&Value::valueCall {})
+        .call();
+    assert_eq!(
+        SteelTest::testChainidCall::abi_decode_returns(&results[0], true)
+            .unwrap()
+            ._0,
+        chain_id._0
+    );
+    assert_eq!(
+        Value::valueCall::abi_decode_returns(&results[1], true)
+            .unwrap()
+            .value,
+        value.value
+    );
+}
+
+/// Inspector counting the executed `SLOAD` instructions.
+#[derive(Default)]
+struct SloadCounter(usize);
+
+impl<DB: revm::Database> revm::Inspector<DB> for SloadCounter {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut revm::EvmContext<DB>) {
+        if interp.current_opcode() == opcode::SLOAD {
+            self.0 += 1;
+        }
+    }
+}
+
+#[test(tokio::test)]
+async fn call_with_inspector() {
+    let provider = test_provider().await;
+    let builder = EthEvmEnv::builder()
+        .provider(provider)
+        .chain_spec(&ANVIL_CHAIN_SPEC);
+
+    let mut env = builder.clone().build().await.unwrap();
+    let (result, counter) = Contract::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .call_builder(&SteelTest::testMuliContractCallsCall {})
+        .call_with_inspector(SloadCounter::default())
+        .await
+        .unwrap();
+    assert_eq!(result._0, uint!(84_U256));
+    // each of the three Value contracts loads its value from storage
+    assert!(counter.0 >= 3);
+    let inspected_input = env.into_input().await.unwrap();
+
+    // the inspector must not change the resulting input
+    let mut env = builder.build().await.unwrap();
+    Contract::preflight(STEEL_TEST_CONTRACT, &mut env)
+        .call_builder(&SteelTest::testMuliContractCallsCall {})
+        .call()
+        .await
+        .unwrap();
+    let input = env.into_input().await.unwrap();
+    assert_eq!(
+        inspected_input.to_bytes().unwrap(),
+        input.to_bytes().unwrap()
+    );
+}
+
+#[test(tokio::test)]
+async fn call_concurrently() {
+    let calls: Vec<_> = (1..=3)
+        .map(|nonce| (STEEL_TEST_CONTRACT.create(nonce), Value::valueCall {}))
+        .collect();
+
+    let (input, returns) = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .chain_spec(&ANVIL_CHAIN_SPEC)
+        .call_concurrently(calls.clone())
+        .await
+        .unwrap();
+    let values: Vec<U256> = returns.into_iter().map(|r| r.value).collect();
+    assert_eq!(values, [uint!(0_U256), uint!(42_U256), uint!(42_U256)]);
+
+    // all calls must be executable on the merged input
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    for ((address, call), value) in calls.into_iter().zip(values) {
+        let result = Contract::new(address, &env).call_builder(&call).call();
+        assert_eq!(result.value, value);
+    }
+}
+
+#[test(tokio::test)]
+async fn call_concurrently_errors() {
+    let builder = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .chain_spec(&ANVIL_CHAIN_SPEC);
+
+    let result = builder
+        .clone()
+        .call_concurrently(Vec::<(Address, Value::valueCall)>::new())
+        .await;
+    assert!(matches!(result, Err(Error::NoCalls)));
+
+    // calling an account without code returns no data, which cannot be decoded
+    let calls = [
+        (STEEL_TEST_CONTRACT.create(1), Value::valueCall {}),
+        (Address::repeat_byte(0xde), Value::valueCall {}),
+    ];
+    let result = builder.call_concurrently(calls).await;
+    assert!(matches!(result, Err(Error::Call(_, _))));
+}
+
+#[test(tokio::test)]
+async fn call_concurrently_matches_sequential() {
+    let provider = test_provider().await;
+    let block_number = provider.get_block_number().await.unwrap();
+    let calls: Vec<_> = (1..=3)
+        .map(|nonce| (STEEL_TEST_CONTRACT.create(nonce), Value::valueCall {}))
+        .collect();
+
+    let builder = EthEvmEnv::builder()
+        .provider(provider)
+        .block_number(block_number)
+        .chain_spec(&ANVIL_CHAIN_SPEC);
+    let (concurrent, _) = builder
+        .clone()
+        .call_concurrently(calls.clone())
+        .await
+        .unwrap();
+
+    // preflight the same calls one after another in a single environment
+    let mut env = builder.build().await.unwrap();
+    for (address, call) in &calls {
+        Contract::preflight(*address, &mut env)
+            .call_builder(call)
+            .call()
+            .await
+            .unwrap();
+    }
+    let sequential = env.into_input().await.unwrap();
+
+    assert_eq!(
+        concurrent.to_bytes().unwrap(),
+        sequential.to_bytes().unwrap()
+    );
+}
+
+#[test(tokio::test)]
+async fn call_concurrently_deterministic() {
+    let provider = test_provider().await;
+    let block_number = provider.get_block_number().await.unwrap();
+    let mut calls: Vec<_> = (1..=3)
+        .map(|nonce| (STEEL_TEST_CONTRACT.create(nonce), Value::valueCall {}))
+        .collect();
+
+    let builder = EthEvmEnv::builder()
+        .provider(provider)
+        .block_number(block_number)
+        .chain_spec(&ANVIL_CHAIN_SPEC);
+    // repeat the preflight for every rotation of the calls, so that the inputs are merged in a
+    // different order and the preflights complete in a different order each time
+    let mut inputs = Vec::new();
+    for _ in 0..2 * calls.len() {
+        let (input, _) = builder
+            .clone()
+            .call_concurrently(calls.clone())
+            .await
+            .unwrap();
+        inputs.push(input.to_bytes().unwrap());
+        calls.rotate_left(1);
+    }
+    assert!(inputs.windows(2).all(|w| w[0] == w[1]));
+}
+
+#[test(tokio::test)]
+async fn batch_requests() {
+    let calls: Vec<_> = (1..=3)
+        .map(|nonce| (STEEL_TEST_CONTRACT.create(nonce), Value::valueCall {}))
+        .collect();
+
+    let mut env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .batch_requests(true)
+        .eip1186_proof_batch_size(2)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    MultiCall::preflight(&mut env, calls.clone())
+        .call()
+        .await
+        .unwrap();
+    let input = env.into_input().await.unwrap();
+
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let values: Vec<_> = MultiCall::new(&env, calls)
+        .call()
+        .into_iter()
+        .map(|r| r.value)
+        .collect();
+    assert_eq!(values, [uint!(0_U256), uint!(42_U256), uint!(42_U256)]);
+}
+
+#[test(tokio::test)]
+async fn preflight_batch() {
+    let value_contracts: Vec<_> = (1..=3)
+        .map(|nonce| STEEL_TEST_CONTRACT.create(nonce))
+        .collect();
+
+    let mut env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let mut batch = env
+        .preflight_batch()
+        .add(STEEL_TEST_CONTRACT, [SteelTest::testChainidCall {}])
+        .add(value_contracts[1], [Value::valueCall {}]);
+    for address in [value_contracts[0], value_contracts[2]] {
+        batch = batch.add(address, [Value::valueCall {}]);
+    }
+    assert_eq!(batch.len(), 4);
+    let returns = batch.call().await.unwrap();
+    assert_eq!(returns.len(), 4);
+    let value = Value::valueCall::abi_decode_returns(&returns[1], true).unwrap();
+    assert_eq!(value.value, uint!(42_U256));
+
+    // all calls must be executable in the guest using the single input
+    let input = env.into_input().await.unwrap();
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let result = Contract::new(STEEL_TEST_CONTRACT, &env)
+        .call_builder(&SteelTest::testChainidCall {})
+        .call();
+    assert_eq!(result._0, uint!(31337_U256));
+    let result = MultiCall::new(
+        &env,
+        value_contracts
+            .iter()
+            .map(|&address| (address, Value::valueCall {})),
+    )
+    .call();
+    let values: Vec<U256> = result.into_iter().map(|r| r.value).collect();
+    assert_eq!(values, [uint!(0_U256), uint!(42_U256), uint!(42_U256)]);
+}
+
+#[test(tokio::test)]
+async fn preflight_batch_invalid_return() {
+    let mut env = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    // calling the zero address returns no data, which cannot be decoded
+    env.preflight_batch()
+        .add(Address::ZERO, [Value::valueCall {}])
+        .call()
+        .await
+        .expect_err("calling an EOA should fail");
+}
+
+#[test(tokio::test)]
+async fn deterministic_input() {
+    let provider = test_provider().await;
+    let block_number = provider.get_block_number().await.unwrap();
+    let builder = EthEvmEnv::builder()
+        .provider(provider)
+        .block_number(block_number);
+
+    let mut inputs = Vec::new();
+    for _ in 0..2 {
+        let input = multi_contract_calls_input(builder.clone()).await.unwrap();
+        inputs.push(input.to_bytes().unwrap());
+    }
+    assert_eq!(inputs[0], inputs[1]);
+
+    // storage tries and contracts must be ordered by their hash
+    let input = multi_contract_calls_input(builder).await.unwrap();
+    let parts = EvmInputParts::try_from(input).unwrap();
+    let trie_hashes: Vec<_> = parts.storage_tries.iter().map(|t| t.hash_slow()).collect();
+    assert!(trie_hashes.windows(2).all(|w| w[0] < w[1]));
+    let code_hashes: Vec<_> = parts.contracts.iter().map(keccak256).collect();
+    assert!(!code_hashes.is_empty() && code_hashes.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test(tokio::test)]
+async fn on_progress() {
+    let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::<ProofProgress>::new()));
+    let builder = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .on_progress({
+            let reports = reports.clone();
+            move |progress| reports.lock().unwrap().push(progress)
+        });
+    multi_contract_calls_input(builder).await.unwrap();
+
+    let reports = reports.lock().unwrap();
+    let (first, last) = (reports.first().unwrap(), reports.last().unwrap());
+    // one initial report and one for each fetched account
+    assert_eq!(reports.len(), last.accounts_total + 1);
+    assert_eq!((first.accounts_fetched, first.slots_fetched), (0, 0));
+    // at least SteelTest and the three Value contracts are accessed
+    assert!(last.accounts_total >= 4);
+    assert_eq!(last.accounts_fetched, last.accounts_total);
+    assert_eq!(last.slots_fetched, last.slots_total);
+}
+
+#[test(tokio::test)]
+async fn input_file_roundtrip() {
+    let builder = EthEvmEnv::builder().provider(test_provider().await);
+    let input = multi_contract_calls_input(builder).await.unwrap();
+
+    let path = std::env::temp_dir().join(format!("steel-input-{}.json", std::process::id()));
+    input.to_file(&path).unwrap();
+    let loaded = EthEvmInput::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        EthEvmInput::from_bytes(&input.to_bytes().unwrap())
+            .unwrap()
+            .to_bytes()
+            .unwrap(),
+        input.to_bytes().unwrap()
+    );
+
+    let commitment = |input: EthEvmInput| {
+        input
+            .into_env()
+            .with_chain_spec(&ANVIL_CHAIN_SPEC)
+            .into_commitment()
+    };
+    assert_eq!(commitment(loaded), commitment(input));
+}
+
+#[test(tokio::test)]
+async fn input_format_version() {
+    let builder = EthEvmEnv::builder().provider(test_provider().await);
+    let input = multi_contract_calls_input(builder).await.unwrap();
+
+    let mut value: serde_json::Value = serde_json::from_slice(&input.to_bytes().unwrap()).unwrap();
+    assert_eq!(value[0], serde_json::json!(INPUT_FORMAT_VERSION));
+
+    // inputs without a version must be rejected
+    let unversioned = serde_json::to_vec(&value[1]).unwrap();
+    assert!(EthEvmInput::from_bytes(&unversioned).is_err());
+
+    value[0] = serde_json::json!(u32::MAX);
+    let err = EthEvmInput::from_bytes(&serde_json::to_vec(&value).unwrap())
+        .err()
+        .unwrap();
+    assert!(format!("{:#}", err).contains("unsupported input format version"));
+}
+
+#[test(tokio::test)]
+async fn input_encoded_len() {
+    let builder = EthEvmEnv::builder()
+        .provider(test_provider().await)
+        .input_size_warning(1);
+    let input = multi_contract_calls_input(builder).await.unwrap();
+
+    // the size must match the encoding used to write the input into the `ExecutorEnv`
+    let words = risc0_zkvm::serde::to_vec(&input).unwrap();
+    assert_eq!(input.encoded_len(), words.len() * size_of::<u32>());
+}
+
+alloy::sol!(
+    // docker run -i ethereum/solc:0.8.26 - --optimize --bin
+    #[sol(rpc, bytecode="60a0604052348015600e575f80fd5b5060405161012a38038061012a833981016040819052602b91604b565b60808190525f5b6080518110156045576001808255016032565b50506061565b5f60208284031215605a575f80fd5b5051919050565b60805160b46100765f395f6047015260b45ff3fe6080604052348015600e575f80fd5b50600436106026575f3560e01c8063380eb4e014602a575b5f80fd5b60306042565b60405190815260200160405180910390f35b5f805b7f0000000000000000000000000000000000000000000000000000000000000000811015607a57805491909101906001016045565b509056fea26469706673582212203687b75eefdd9cc7ceedb243aa360bd9e1b4cab1930149a371efef74ce18bdf164736f6c634300081a0033")]
+    #[derive(Debug, PartialEq, Eq)]
+    contract SlotsTest {
+        uint256 internal immutable N;
+
+        constructor(uint256 n) {
+            N = n;
+            for (uint256 i = 0; i < N; i++) {
+                assembly { sstore(i, 1) }
+            }
+        }
+
+        function sload() external view returns (uint256 sum) {
+            for (uint256 i = 0; i < N; i++) {
+                assembly { sum := add(sum, sload(i)) }
+            }
+        }
+    }
+);
+
+#[test(tokio::test)]
+async fn prefetch_access_list() {
+    const NUM_SLOTS: U256 = uint!(1_250_U256);
+
+    let provider = test_provider().await;
+    let instance = SlotsTest::deploy(&provider, NUM_SLOTS).await.unwrap();
+    let address = *instance.address();
+    let call = SlotsTest::sloadCall {};
+
+    let mut access_list = {
+        let tx = TransactionRequest::default()
+            .from(address)
+            .to(address)
+            .input(call.abi_encode().into());
+        let access_list_with_gas_used = provider.create_access_list(&tx).await.unwrap();
+        access_list_with_gas_used.access_list
+    };
+    // remove one storage proof from the access list
+    access_list.0.first_mut().unwrap().storage_keys.pop();
+    let options = CallOptions::with_access_list(access_list);
+
+    common::eth_call(provider, address, call, options).await;
+}
+
+#[test(tokio::test)]
+async fn auto_access_list() {
+    const NUM_SLOTS: U256 = uint!(10_U256);
+
+    let provider = test_provider().await;
+    let instance = SlotsTest::deploy(&provider, NUM_SLOTS).await.unwrap();
+    let address = *instance.address();
+    let call = SlotsTest::sloadCall {};
+
+    let mut env = EthEvmEnv::builder()
+        .provider(provider)
+        .auto_access_list(true)
+        .build()
+        .await
+        .unwrap()
+        .with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let preflight = Contract::preflight(address, &mut env)
+        .call_builder(&call)
+        .call()
+        .await
+        .unwrap();
+    let input = env.into_input().await.unwrap();
+
+    let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+    let result = Contract::new(address, &env).call_builder(&call).call();
+    assert_eq!(result.sum, preflight.sum);
+}
+
+#[test(tokio::test)]
+async fn call_many() {
+    let provider = test_provider().await;
+    let deploy_block = provider.get_block_number().await.unwrap();
+    // mine another block
+    SlotsTest::deploy(&provider, uint!(1_U256)).await.unwrap();
+
+    let call = SteelTest::testChainidCall {};
+    let inputs = EthEvmEnv::builder()
+        .provider(provider)
+        .chain_spec(&ANVIL_CHAIN_SPEC)
+        .call_many(
+            STEEL_TEST_CONTRACT,
+            &call,
+            [
+                host::BlockNumberOrTag::Latest,
+                host::BlockNumberOrTag::Number(deploy_block),
+            ],
+        )
+        .await
+        .unwrap();
+
+    let numbers: Vec<_> = inputs.iter().map(|input| input.block_number()).collect();
+    assert_eq!(numbers, [deploy_block + 1, deploy_block]);
+    for input in inputs {
+        let env = input.into_env().with_chain_spec(&ANVIL_CHAIN_SPEC);
+        let result = Contract::new(STEEL_TEST_CONTRACT, &env)
+            .call_builder(&call)
+            .call();
+        assert_eq!(result._0, uint!(31337_U256));
+    }
 }